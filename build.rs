@@ -2,6 +2,10 @@ fn main() {
     let version = env!("CARGO_PKG_VERSION");
     println!("cargo:rustc-env=APP_VERSION={}", version);
 
+    if std::env::var("CARGO_CFG_TARGET_OS").as_deref() != Ok("windows") {
+        return;
+    }
+
     let mut res = winres::WindowsResource::new();
     res.set_icon("res/favicon.ico");
     res.set("ProductName", "YuyuBot");
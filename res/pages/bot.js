@@ -1,52 +1,169 @@
+const createBotConfig = (id) => ({
+  id,
+  name: '',
+  host: 'localhost',
+  apiPort: 3010,
+  eventPort: 3011,
+  token: '',
+  auto_connect: false,
+  eventTransport: 'sse',
+  keepEventStream: true,
+  scheme: 'http',
+  basePath: '',
+  tls: { caCertPath: '', skipVerify: false }
+});
+
 const BotPage = {
-  props: ['botConfig'],
+  props: ['botState'],
   data() {
     return {
       loading: false,
       message: '',
-      connected: false,
-      connecting: false,
-      connectionStatus: '未连接',
       statusEventSource: null
     };
   },
+  computed: {
+    accounts() {
+      return this.botState.accounts;
+    },
+    current() {
+      return this.accounts.find(account => account.config.id === this.botState.selectedId) || null;
+    },
+    botConfig() {
+      return this.current ? this.current.config : createBotConfig('');
+    },
+    connected() {
+      return !!this.current && this.current.connected;
+    },
+    connecting() {
+      return !!this.current && this.current.connecting;
+    },
+    connectionStatus() {
+      if (this.connected) {
+        const info = this.current.loginInfo;
+        return info ? `已连接：${info.nickname}(${info.uin})` : '已连接';
+      }
+      return this.connecting ? '正在连接...' : '未连接';
+    }
+  },
   methods: {
-    async loadConfig() {
+    async loadAccounts() {
       try {
-        const response = await fetch('/api/bot/get_config');
+        const response = await fetch('/api/bot/list');
         const result = await response.json();
         if (result.retcode === 0) {
-          Object.assign(this.botConfig, result.data);
+          // 保留尚未保存的新账号
+          const unsaved = this.accounts.filter(account => !account.saved);
+          const loaded = result.data.map(item => ({
+            config: Object.assign(createBotConfig(item.config.id), item.config, {
+              token: item.config.token || '',
+              basePath: item.config.basePath || '',
+              tls: Object.assign({ caCertPath: '', skipVerify: false }, item.config.tls, {
+                caCertPath: (item.config.tls && item.config.tls.caCertPath) || ''
+              })
+            }),
+            connected: item.connected,
+            connecting: item.connecting,
+            loginInfo: item.loginInfo,
+            saved: true
+          }));
+          this.botState.accounts = loaded.concat(unsaved.filter(account => !loaded.some(item => item.config.id === account.config.id)));
+          if (!this.current && this.accounts.length > 0) {
+            this.botState.selectedId = this.accounts[0].config.id;
+          }
         }
       } catch (error) {
-        console.error('Failed to load config:', error);
+        console.error('Failed to load bot accounts:', error);
       }
     },
-    async loadConnectionStatus() {
-      try {
-        const response = await fetch('/api/bot/get_status');
-        const result = await response.json();
-        if (result.retcode === 0) {
-          this.updateStatus(result.data);
+
+    selectAccount(id) {
+      this.botState.selectedId = id;
+      this.message = '';
+    },
+
+    addAccount() {
+      let index = this.accounts.length + 1;
+      while (this.accounts.some(account => account.config.id === `bot${index}`)) {
+        index++;
+      }
+      const id = `bot${index}`;
+      this.botState.accounts.push({
+        config: createBotConfig(id),
+        connected: false,
+        connecting: false,
+        loginInfo: null,
+        saved: false
+      });
+      this.selectAccount(id);
+    },
+
+    async deleteAccount() {
+      if (!this.current) {
+        return;
+      }
+      const id = this.current.config.id;
+      if (!confirm(`确定删除账号 ${this.accountLabel(this.current)} 吗？`)) {
+        return;
+      }
+
+      if (this.current.saved) {
+        this.loading = true;
+        try {
+          const response = await fetch(`/api/bot/delete?id=${encodeURIComponent(id)}`, { method: 'POST' });
+          const result = await response.json();
+          if (result.retcode !== 0) {
+            this.message = '删除失败: ' + result.data;
+            return;
+          }
+        } catch (error) {
+          this.message = '操作失败: ' + error.message;
+          return;
+        } finally {
+          this.loading = false;
         }
-      } catch (error) {
-        console.error('Failed to load connection status:', error);
       }
+
+      this.botState.accounts = this.accounts.filter(account => account.config.id !== id);
+      this.botState.selectedId = this.accounts.length > 0 ? this.accounts[0].config.id : '';
+      this.$root.refreshUserAvatar();
     },
-    
-    updateStatus(status) {
-      this.connected = status.connected;
-      this.connecting = status.connecting;
 
-      if (status.connected) {
-        this.connectionStatus = '已连接';
-      } else {
-        // 未连接或正在连接时都清除头像
-        this.connectionStatus = status.connecting ? '正在连接...' : '未连接';
-        this.$root.clearUserAvatar();
+    renameAccount(value) {
+      const id = value.trim();
+      this.current.config.id = id;
+      this.botState.selectedId = id;
+    },
+
+    accountLabel(account) {
+      return account.config.name || account.config.id;
+    },
+
+    accountStatusText(account) {
+      if (account.connected) {
+        return account.loginInfo ? `已连接 · ${account.loginInfo.uin}` : '已连接';
+      }
+      if (account.connecting) {
+        return '正在连接...';
+      }
+      return account.saved ? '未连接' : '未保存';
+    },
+
+    async updateStatus(status) {
+      const account = this.accounts.find(item => item.config.id === status.id);
+      if (!account) {
+        return;
+      }
+      const wasConnected = account.connected;
+      account.connected = status.connected;
+      account.connecting = status.connecting;
+
+      if (status.connected && !wasConnected) {
+        await this.loadAccounts();
+      } else if (!status.connected) {
+        account.loginInfo = null;
       }
     },
-    
 
     connectStatusSSE() {
       if (this.statusEventSource) {
@@ -84,25 +201,29 @@ const BotPage = {
 
     
     async toggleConnection() {
+      if (!this.current) {
+        return;
+      }
+      const account = this.current;
+      const id = encodeURIComponent(account.config.id);
       this.loading = true;
       this.message = '';
       
       try {
         if (this.connected) {
           // 断开连接
-          const response = await fetch('/api/bot/disconnect', { method: 'POST' });
+          const response = await fetch(`/api/bot/disconnect?id=${id}`, { method: 'POST' });
           const result = await response.json();
           if (result.retcode === 0) {
-            this.connected = false;
-            this.connecting = false;
-            this.connectionStatus = '未连接';
+            account.connected = false;
+            account.connecting = false;
             this.message = '已断开连接';
           } else {
             this.message = '断开失败: ' + result.data;
           }
         } else if (this.connecting) {
           // 如果正在连接，点击按钮取消连接
-          const response = await fetch('/api/bot/disconnect', { method: 'POST' });
+          const response = await fetch(`/api/bot/disconnect?id=${id}`, { method: 'POST' });
           const result = await response.json();
           if (result.retcode === 0) {
             this.message = '已取消连接';
@@ -116,10 +237,11 @@ const BotPage = {
             headers: {
               'Content-Type': 'application/json'
             },
-            body: JSON.stringify(this.botConfig)
+            body: JSON.stringify(account.config)
           });
           const result = await response.json();
           if (result.retcode === 0) {
+            account.saved = true;
             this.message = '开始连接...';
             setTimeout(() => { this.message = ''; }, 2000);
           } else {
@@ -160,8 +282,7 @@ const BotPage = {
     }
   },
   mounted() {
-    this.loadConfig();
-    this.loadConnectionStatus();
+    this.loadAccounts();
     this.connectStatusSSE();
   },
   
@@ -178,24 +299,65 @@ const BotPage = {
         <h1>Bot配置</h1>
       </div>
       <div class="card">
+        <div class="card-title"><svg viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2"><path d="M17 21v-2a4 4 0 0 0-4-4H5a4 4 0 0 0-4 4v2"></path><circle cx="9" cy="7" r="4"></circle><path d="M23 21v-2a4 4 0 0 0-3-3.87"></path><path d="M16 3.13a4 4 0 0 1 0 7.75"></path></svg>账号</div>
+        <div class="text-muted">每个账号独立连接一个 Milky 服务端。插件默认可以使用全部账号，可在插件页面绑定指定账号。</div>
+        <div class="group-options-grid">
+          <button
+            v-for="account in accounts"
+            :key="account.config.id"
+            type="button"
+            class="group-option-card"
+            :class="{ active: account.config.id === botState.selectedId }"
+            @click="selectAccount(account.config.id)"
+          >
+            <span class="group-option-name">{{ accountLabel(account) }}</span>
+            <span class="group-option-meta">ID {{ account.config.id }}</span>
+            <span class="group-option-meta">{{ accountStatusText(account) }}</span>
+          </button>
+        </div>
+        <div class="btn-center">
+          <button class="btn-clear" @click="addAccount" :disabled="loading">新增账号</button>
+        </div>
+      </div>
+      <div class="card" v-if="current">
         <div class="card-title"><svg viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2"><circle cx="12" cy="12" r="10"></circle><line x1="2" y1="12" x2="22" y2="12"></line><path d="M12 2a15.3 15.3 0 0 1 4 10 15.3 15.3 0 0 1-4 10 15.3 15.3 0 0 1-4-10 15.3 15.3 0 0 1 4-10z"></path></svg>连接设置</div>
         <div style="padding: 12px 15px; margin-bottom: 15px; background: var(--hover-bg); border-radius: 8px; font-size: 13px; color: var(--text-secondary);">
           <svg viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2" style="width: 16px; height: 16px; display: inline-block; vertical-align: middle; margin-right: 6px;"><circle cx="12" cy="12" r="10"></circle><line x1="12" y1="16" x2="12" y2="12"></line><line x1="12" y1="8" x2="12.01" y2="8"></line></svg>
           使用 <a href="https://milky.ntqqrev.org/" target="_blank" style="color: var(--accent-color); text-decoration: none;">Milky 协议</a> 进行通信
         </div>
         <div class="config-form">
+          <div class="form-group"><label>账号 ID</label><input type="text" :value="botConfig.id" @input="renameAccount($event.target.value)" placeholder="字母、数字、- 或 _" :disabled="current.saved"></div>
+
+          <div class="form-group"><label>名称 (可选)</label><input type="text" v-model="botConfig.name" placeholder="在 WebUI 中显示的名称" :disabled="isInputDisabled()"></div>
+
+          <div class="form-group"><label>协议</label><select v-model="botConfig.scheme" :disabled="isInputDisabled()"><option value="http">HTTP / WS</option><option value="https">HTTPS / WSS</option></select></div>
+
           <div class="form-group"><label>Host</label><input type="text" v-model="botConfig.host" placeholder="localhost" :disabled="isInputDisabled()"></div>
           
           <div class="form-group"><label>API 端口</label><input type="number" v-model.number="botConfig.apiPort" placeholder="3010" :disabled="isInputDisabled()"></div>
           
           <div class="form-group"><label>Event 端口</label><input type="number" v-model.number="botConfig.eventPort" placeholder="3011" :disabled="isInputDisabled()"></div>
 
+          <div class="form-group"><label>路径前缀 (可选)</label><input type="text" v-model="botConfig.basePath" placeholder="如 /milky，留空则直接访问 /api 与 /event" :disabled="isInputDisabled()"></div>
+
+          <template v-if="botConfig.scheme === 'https'">
+            <div class="form-group"><label>CA 证书 (可选)</label><input type="text" v-model="botConfig.tls.caCertPath" placeholder="PEM 文件路径，用于自签名证书" :disabled="isInputDisabled()"></div>
+
+            <div class="form-group"><label class="toggle-switch"><input type="checkbox" v-model="botConfig.tls.skipVerify" :disabled="isInputDisabled()"><span class="toggle-slider"></span><span class="toggle-label">跳过证书校验（不安全，仅用于调试）</span></label></div>
+          </template>
+
+          <div class="form-group"><label>事件传输方式</label><select v-model="botConfig.eventTransport" :disabled="isInputDisabled()"><option value="sse">SSE (HTTP)</option><option value="websocket">WebSocket</option></select></div>
+
+          <div class="form-group"><label class="toggle-switch"><input type="checkbox" v-model="botConfig.keepEventStream" :disabled="isInputDisabled()"><span class="toggle-slider"></span><span class="toggle-label">没有插件订阅时也缓冲事件，供插件重连后补发</span></label></div>
+
           <div class="form-group"><label>Token (可选)</label><input type="password" v-model="botConfig.token" placeholder="共用于 API 和 Event" :disabled="isInputDisabled()"></div>
           
           <div class="btn-center">
 
             <button :class="getButtonClass()" @click="toggleConnection" :disabled="loading">{{ getButtonText() }}</button>
+            <button class="btn-danger" style="margin-left: 12px;" @click="deleteAccount" :disabled="loading || isInputDisabled()">删除账号</button>
           </div>
+          <div class="text-muted" style="text-align: center;">{{ connectionStatus }}</div>
           <div v-if="message" style="margin-top: 12px; padding: 10px; border-radius: 4px; text-align: center; background: var(--hover-bg); color: var(--text-secondary);">{{ message }}</div>
         </div>
       </div>
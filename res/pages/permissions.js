@@ -13,8 +13,15 @@ const PermissionsPage = {
       config: {
         mode: 'blacklist',
         blacklistGroups: [],
-        whitelistGroups: []
+        whitelistGroups: [],
+        userMode: 'blacklist',
+        blacklistUsers: [],
+        whitelistUsers: [],
+        pluginOverrides: {}
       },
+      userInput: '',
+      scope: '',
+      plugins: [],
       groupOptions: [],
       groupOptionsConnected: false,
       groupOptionsMessage: '',
@@ -25,10 +32,34 @@ const PermissionsPage = {
     pageBusy() {
       return this.loading || this.loadingGroups;
     },
+    hasOverride() {
+      return !!this.scope && !!this.config.pluginOverrides[this.scope];
+    },
+    activeRule() {
+      return this.hasOverride ? this.config.pluginOverrides[this.scope] : this.config;
+    },
+    ruleLocked() {
+      return !!this.scope && !this.hasOverride;
+    },
+    scopeOptions() {
+      const options = this.plugins.map(plugin => ({ id: plugin.id, name: plugin.name }));
+      const known = new Set(options.map(option => option.id));
+      Object.keys(this.config.pluginOverrides).forEach(pluginId => {
+        if (!known.has(pluginId)) {
+          options.push({ id: pluginId, name: pluginId });
+        }
+      });
+      return options;
+    },
     currentGroups() {
-      return this.config.mode === 'blacklist'
-        ? this.config.blacklistGroups
-        : this.config.whitelistGroups;
+      return this.activeRule.mode === 'blacklist'
+        ? this.activeRule.blacklistGroups
+        : this.activeRule.whitelistGroups;
+    },
+    currentUsers() {
+      return this.activeRule.userMode === 'whitelist'
+        ? this.activeRule.whitelistUsers
+        : this.activeRule.blacklistUsers;
     },
     filteredGroupOptions() {
       const keyword = (this.groupFilter || '').trim().toLowerCase();
@@ -64,7 +95,36 @@ const PermissionsPage = {
       }
     },
     async loadPageData() {
-      await Promise.all([this.loadConfig(), this.loadGroupOptions()]);
+      await Promise.all([this.loadConfig(), this.loadGroupOptions(), this.loadPlugins()]);
+    },
+    async loadPlugins() {
+      try {
+        const response = await fetch('/api/plugins/list');
+        const result = await response.json();
+
+        if (result.retcode === 0) {
+          this.plugins = (result.data || []).map(plugin => ({ id: plugin.id, name: plugin.name || plugin.id }));
+        }
+      } catch (error) {
+        console.error('Failed to load plugins:', error);
+      }
+    },
+    normalizeRule(rule) {
+      return {
+        mode: (rule && rule.mode) || 'blacklist',
+        blacklistGroups: this.normalizeGroups(rule && rule.blacklistGroups),
+        whitelistGroups: this.normalizeGroups(rule && rule.whitelistGroups),
+        userMode: (rule && rule.userMode) || 'blacklist',
+        blacklistUsers: this.normalizeGroups(rule && rule.blacklistUsers),
+        whitelistUsers: this.normalizeGroups(rule && rule.whitelistUsers)
+      };
+    },
+    normalizeOverrides(overrides) {
+      const result = {};
+      Object.keys(overrides || {}).sort().forEach(pluginId => {
+        result[pluginId] = this.normalizeRule(overrides[pluginId]);
+      });
+      return result;
     },
     normalizeGroups(groups) {
       return [...new Set((groups || []).map(Number).filter(Number.isSafeInteger))]
@@ -75,7 +135,11 @@ const PermissionsPage = {
       return {
         mode: this.config.mode,
         blacklistGroups: this.normalizeGroups(this.config.blacklistGroups),
-        whitelistGroups: this.normalizeGroups(this.config.whitelistGroups)
+        whitelistGroups: this.normalizeGroups(this.config.whitelistGroups),
+        userMode: this.config.userMode,
+        blacklistUsers: this.normalizeGroups(this.config.blacklistUsers),
+        whitelistUsers: this.normalizeGroups(this.config.whitelistUsers),
+        pluginOverrides: this.normalizeOverrides(this.config.pluginOverrides)
       };
     },
     serializePayload(payload) {
@@ -143,6 +207,9 @@ const PermissionsPage = {
         if (result.retcode === 0) {
           this.config.blacklistGroups = payload.blacklistGroups;
           this.config.whitelistGroups = payload.whitelistGroups;
+          this.config.blacklistUsers = payload.blacklistUsers;
+          this.config.whitelistUsers = payload.whitelistUsers;
+          this.config.pluginOverrides = payload.pluginOverrides;
           this.lastSavedSnapshot = snapshot;
           this.setSaveStatus('saved');
         } else {
@@ -173,7 +240,11 @@ const PermissionsPage = {
           this.config = {
             mode: result.data.mode || 'blacklist',
             blacklistGroups: this.normalizeGroups(result.data.blacklistGroups || []),
-            whitelistGroups: this.normalizeGroups(result.data.whitelistGroups || [])
+            whitelistGroups: this.normalizeGroups(result.data.whitelistGroups || []),
+            userMode: result.data.userMode || 'blacklist',
+            blacklistUsers: this.normalizeGroups(result.data.blacklistUsers || []),
+            whitelistUsers: this.normalizeGroups(result.data.whitelistUsers || []),
+            pluginOverrides: this.normalizeOverrides(result.data.pluginOverrides)
           };
           this.lastSavedSnapshot = this.serializePayload(this.buildPayload());
           this.autoSaveReady = true;
@@ -221,21 +292,53 @@ const PermissionsPage = {
         this.loadingGroups = false;
       }
     },
+    selectScope(scope) {
+      this.scope = scope;
+      this.groupFilter = '';
+    },
+    createOverride() {
+      if (!this.scope || this.hasOverride) {
+        return;
+      }
+
+      this.config.pluginOverrides = {
+        ...this.config.pluginOverrides,
+        [this.scope]: this.normalizeRule(this.config)
+      };
+      this.scheduleAutoSave();
+    },
+    removeOverride() {
+      if (!this.hasOverride) {
+        return;
+      }
+
+      const next = { ...this.config.pluginOverrides };
+      delete next[this.scope];
+      this.config.pluginOverrides = next;
+      this.scheduleAutoSave();
+    },
+    scopeLabel(pluginId) {
+      const option = this.scopeOptions.find(item => item.id === pluginId);
+      return option ? `${option.name} (${pluginId})` : pluginId;
+    },
     selectMode(mode) {
-      this.config.mode = mode;
+      if (this.ruleLocked) {
+        return;
+      }
+      this.activeRule.mode = mode;
       this.groupFilter = '';
       this.scheduleAutoSave();
     },
     modeButtonClass(mode) {
-      return this.config.mode === mode ? 'btn-primary' : 'btn-clear';
+      return this.activeRule.mode === mode ? 'btn-primary' : 'btn-clear';
     },
     currentModeTitle() {
-      return this.config.mode === 'blacklist'
+      return this.activeRule.mode === 'blacklist'
         ? '\u9ED1\u540D\u5355\u7FA4'
         : '\u767D\u540D\u5355\u7FA4';
     },
     currentModeDescription() {
-      return this.config.mode === 'blacklist'
+      return this.activeRule.mode === 'blacklist'
         ? '\u70B9\u51FB\u4E0B\u65B9\u7684\u7FA4\u5361\u7247\u52A0\u5165\u9ED1\u540D\u5355\u3002\u88AB\u9009\u4E2D\u7684\u7FA4\u5C06\u88AB\u62D2\u7EDD\u4E8B\u4EF6\u4E0E\u526F\u4F5C\u7528 API\u3002'
         : '\u70B9\u51FB\u4E0B\u65B9\u7684\u7FA4\u5361\u7247\u52A0\u5165\u767D\u540D\u5355\u3002\u53EA\u6709\u88AB\u9009\u4E2D\u7684\u7FA4\u4F1A\u5141\u8BB8\u4E8B\u4EF6\u4E0E\u526F\u4F5C\u7528 API\u3002';
     },
@@ -243,8 +346,13 @@ const PermissionsPage = {
       return this.currentGroups.includes(groupId);
     },
     toggleCurrentGroup(groupId) {
-      const key = this.config.mode === 'blacklist' ? 'blacklistGroups' : 'whitelistGroups';
-      const nextGroups = new Set(this.config[key]);
+      if (this.ruleLocked) {
+        return;
+      }
+
+      const rule = this.activeRule;
+      const key = rule.mode === 'blacklist' ? 'blacklistGroups' : 'whitelistGroups';
+      const nextGroups = new Set(rule[key]);
 
       if (nextGroups.has(groupId)) {
         nextGroups.delete(groupId);
@@ -252,7 +360,7 @@ const PermissionsPage = {
         nextGroups.add(groupId);
       }
 
-      this.config[key] = Array.from(nextGroups).sort((a, b) => a - b);
+      rule[key] = Array.from(nextGroups).sort((a, b) => a - b);
       this.scheduleAutoSave();
     },
     removeCurrentGroup(groupId) {
@@ -260,6 +368,44 @@ const PermissionsPage = {
         this.toggleCurrentGroup(groupId);
       }
     },
+    selectUserMode(mode) {
+      if (this.ruleLocked) {
+        return;
+      }
+      this.activeRule.userMode = mode;
+      this.scheduleAutoSave();
+    },
+    userModeButtonClass(mode) {
+      return this.activeRule.userMode === mode ? 'btn-primary' : 'btn-clear';
+    },
+    currentUserKey() {
+      return this.activeRule.userMode === 'whitelist' ? 'whitelistUsers' : 'blacklistUsers';
+    },
+    addUsers() {
+      if (this.ruleLocked) {
+        return;
+      }
+
+      const input = this.normalizeGroups((this.userInput || '').split(/[\s,\uFF0C]+/));
+      if (input.length === 0) {
+        this.showToast('\u8BF7\u8F93\u5165\u6709\u6548\u7684 QQ \u53F7', 'error');
+        return;
+      }
+
+      const key = this.currentUserKey();
+      this.activeRule[key] = this.normalizeGroups([...this.activeRule[key], ...input]);
+      this.userInput = '';
+      this.scheduleAutoSave();
+    },
+    removeUser(userId) {
+      if (this.ruleLocked) {
+        return;
+      }
+
+      const key = this.currentUserKey();
+      this.activeRule[key] = this.activeRule[key].filter(id => id !== userId);
+      this.scheduleAutoSave();
+    },
     getGroupLabel(groupId) {
       const group = this.groupOptions.find(item => item.groupId === groupId);
       return group ? `${group.groupName} (${groupId})` : `QQ\u7FA4 ${groupId}`;
@@ -283,6 +429,41 @@ const PermissionsPage = {
         <h1>\u6743\u9650\u914D\u7F6E</h1>
       </div>
 
+      <div class="card">
+        <div class="card-title">
+          <svg viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2">
+            <rect x="3" y="3" width="7" height="7"></rect>
+            <rect x="14" y="3" width="7" height="7"></rect>
+            <rect x="3" y="14" width="7" height="7"></rect>
+            <rect x="14" y="14" width="7" height="7"></rect>
+          </svg>
+          \u89C4\u5219\u8303\u56F4
+        </div>
+
+        <div class="form-group" style="margin-bottom: 0;">
+          <label>\u7F16\u8F91\u5BF9\u8C61</label>
+          <select :value="scope" @change="selectScope($event.target.value)" :disabled="pageBusy">
+            <option value="">\u5168\u5C40\u89C4\u5219\uFF08\u6240\u6709\u63D2\u4EF6\u9ED8\u8BA4\u4F7F\u7528\uFF09</option>
+            <option v-for="option in scopeOptions" :key="option.id" :value="option.id">
+              {{ option.name }} ({{ option.id }}){{ config.pluginOverrides[option.id] ? ' \u00B7 \u5355\u72EC\u89C4\u5219' : '' }}
+            </option>
+          </select>
+        </div>
+
+        <div class="info-panel" style="margin-top: 16px;" v-if="scope">
+          <div class="text-muted" v-if="hasOverride">
+            {{ scopeLabel(scope) }} \u6B63\u5728\u4F7F\u7528\u5355\u72EC\u7684\u7FA4\u89C4\u5219\uFF0C\u5168\u5C40\u89C4\u5219\u5BF9\u5B83\u4E0D\u751F\u6548\u3002
+          </div>
+          <div class="text-muted" v-else>
+            {{ scopeLabel(scope) }} \u5F53\u524D\u4F7F\u7528\u5168\u5C40\u89C4\u5219\u3002\u542F\u7528\u5355\u72EC\u89C4\u5219\u540E\u5C06\u4EE5\u5168\u5C40\u89C4\u5219\u4E3A\u521D\u59CB\u503C\u3002
+          </div>
+          <div style="margin-top: 12px;">
+            <button v-if="hasOverride" class="btn-clear" @click="removeOverride" :disabled="pageBusy">\u79FB\u9664\u5355\u72EC\u89C4\u5219</button>
+            <button v-else class="btn-primary" @click="createOverride" :disabled="pageBusy">\u542F\u7528\u5355\u72EC\u89C4\u5219</button>
+          </div>
+        </div>
+      </div>
+
       <div class="card">
         <div class="card-title">
           <svg viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2">
@@ -299,19 +480,19 @@ const PermissionsPage = {
         </div>
 
         <div class="mode-grid" style="margin-top: 16px;">
-          <button :class="modeButtonClass('blacklist')" @click="selectMode('blacklist')" :disabled="pageBusy">
+          <button :class="modeButtonClass('blacklist')" @click="selectMode('blacklist')" :disabled="pageBusy || ruleLocked">
             \u9ED1\u540D\u5355\u6A21\u5F0F
           </button>
-          <button :class="modeButtonClass('whitelist')" @click="selectMode('whitelist')" :disabled="pageBusy">
+          <button :class="modeButtonClass('whitelist')" @click="selectMode('whitelist')" :disabled="pageBusy || ruleLocked">
             \u767D\u540D\u5355\u6A21\u5F0F
           </button>
         </div>
 
         <div class="info-panel" style="margin-top: 16px;">
           <div style="font-size: 15px; font-weight: 600; margin-bottom: 8px;">
-            \u5F53\u524D\u751F\u6548\uFF1A{{ config.mode === 'blacklist' ? '\\u9ED1\\u540D\\u5355\\u6A21\\u5F0F' : '\\u767D\\u540D\\u5355\\u6A21\\u5F0F' }}
+            \u5F53\u524D\u751F\u6548\uFF1A{{ activeRule.mode === 'blacklist' ? '\\u9ED1\\u540D\\u5355\\u6A21\\u5F0F' : '\\u767D\\u540D\\u5355\\u6A21\\u5F0F' }}
           </div>
-          <div class="text-muted" v-if="config.mode === 'blacklist'">
+          <div class="text-muted" v-if="activeRule.mode === 'blacklist'">
             \u63D2\u4EF6\u4E0D\u80FD\u63A5\u6536\u6216\u64CD\u4F5C\u9ED1\u540D\u5355\u4E2D\u7684\u7FA4\uFF0C\u672A\u5217\u51FA\u7684\u7FA4\u9ED8\u8BA4\u5141\u8BB8\u3002
           </div>
           <div class="text-muted" v-else>
@@ -320,7 +501,7 @@ const PermissionsPage = {
           <div class="text-muted" style="margin-top: 8px;">
             \u5F53\u524D\u751F\u6548\u7FA4\u6570\uFF1A{{ currentGroups.length }}
           </div>
-          <div v-if="config.mode === 'whitelist' && currentGroups.length === 0" class="text-muted" style="margin-top: 8px; color: #ff9800;">
+          <div v-if="activeRule.mode === 'whitelist' && currentGroups.length === 0" class="text-muted" style="margin-top: 8px; color: #ff9800;">
             \u767D\u540D\u5355\u4E3A\u7A7A\u65F6\uFF0C\u6240\u6709\u7FA4\u4E8B\u4EF6\u548C\u7FA4\u804A\u526F\u4F5C\u7528 API \u90FD\u4F1A\u88AB\u62D2\u7EDD\u3002
           </div>
         </div>
@@ -369,11 +550,11 @@ const PermissionsPage = {
           <div v-if="currentGroups.length > 0" class="tag-list">
             <button
               v-for="groupId in currentGroups"
-              :key="'selected-' + config.mode + '-' + groupId"
+              :key="'selected-' + activeRule.mode + '-' + groupId"
               class="tag-chip tag-chip-button"
               type="button"
               @click="removeCurrentGroup(groupId)"
-              :disabled="pageBusy"
+              :disabled="pageBusy || ruleLocked"
             >
               {{ getGroupLabel(groupId) }}
             </button>
@@ -395,7 +576,7 @@ const PermissionsPage = {
             class="group-option-card"
             :class="{ active: isCurrentGroupSelected(group.groupId) }"
             @click="toggleCurrentGroup(group.groupId)"
-            :disabled="pageBusy"
+            :disabled="pageBusy || ruleLocked"
           >
             <span class="group-option-name">{{ group.groupName }}</span>
             <span class="group-option-meta">QQ\u7FA4 {{ group.groupId }}</span>
@@ -409,6 +590,63 @@ const PermissionsPage = {
         </div>
       </div>
 
+      <div class="card">
+        <div class="card-title">
+          <svg viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2">
+            <path d="M20 21v-2a4 4 0 0 0-4-4H8a4 4 0 0 0-4 4v2"></path>
+            <circle cx="12" cy="7" r="4"></circle>
+          </svg>
+          \u79C1\u804A\u7528\u6237\u540D\u5355
+        </div>
+
+        <div class="text-muted">
+          \u9650\u5236\u63D2\u4EF6\u63A5\u6536\u7684\u597D\u53CB/\u4E34\u65F6\u4F1A\u8BDD\u6D88\u606F\u3001\u597D\u53CB\u8BF7\u6C42\uFF0C\u4EE5\u53CA\u53D1\u9001\u79C1\u804A\u3001\u5904\u7406\u597D\u53CB\u8BF7\u6C42\u7B49\u6709\u526F\u4F5C\u7528\u7684 API\u3002
+        </div>
+
+        <div class="mode-grid" style="margin-top: 16px;">
+          <button :class="userModeButtonClass('blacklist')" @click="selectUserMode('blacklist')" :disabled="pageBusy || ruleLocked">
+            \u9ED1\u540D\u5355\u6A21\u5F0F
+          </button>
+          <button :class="userModeButtonClass('whitelist')" @click="selectUserMode('whitelist')" :disabled="pageBusy || ruleLocked">
+            \u767D\u540D\u5355\u6A21\u5F0F
+          </button>
+        </div>
+
+        <div class="group-selector-toolbar">
+          <div class="form-group" style="flex: 1; min-width: 220px; margin-bottom: 0;">
+            <label>{{ activeRule.userMode === 'whitelist' ? '\u6DFB\u52A0\u5230\u767D\u540D\u5355' : '\u6DFB\u52A0\u5230\u9ED1\u540D\u5355' }}</label>
+            <input
+              type="text"
+              v-model="userInput"
+              :disabled="pageBusy || ruleLocked"
+              placeholder="\u8F93\u5165 QQ \u53F7\uFF0C\u591A\u4E2A\u7528\u9017\u53F7\u6216\u7A7A\u683C\u5206\u9694"
+              @keyup.enter="addUsers"
+            >
+          </div>
+          <button class="btn-primary" @click="addUsers" :disabled="pageBusy || ruleLocked" style="align-self: flex-end;">
+            \u6DFB\u52A0
+          </button>
+        </div>
+
+        <div style="margin-top: 16px;">
+          <div v-if="currentUsers.length > 0" class="tag-list">
+            <button
+              v-for="userId in currentUsers"
+              :key="'user-' + activeRule.userMode + '-' + userId"
+              class="tag-chip tag-chip-button"
+              type="button"
+              @click="removeUser(userId)"
+              :disabled="pageBusy || ruleLocked"
+            >
+              QQ {{ userId }}
+            </button>
+          </div>
+          <div v-else class="text-muted">
+            {{ activeRule.userMode === 'whitelist' ? '\u767D\u540D\u5355\u4E3A\u7A7A\uFF0C\u6240\u6709\u79C1\u804A\u4E8B\u4EF6\u548C\u79C1\u804A\u526F\u4F5C\u7528 API \u90FD\u4F1A\u88AB\u62D2\u7EDD\u3002' : '\u6682\u672A\u6DFB\u52A0\u4EFB\u4F55\u7528\u6237\uFF0C\u6240\u6709\u79C1\u804A\u9ED8\u8BA4\u5141\u8BB8\u3002' }}
+          </div>
+        </div>
+      </div>
+
       <div class="card">
         <div style="display: flex; align-items: center; justify-content: space-between; gap: 12px; flex-wrap: wrap;">
           <div class="text-muted">\u66F4\u6539\u540E\u4F1A\u81EA\u52A8\u4FDD\u5B58\u5E76\u7ACB\u5373\u751F\u6548\uFF0C\u4E0D\u9700\u8981\u91CD\u542F\u7A0B\u5E8F\u6216\u63D2\u4EF6\u3002</div>
@@ -1,5 +1,28 @@
 const MAX_OUTPUT_LINES = 500; // 前端最大显示行数
 
+// 终端标准 16 色
+const ANSI_PALETTE = [
+  '#000000', '#cd3131', '#0dbc79', '#e5e510', '#2472c8', '#bc3fbc', '#11a8cd', '#e5e5e5',
+  '#666666', '#f14c4c', '#23d18b', '#f5f543', '#3b8eea', '#d670d6', '#29b8db', '#ffffff'
+];
+
+// 颜色为 0-255 的调色板序号或 [r, g, b]
+function ansiColorToCss(color) {
+  if (Array.isArray(color)) {
+    return 'rgb(' + color.join(',') + ')';
+  }
+  if (color < 16) {
+    return ANSI_PALETTE[color];
+  }
+  if (color < 232) {
+    const levels = [0, 95, 135, 175, 215, 255];
+    const n = color - 16;
+    return 'rgb(' + levels[Math.floor(n / 36)] + ',' + levels[Math.floor(n / 6) % 6] + ',' + levels[n % 6] + ')';
+  }
+  const gray = 8 + (color - 232) * 10;
+  return 'rgb(' + gray + ',' + gray + ',' + gray + ')';
+}
+
 const PluginsPage = {
   data() {
     return {
@@ -11,11 +34,20 @@ const PluginsPage = {
       pendingStatusUpdates: {},
       activeMoreMenu: null,
       stoppingPlugins: {},
+      botAccounts: [],
       confirmDialog: {
         show: false,
         title: '',
         message: '',
         onConfirm: null
+      },
+      kvDialog: {
+        show: false,
+        pluginId: '',
+        pluginName: '',
+        prefix: '',
+        entries: [],
+        loading: false
       }
     };
   },
@@ -58,6 +90,23 @@ const PluginsPage = {
                 <span class="plugin-version">v{{ plugin.version }}</span>
                 <span :class="'plugin-status ' + plugin.status">{{ getStatusText(plugin.status) }}</span>
                 <span :class="'plugin-enabled ' + (plugin.enabled ? 'yes' : 'no')">{{ plugin.enabled ? '已启用' : '已禁用' }}</span>
+                <span v-if="plugin.crash_count > 0" class="plugin-crashes" title="本次运行期间插件意外退出的次数">崩溃 {{ plugin.crash_count }} 次</span>
+                <span v-if="plugin.status === 'running' && !plugin.ready" class="plugin-crashes" title="插件尚未通过就绪检查">等待就绪</span>
+              </div>
+              <div v-if="plugin.depends_on && plugin.depends_on.length" class="plugin-permissions">
+                <span class="plugin-permissions-label">依赖插件:</span>
+                <span v-for="dep in plugin.depends_on" :key="dep" class="plugin-permission">{{ dep }}</span>
+              </div>
+              <div v-if="plugin.permissions && plugin.permissions.length" class="plugin-permissions" @click.stop>
+                <span class="plugin-permissions-label">申请权限:</span>
+                <span v-for="perm in plugin.permissions" :key="perm.name" :class="'plugin-permission ' + (perm.approved ? 'approved' : 'pending')" :title="perm.approved ? '已批准' : '待批准'">{{ perm.name }}</span>
+                <button v-if="plugin.permissions.some(p => !p.approved)" class="btn-text" @click.stop="approvePermissions(plugin)" :disabled="loading">批准</button>
+                <button v-else class="btn-text" @click.stop="revokePermissions(plugin)" :disabled="loading">撤销</button>
+              </div>
+              <div v-if="botAccounts.length > 1 || (plugin.bots && plugin.bots.length)" class="plugin-permissions" @click.stop>
+                <span class="plugin-permissions-label">可用账号:</span>
+                <span v-if="!plugin.bots || !plugin.bots.length" class="plugin-permission approved">全部</span>
+                <span v-for="bot in botAccounts" :key="bot.id" :class="'plugin-permission ' + (isBotBound(plugin, bot.id) ? 'approved' : '')" style="cursor: pointer;" :title="isBotBound(plugin, bot.id) ? '点击解除绑定' : '点击绑定'" @click.stop="toggleBotBinding(plugin, bot.id)">{{ bot.name || bot.id }}</span>
               </div>
             </div>
             <div class="plugin-actions">
@@ -92,6 +141,10 @@ const PluginsPage = {
                     <svg viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2"><path d="M21 16V8a2 2 0 0 0-1-1.73l-7-4a2 2 0 0 0-2 0l-7 4A2 2 0 0 0 3 8v8a2 2 0 0 0 1 1.73l7 4a2 2 0 0 0 2 0l7-4A2 2 0 0 0 21 16z"></path><polyline points="3.27 6.96 12 12.01 20.73 6.96"></polyline><line x1="12" y1="22.08" x2="12" y2="12"></line></svg>
                     数据目录
                   </button>
+                  <button class="btn-primary" @click="openKvBrowser(plugin); activeMoreMenu = null" title="存储数据">
+                    <svg viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2"><ellipse cx="12" cy="5" rx="9" ry="3"></ellipse><path d="M21 12c0 1.66-4 3-9 3s-9-1.34-9-3"></path><path d="M3 5v14c0 1.66 4 3 9 3s9-1.34 9-3V5"></path></svg>
+                    存储数据
+                  </button>
                   <button class="btn-primary" @click="exportPlugin(plugin.id); activeMoreMenu = null" :disabled="loading" title="导出插件">
                     <svg viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2"><path d="M21 15v4a2 2 0 0 1-2 2H5a2 2 0 0 1-2-2v-4"></path><polyline points="7 10 12 15 17 10"></polyline><line x1="12" y1="15" x2="12" y2="3"></line></svg>
                     导出
@@ -117,13 +170,42 @@ const PluginsPage = {
               <div v-if="!plugin.output || plugin.output.length === 0" style="color: var(--text-secondary); padding: 10px;">
                 暂无输出
               </div>
-              <div v-for="(line, i) in plugin.output" :key="i" class="output-line">{{ line }}</div>
+              <div v-for="(entry, i) in plugin.output" :key="i" class="output-line">
+                <template v-if="entry.spans && entry.spans.length"><span v-for="(span, j) in entry.spans" :key="j" :style="outputSpanStyle(span)">{{ span.text }}</span></template>
+                <template v-else>{{ entry.line }}</template>
+              </div>
             </div>
           </div>
         </div>
         </div>
       </div>
       
+      <!-- KV Browser Modal -->
+      <div v-if="kvDialog.show" class="modal-overlay" @click="kvDialog.show = false">
+        <div class="modal" @click.stop style="max-width: 720px;">
+          <div class="modal-header">存储数据 - {{ kvDialog.pluginName }}</div>
+          <div style="display: flex; gap: 8px; margin-bottom: 12px;">
+            <div class="form-group" style="flex: 1;"><input type="text" v-model="kvDialog.prefix" @keyup.enter="loadKvEntries" placeholder="按键名前缀筛选"></div>
+            <button class="btn-primary" @click="loadKvEntries" :disabled="kvDialog.loading">查询</button>
+          </div>
+          <div class="modal-body" style="max-height: 50vh; overflow-y: auto;">
+            <div v-if="kvDialog.loading">加载中...</div>
+            <div v-else-if="kvDialog.entries.length === 0">暂无数据</div>
+            <div v-else v-for="entry in kvDialog.entries" :key="entry.key" style="padding: 8px 0; border-bottom: 1px solid var(--border-color);">
+              <div style="display: flex; justify-content: space-between; gap: 10px;">
+                <span style="font-weight: 600; color: var(--text-primary); word-break: break-all;">{{ entry.key }}</span>
+                <span v-if="entry.expiresAt" style="white-space: nowrap;">过期: {{ new Date(entry.expiresAt).toLocaleString() }}</span>
+              </div>
+              <pre style="margin: 4px 0 0; white-space: pre-wrap; word-break: break-all; font-size: 12px;">{{ formatKvValue(entry.value) }}</pre>
+            </div>
+          </div>
+          <div class="modal-footer">
+            <span class="text-muted" style="margin-right: auto;">只读，最多显示 1000 条</span>
+            <button class="btn-text" @click="kvDialog.show = false">关闭</button>
+          </div>
+        </div>
+      </div>
+
       <!-- Confirmation Modal -->
       <div v-if="confirmDialog.show" class="modal-overlay" @click="confirmDialog.show = false">
         <div class="modal" @click.stop>
@@ -139,6 +221,7 @@ const PluginsPage = {
   `,
   mounted() {
     this.loadPlugins();
+    this.loadBotAccounts();
     this.connectEventsSSE();
     this.clickListener = () => {
       this.activeMoreMenu = null;
@@ -183,6 +266,47 @@ const PluginsPage = {
       }
       this.confirmDialog.show = false;
     },
+    openKvBrowser(plugin) {
+      this.kvDialog = {
+        show: true,
+        pluginId: plugin.id,
+        pluginName: plugin.name,
+        prefix: '',
+        entries: [],
+        loading: false
+      };
+      this.loadKvEntries();
+    },
+    loadKvEntries() {
+      const pluginId = this.kvDialog.pluginId;
+      const params = new URLSearchParams({ prefix: this.kvDialog.prefix, limit: 1000 });
+      this.kvDialog.loading = true;
+      fetch('/api/kv/browse/' + encodeURIComponent(pluginId) + '?' + params.toString())
+        .then(res => res.json())
+        .then(data => {
+          if (this.kvDialog.pluginId !== pluginId) return;
+          if (data.retcode === 0) {
+            this.kvDialog.entries = data.data;
+          } else {
+            window.showToast('读取存储数据失败: ' + data.data, 'error');
+          }
+        })
+        .catch(err => {
+          console.error('Failed to load kv entries:', err);
+          window.showToast('读取存储数据失败: ' + err, 'error');
+        })
+        .finally(() => { this.kvDialog.loading = false; });
+    },
+    formatKvValue(value) {
+      return typeof value === 'string' ? value : JSON.stringify(value, null, 2);
+    },
+    outputSpanStyle(span) {
+      const style = {};
+      if (span.fg !== undefined) style.color = ansiColorToCss(span.fg);
+      if (span.bg !== undefined) style.backgroundColor = ansiColorToCss(span.bg);
+      if (span.bold) style.fontWeight = 'bold';
+      return style;
+    },
     importPlugin() {
       this.loading = true;
       fetch('/api/plugins/import', { method: 'POST' })
@@ -262,6 +386,80 @@ const PluginsPage = {
         }
       };
     },
+    approvePermissions(plugin) {
+      const names = plugin.permissions.map(p => p.name).join(', ');
+      this.confirmDialog = {
+        show: true,
+        title: '批准权限',
+        message: '确定允许插件 ' + plugin.id + ' 调用以下接口吗？\n' + names,
+        onConfirm: () => {
+          this.updatePermissions(plugin.id, 'approve');
+        }
+      };
+    },
+    revokePermissions(plugin) {
+      this.updatePermissions(plugin.id, 'revoke');
+    },
+    updatePermissions(id, action) {
+      this.loading = true;
+      fetch('/api/plugins/' + encodeURIComponent(id) + '/permissions/' + action, { method: 'POST' })
+        .then(res => res.json())
+        .then(data => {
+          if (data.retcode === 0) {
+            window.showToast(action === 'approve' ? '已批准权限' : '已撤销权限', 'success');
+            this.loadPlugins();
+          } else {
+            window.showToast('操作失败: ' + data.data, 'error');
+          }
+        })
+        .catch(err => {
+          console.error('Failed to update plugin permissions:', err);
+          window.showToast('操作失败: ' + err, 'error');
+        })
+        .finally(() => { this.loading = false; });
+    },
+    loadBotAccounts() {
+      fetch('/api/bot/list')
+        .then(res => res.json())
+        .then(data => {
+          if (data.retcode === 0) {
+            this.botAccounts = data.data.map(item => ({ id: item.config.id, name: item.config.name }));
+          }
+        })
+        .catch(err => {
+          console.error('Failed to load bot accounts:', err);
+        });
+    },
+    isBotBound(plugin, botId) {
+      return !!plugin.bots && plugin.bots.includes(botId);
+    },
+    // 未绑定任何账号时插件可以使用全部账号
+    toggleBotBinding(plugin, botId) {
+      const current = plugin.bots || [];
+      const bots = current.includes(botId)
+        ? current.filter(id => id !== botId)
+        : current.concat([botId]);
+
+      this.loading = true;
+      fetch('/api/plugins/' + encodeURIComponent(plugin.id) + '/bots', {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify(bots)
+      })
+        .then(res => res.json())
+        .then(data => {
+          if (data.retcode === 0) {
+            plugin.bots = bots;
+          } else {
+            window.showToast('操作失败: ' + data.data, 'error');
+          }
+        })
+        .catch(err => {
+          console.error('Failed to update plugin bot bindings:', err);
+          window.showToast('操作失败: ' + err, 'error');
+        })
+        .finally(() => { this.loading = false; });
+    },
     openPluginDir(id) {
       fetch('/api/plugins/' + encodeURIComponent(id) + '/open_dir', { method: 'POST' })
         .then(res => res.json())
@@ -299,7 +497,8 @@ const PluginsPage = {
             this.plugins.forEach(p => { oldPlugins[p.id] = p.output; });
             
             this.plugins = data.data.map(p => {
-              let output = oldPlugins[p.id] || p.output || [];
+              const spans = p.output_spans || [];
+              let output = oldPlugins[p.id] || (p.output || []).map((line, i) => ({ line, spans: spans[i] || [] }));
               // 限制输出行数
               if (output.length > MAX_OUTPUT_LINES) {
                 output = output.slice(-MAX_OUTPUT_LINES);
@@ -311,6 +510,7 @@ const PluginsPage = {
                   p.status = update.status;
                   p.enabled = update.enabled;
                   p.webui_url = update.webui_url;
+                  p.crash_count = update.crash_count;
               }
               
               return { ...p, output };
@@ -393,6 +593,7 @@ const PluginsPage = {
               plugin.status = statusEvent.status;
               plugin.enabled = statusEvent.enabled;
               plugin.webui_url = statusEvent.webui_url;
+              plugin.crash_count = statusEvent.crash_count;
               if (statusEvent.status !== 'running') {
                 this.stoppingPlugins = { ...this.stoppingPlugins, [statusEvent.plugin_id]: false };
               }
@@ -400,7 +601,8 @@ const PluginsPage = {
               this.pendingStatusUpdates[statusEvent.plugin_id] = {
                   status: statusEvent.status,
                   enabled: statusEvent.enabled,
-                  webui_url: statusEvent.webui_url
+                  webui_url: statusEvent.webui_url,
+                  crash_count: statusEvent.crash_count
               };
             }
           } else if (type === 'Output') {
@@ -410,7 +612,7 @@ const PluginsPage = {
               if (!plugin.output) {
                 plugin.output = [];
               }
-              plugin.output.push(outputEvent.line);
+              plugin.output.push({ line: outputEvent.line, spans: outputEvent.spans || [] });
               
               if (plugin.output.length > MAX_OUTPUT_LINES) {
                 plugin.output.shift();
@@ -7,6 +7,11 @@ const SystemPage = {
       autoStart: false,
       loading: true,
       savingAutoStart: false,
+      logLevels: { default: 'info', sources: [] },
+      savingLogLevels: false,
+      rateLimit: null,
+      savingRateLimit: false,
+      cacheStats: null,
       confirmDialog: {
         show: false,
         title: '',
@@ -17,6 +22,8 @@ const SystemPage = {
   },
   mounted() {
     this.loadSystemInfo();
+    this.loadCacheStats();
+    this.loadRateLimitConfig();
   },
   methods: {
     async loadSystemInfo() {
@@ -28,6 +35,11 @@ const SystemPage = {
           this.dataDir = result.data.data_dir;
           this.pluginsDir = result.data.plugins_root;
           this.autoStart = !!result.data.auto_start;
+          const logLevels = result.data.log_levels || {};
+          this.logLevels = {
+            default: logLevels.default || 'info',
+            sources: Object.entries(logLevels.sources || {}).map(([source, level]) => ({ source, level }))
+          };
         }
       } catch (err) {
         console.error('Failed to load system info:', err);
@@ -35,6 +47,35 @@ const SystemPage = {
         this.loading = false;
       }
     },
+    async loadCacheStats() {
+      try {
+        const response = await fetch('/api/api_cache/stats');
+        const result = await response.json();
+        if (result.retcode === 0) {
+          this.cacheStats = result.data;
+        }
+      } catch (err) {
+        console.error('Failed to load API cache stats:', err);
+      }
+    },
+    async clearCache() {
+      try {
+        const response = await fetch('/api/api_cache/clear', { method: 'POST' });
+        const result = await response.json();
+        if (result.retcode !== 0) {
+          throw new Error(result.data || '清空缓存失败');
+        }
+        this.showToast('API 缓存已清空', 'success');
+        await this.loadCacheStats();
+      } catch (err) {
+        console.error('Failed to clear API cache:', err);
+        this.showToast(`清空失败：${err.message}`, 'error');
+      }
+    },
+    hitRate(hits, misses) {
+      const total = hits + misses;
+      return total > 0 ? `${(hits * 100 / total).toFixed(1)}%` : '-';
+    },
     async openDataDir() {
       try {
         await fetch('/api/open_data_dir', { method: 'POST' });
@@ -87,6 +128,128 @@ const SystemPage = {
         this.savingAutoStart = false;
       }
     },
+    addLogSource() {
+      this.logLevels.sources.push({ source: '', level: 'debug' });
+    },
+    removeLogSource(index) {
+      this.logLevels.sources.splice(index, 1);
+    },
+    async saveLogLevels() {
+      const sources = {};
+      for (const item of this.logLevels.sources) {
+        const source = item.source.trim();
+        if (source) {
+          sources[source] = item.level;
+        }
+      }
+
+      this.savingLogLevels = true;
+      try {
+        const response = await fetch('/api/system/save_config', {
+          method: 'POST',
+          headers: {
+            'Content-Type': 'application/json'
+          },
+          body: JSON.stringify({
+            autoStart: this.autoStart,
+            logLevels: { default: this.logLevels.default, sources }
+          })
+        });
+
+        const result = await response.json();
+        if (result.retcode !== 0) {
+          throw new Error(result.data || '保存日志级别失败');
+        }
+
+        this.showToast('日志级别已保存', 'success');
+      } catch (err) {
+        console.error('Failed to save log levels:', err);
+        this.showToast(`保存失败：${err.message}`, 'error');
+      } finally {
+        this.savingLogLevels = false;
+      }
+    },
+    async loadRateLimitConfig() {
+      try {
+        const response = await fetch('/api/rate_limits/get_config');
+        const result = await response.json();
+        if (result.retcode === 0) {
+          const config = result.data;
+          this.rateLimit = {
+            enabled: !!config.enabled,
+            plugin: this.toRuleForm(config.plugin),
+            group: this.toRuleForm(config.group),
+            apis: Object.entries(config.apis || {}).map(([api, rule]) => ({ api, burst: rule.burst, perSecond: rule.perSecond }))
+          };
+        }
+      } catch (err) {
+        console.error('Failed to load rate limit config:', err);
+      }
+    },
+    toRuleForm(rule) {
+      return rule
+        ? { enabled: true, burst: rule.burst, perSecond: rule.perSecond }
+        : { enabled: false, burst: 10, perSecond: 2 };
+    },
+    fromRuleForm(rule) {
+      return rule.enabled ? { burst: Number(rule.burst), perSecond: Number(rule.perSecond) } : null;
+    },
+    handleRateLimitToggle(event) {
+      this.rateLimit.enabled = event.target.checked;
+      // 首次开启且没有任何规则时，填入推荐规则供修改
+      const rl = this.rateLimit;
+      if (rl.enabled && !rl.plugin.enabled && !rl.group.enabled && rl.apis.length === 0) {
+        rl.plugin = { enabled: true, burst: 50, perSecond: 20 };
+        rl.apis = [
+          { api: 'send_group_message', burst: 10, perSecond: 2 },
+          { api: 'send_private_message', burst: 10, perSecond: 2 }
+        ];
+      }
+    },
+    addRateLimitApi() {
+      this.rateLimit.apis.push({ api: '', burst: 10, perSecond: 2 });
+    },
+    removeRateLimitApi(index) {
+      this.rateLimit.apis.splice(index, 1);
+    },
+    async saveRateLimitConfig() {
+      const apis = {};
+      for (const item of this.rateLimit.apis) {
+        const api = item.api.trim();
+        if (api) {
+          apis[api] = { burst: Number(item.burst), perSecond: Number(item.perSecond) };
+        }
+      }
+
+      this.savingRateLimit = true;
+      try {
+        const response = await fetch('/api/rate_limits/save_config', {
+          method: 'POST',
+          headers: {
+            'Content-Type': 'application/json'
+          },
+          body: JSON.stringify({
+            enabled: this.rateLimit.enabled,
+            plugin: this.fromRuleForm(this.rateLimit.plugin),
+            group: this.fromRuleForm(this.rateLimit.group),
+            apis
+          })
+        });
+
+        const result = await response.json();
+        if (result.retcode !== 0) {
+          throw new Error(result.data || '保存限流配置失败');
+        }
+
+        this.showToast('限流配置已保存', 'success');
+        await this.loadRateLimitConfig();
+      } catch (err) {
+        console.error('Failed to save rate limit config:', err);
+        this.showToast(`保存失败：${err.message}`, 'error');
+      } finally {
+        this.savingRateLimit = false;
+      }
+    },
     confirmAction() {
       if (this.confirmDialog.onConfirm) {
         this.confirmDialog.onConfirm();
@@ -174,6 +337,122 @@ const SystemPage = {
         <div v-if="savingAutoStart" class="text-muted" style="margin-top: 12px;">正在保存启动设置…</div>
       </div>
 
+      <div class="card">
+        <div class="card-title"><svg viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2"><path d="M14 2H6a2 2 0 0 0-2 2v16a2 2 0 0 0 2 2h12a2 2 0 0 0 2-2V8z"></path><polyline points="14 2 14 8 20 8"></polyline></svg>日志级别</div>
+        <div class="info-panel" style="margin-bottom: 16px;">
+          <div class="text-muted">低于最低级别的日志不会显示，也不会写入 logs 目录下的日志文件。可以按来源（日志页中的来源列，如 [核心]）单独设置级别。</div>
+        </div>
+
+        <div class="config-form">
+          <div class="form-group">
+            <label>默认最低级别</label>
+            <select v-model="logLevels.default">
+              <option v-for="level in ['error', 'warn', 'info', 'debug', 'trace']" :key="level" :value="level">{{ level }}</option>
+            </select>
+          </div>
+
+          <div v-for="(item, index) in logLevels.sources" :key="index" style="display: flex; gap: 8px; align-items: flex-end;">
+            <div class="form-group" style="flex: 1; margin-bottom: 0;">
+              <label>来源</label>
+              <input type="text" v-model="item.source" placeholder="[核心]">
+            </div>
+            <div class="form-group" style="margin-bottom: 0;">
+              <label>最低级别</label>
+              <select v-model="item.level">
+                <option v-for="level in ['error', 'warn', 'info', 'debug', 'trace']" :key="level" :value="level">{{ level }}</option>
+              </select>
+            </div>
+            <button @click="removeLogSource(index)" class="btn-clear">删除</button>
+          </div>
+        </div>
+
+        <div style="display: flex; gap: 8px; margin-top: 16px;">
+          <button @click="addLogSource" class="btn-clear">添加来源</button>
+          <button @click="saveLogLevels" class="btn-primary" :disabled="loading || savingLogLevels">保存</button>
+        </div>
+      </div>
+
+      <div class="card" v-if="rateLimit">
+        <div class="card-title"><svg viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2"><circle cx="12" cy="12" r="10"></circle><polyline points="12 6 12 12 16 14"></polyline></svg>调用频率限制</div>
+        <div class="info-panel" style="margin-bottom: 16px;">
+          <div class="text-muted">按令牌桶限制每个插件调用 Milky API 的频率，超出限制的调用返回 429。“突发”为最多可连续调用的次数，“每秒”为每秒恢复的次数。</div>
+        </div>
+
+        <div class="switch-row">
+          <div>
+            <div style="font-size: 14px; font-weight: 600; margin-bottom: 4px;">启用限流</div>
+            <div class="text-muted">关闭时不限制插件的调用频率。</div>
+          </div>
+          <label class="switch">
+            <input type="checkbox" :checked="rateLimit.enabled" @change="handleRateLimitToggle">
+            <span class="slider"></span>
+          </label>
+        </div>
+
+        <div v-if="rateLimit.enabled" class="config-form" style="margin-top: 16px;">
+          <div v-for="item in [{ key: 'plugin', label: '每个插件的全部调用' }, { key: 'group', label: '每个插件对同一个群的同一个 API' }]" :key="item.key" style="display: flex; gap: 8px; align-items: flex-end;">
+            <label style="flex: 1; display: flex; gap: 6px; align-items: center;">
+              <input type="checkbox" v-model="rateLimit[item.key].enabled">{{ item.label }}
+            </label>
+            <div class="form-group" style="margin-bottom: 0;">
+              <label>突发</label>
+              <input type="number" min="1" v-model="rateLimit[item.key].burst" :disabled="!rateLimit[item.key].enabled">
+            </div>
+            <div class="form-group" style="margin-bottom: 0;">
+              <label>每秒</label>
+              <input type="number" min="0.1" step="0.1" v-model="rateLimit[item.key].perSecond" :disabled="!rateLimit[item.key].enabled">
+            </div>
+          </div>
+
+          <div v-for="(item, index) in rateLimit.apis" :key="'api-' + index" style="display: flex; gap: 8px; align-items: flex-end;">
+            <div class="form-group" style="flex: 1; margin-bottom: 0;">
+              <label>API</label>
+              <input type="text" v-model="item.api" placeholder="send_group_message">
+            </div>
+            <div class="form-group" style="margin-bottom: 0;">
+              <label>突发</label>
+              <input type="number" min="1" v-model="item.burst">
+            </div>
+            <div class="form-group" style="margin-bottom: 0;">
+              <label>每秒</label>
+              <input type="number" min="0.1" step="0.1" v-model="item.perSecond">
+            </div>
+            <button @click="removeRateLimitApi(index)" class="btn-clear">删除</button>
+          </div>
+        </div>
+
+        <div style="display: flex; gap: 8px; margin-top: 16px;">
+          <button v-if="rateLimit.enabled" @click="addRateLimitApi" class="btn-clear">添加 API 规则</button>
+          <button @click="saveRateLimitConfig" class="btn-primary" :disabled="savingRateLimit">保存</button>
+        </div>
+      </div>
+
+      <div class="card" v-if="cacheStats">
+        <div class="card-title"><svg viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2"><ellipse cx="12" cy="5" rx="9" ry="3"></ellipse><path d="M21 12c0 1.66-4 3-9 3s-9-1.34-9-3"></path><path d="M3 5v14c0 1.66 4 3 9 3s9-1.34 9-3V5"></path></svg>API 缓存</div>
+        <div class="info-panel" style="margin-bottom: 16px;">
+          <div class="text-muted">插件调用的只读 API（如 get_group_list）会被缓存一段时间，群成员变动等事件会自动清除相关缓存。缓存规则保存在 config/api_cache.json。</div>
+        </div>
+
+        <div v-if="!cacheStats.enabled" class="text-muted" style="margin-bottom: 12px;">缓存已关闭</div>
+
+        <div class="stat-grid" style="margin-bottom: 16px;">
+          <div class="stat-item"><div class="stat-value">{{ cacheStats.hits }}</div><div class="stat-label">命中</div></div>
+          <div class="stat-item"><div class="stat-value">{{ cacheStats.misses }}</div><div class="stat-label">未命中</div></div>
+          <div class="stat-item"><div class="stat-value">{{ hitRate(cacheStats.hits, cacheStats.misses) }}</div><div class="stat-label">命中率</div></div>
+          <div class="stat-item"><div class="stat-value">{{ cacheStats.entries }}</div><div class="stat-label">缓存条目</div></div>
+        </div>
+
+        <div v-for="item in cacheStats.apis" :key="item.api" class="switch-row" style="padding: 8px 0;">
+          <div style="font-size: 14px;">{{ item.api }}</div>
+          <div class="text-muted">命中 {{ item.hits }} / 未命中 {{ item.misses }}（{{ hitRate(item.hits, item.misses) }}）<span v-if="item.coalesced"> · 合并 {{ item.coalesced }}</span></div>
+        </div>
+
+        <div style="display: flex; gap: 8px; margin-top: 16px;">
+          <button @click="loadCacheStats" class="btn-clear">刷新</button>
+          <button @click="clearCache" class="btn-clear">清空缓存</button>
+        </div>
+      </div>
+
       <div class="card">
         <div class="card-title"><svg viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2"><path d="M23 4v6h-6"></path><path d="M20.49 15a9 9 0 1 1-2.12-9.36L23 10"></path></svg>程序操作</div>
         <button @click="restartProgram" class="btn-danger" style="width: 100%;">
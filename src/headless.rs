@@ -0,0 +1,68 @@
+use crate::runtime;
+use crate::server;
+use std::time::Duration;
+
+/// 无界面模式：不创建窗口与托盘，只运行 WebUI、Milky 代理和插件管理器
+///
+/// 收到 `SIGINT`/`SIGTERM`（Windows 下为 `Ctrl+C`）后停止所有插件并清理临时目录，
+/// 适合作为 systemd 服务或在容器中运行。
+pub fn run() {
+    log_info!("框架启动（无界面模式）");
+
+    let _runtime = runtime::init_runtime();
+
+    let (port, server_state) = match server::start_server_safe() {
+        Ok(res) => res,
+        Err(e) => {
+            log_error!("服务器启动失败: {}", e);
+            eprintln!("服务器启动失败: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    // 地址中带有会话令牌，只打印到标准输出，不写入日志
+    println!("WebUI: {}", server_state.webui_session.login_url(port));
+
+    runtime::block_on(async move {
+        wait_for_shutdown_signal().await;
+
+        log_info!("正在停止所有插件...");
+        server_state
+            .plugin_manager
+            .stop_all_plugins_and_wait(Duration::from_secs(8))
+            .await;
+
+        server_state.plugin_manager.cleanup_tmp_apps().await;
+    });
+
+    log_info!("框架已退出");
+}
+
+#[cfg(unix)]
+async fn wait_for_shutdown_signal() {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut sigterm = match signal(SignalKind::terminate()) {
+        Ok(s) => s,
+        Err(e) => {
+            log_warn!("Failed to listen for SIGTERM: {}", e);
+            let _ = tokio::signal::ctrl_c().await;
+            return;
+        }
+    };
+
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => {
+            log_info!("收到 SIGINT，准备退出");
+        }
+        _ = sigterm.recv() => {
+            log_info!("收到 SIGTERM，准备退出");
+        }
+    }
+}
+
+#[cfg(not(unix))]
+async fn wait_for_shutdown_signal() {
+    let _ = tokio::signal::ctrl_c().await;
+    log_info!("收到 Ctrl+C，准备退出");
+}
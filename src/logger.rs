@@ -1,14 +1,27 @@
 use chrono::Local;
-use once_cell::sync::Lazy;
-use serde::Serialize;
-use std::collections::VecDeque;
-use std::sync::{Arc, Mutex};
+use once_cell::sync::{Lazy, OnceCell};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex, RwLock};
 use tokio::sync::broadcast;
-use tracing_subscriber::{fmt, prelude::*, EnvFilter};
+use tracing_subscriber::{filter::filter_fn, fmt, prelude::*, reload, EnvFilter, Registry};
 
-#[derive(Clone, Debug, Serialize)]
+/// 单个日志文件的大小上限，超过后在同一天内新开一个文件
+const MAX_LOG_FILE_BYTES: u64 = 8 * 1024 * 1024;
+/// 日志文件保留的天数
+const LOG_RETENTION_DAYS: i64 = 14;
+const LOG_FILE_PREFIX: &str = "yuyubot-";
+const DEFAULT_QUERY_LIMIT: usize = 200;
+const MAX_QUERY_LIMIT: usize = 2000;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct LogEntry {
     pub time: String,
+    /// 毫秒级 Unix 时间戳
+    #[serde(default)]
+    pub timestamp: i64,
     pub level: String,
     pub source: String,
     pub message: String,
@@ -19,6 +32,121 @@ pub struct LoggerState {
     tx: broadcast::Sender<LogEntry>,
 }
 
+fn default_log_level() -> String {
+    "info".to_string()
+}
+
+/// 各日志来源的最低级别，保存在 `system.json` 中
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LogLevelConfig {
+    /// 没有单独配置的来源使用的最低级别
+    #[serde(default = "default_log_level")]
+    pub default: String,
+    /// 按来源（如 `[核心]`）覆盖最低级别
+    #[serde(default)]
+    pub sources: HashMap<String, String>,
+}
+
+impl Default for LogLevelConfig {
+    fn default() -> Self {
+        Self {
+            default: default_log_level(),
+            sources: HashMap::new(),
+        }
+    }
+}
+
+impl LogLevelConfig {
+    /// 丢弃无法识别的级别
+    pub fn normalized(mut self) -> Self {
+        if parse_level(&self.default).is_none() {
+            self.default = default_log_level();
+        }
+        self.sources
+            .retain(|source, level| !source.trim().is_empty() && parse_level(level).is_some());
+        self
+    }
+}
+
+/// 解析级别名称，不区分大小写
+fn parse_level(level: &str) -> Option<tracing::Level> {
+    [
+        tracing::Level::ERROR,
+        tracing::Level::WARN,
+        tracing::Level::INFO,
+        tracing::Level::DEBUG,
+        tracing::Level::TRACE,
+    ]
+    .into_iter()
+    .find(|candidate| candidate.as_str().eq_ignore_ascii_case(level))
+}
+
+/// 预先解析好的各来源最低级别，过滤每条日志时不再处理字符串
+#[derive(Debug)]
+struct LevelFilters {
+    default: tracing::Level,
+    sources: HashMap<String, tracing::Level>,
+}
+
+impl LevelFilters {
+    fn new(config: &LogLevelConfig) -> Self {
+        Self {
+            default: parse_level(&config.default).unwrap_or(tracing::Level::INFO),
+            sources: config
+                .sources
+                .iter()
+                .filter_map(|(source, level)| Some((source.clone(), parse_level(level)?)))
+                .collect(),
+        }
+    }
+
+    /// tracing 中越详细的级别越大
+    fn enabled(&self, level: &tracing::Level, source: &str) -> bool {
+        level <= self.sources.get(source).unwrap_or(&self.default)
+    }
+
+    /// 所有来源中最详细的级别，更详细的日志在 EnvFilter 中就会被丢弃
+    fn most_verbose(&self) -> tracing::Level {
+        self.sources
+            .values()
+            .copied()
+            .fold(self.default, std::cmp::max)
+    }
+}
+
+static LOG_LEVELS: Lazy<RwLock<LevelFilters>> =
+    Lazy::new(|| RwLock::new(LevelFilters::new(&LogLevelConfig::default())));
+
+/// 用于在级别配置变化时替换 EnvFilter
+static ENV_FILTER: OnceCell<reload::Handle<EnvFilter, Registry>> = OnceCell::new();
+
+fn build_env_filter(max_level: tracing::Level) -> EnvFilter {
+    EnvFilter::from_default_env()
+        .add_directive(max_level.into())
+        .add_directive("rocket=off".parse().unwrap()) // 完全屏蔽 Rocket 的所有日志
+        .add_directive("hyper=off".parse().unwrap()) // 屏蔽底层 hyper 库的日志
+        .add_directive("tao=off".parse().unwrap()) // 屏蔽 tao 事件循环警告
+}
+
+pub fn set_log_levels(config: LogLevelConfig) {
+    let filters = LevelFilters::new(&config.normalized());
+    let max_level = filters.most_verbose();
+    if let Ok(mut levels) = LOG_LEVELS.write() {
+        *levels = filters;
+    }
+    if let Some(handle) = ENV_FILTER.get() {
+        let _ = handle.reload(build_env_filter(max_level));
+    }
+}
+
+fn is_level_enabled(level: &tracing::Level, source: &str) -> bool {
+    LOG_LEVELS
+        .read()
+        .map(|levels| levels.enabled(level, source))
+        .unwrap_or(true)
+}
+
 static LOGGER: Lazy<Arc<Mutex<LoggerState>>> = Lazy::new(|| {
     let (tx, _) = broadcast::channel(100);
     Arc::new(Mutex::new(LoggerState {
@@ -27,8 +155,6 @@ static LOGGER: Lazy<Arc<Mutex<LoggerState>>> = Lazy::new(|| {
     }))
 });
 
-use once_cell::sync::OnceCell;
-
 static LOGGER_INIT: OnceCell<()> = OnceCell::new();
 
 struct AppLogLayer;
@@ -77,19 +203,26 @@ impl tracing::field::Visit for MessageVisitor {
 
 pub fn init_logger() {
     LOGGER_INIT.get_or_init(|| {
-        let filter = EnvFilter::from_default_env()
-            .add_directive(tracing::Level::INFO.into())
-            .add_directive("rocket=off".parse().unwrap()) // 完全屏蔽 Rocket 的所有日志
-            .add_directive("hyper=off".parse().unwrap()) // 屏蔽底层 hyper 库的日志
-            .add_directive("tao=off".parse().unwrap()); // 屏蔽 tao 事件循环警告
+        // EnvFilter 只放行到配置中最详细的级别，再由 `system.json` 中按来源配置的级别细分
+        let max_level = LOG_LEVELS
+            .read()
+            .map(|levels| levels.most_verbose())
+            .unwrap_or(tracing::Level::INFO);
+        let (filter, handle) = reload::Layer::new(build_env_filter(max_level));
+        let _ = ENV_FILTER.set(handle);
 
         let registry = tracing_subscriber::registry()
+            .with(filter)
             .with(AppLogLayer)
-            .with(filter);
+            .with(filter_fn(|metadata| {
+                is_level_enabled(metadata.level(), metadata.target())
+            }));
+
+        start_file_sink(crate::runtime::get_exe_dir().join("logs"));
 
-        // 仅在环境变量 YUYU_LOG_STDERR 设置时才输出到 stderr
+        // 仅在环境变量 YUYU_LOG_STDERR 设置或无界面模式下才输出到 stderr
         // 避免在 Windows GUI 模式下 hijack 插件控制台输出
-        if std::env::var("YUYU_LOG_STDERR").is_ok() {
+        if std::env::var("YUYU_LOG_STDERR").is_ok() || crate::runtime::is_headless_mode() {
             registry
                 .with(fmt::layer().with_writer(std::io::stderr).with_ansi(true))
                 .init();
@@ -100,13 +233,21 @@ pub fn init_logger() {
 }
 
 pub fn log_message(level: &str, source: &str, message: String) {
+    let now = Local::now();
     let entry = LogEntry {
-        time: Local::now().format("%Y-%m-%d %H:%M:%S%.3f").to_string(),
+        time: now.format("%Y-%m-%d %H:%M:%S%.3f").to_string(),
+        timestamp: now.timestamp_millis(),
         level: level.to_string(),
         source: source.to_string(),
         message,
     };
 
+    if let Some(sink) = FILE_SINK.get() {
+        if let Ok(sink) = sink.lock() {
+            let _ = sink.send(entry.clone());
+        }
+    }
+
     if let Ok(mut state) = LOGGER.lock() {
         if state.logs.len() >= 1000 {
             state.logs.pop_front();
@@ -116,6 +257,259 @@ pub fn log_message(level: &str, source: &str, message: String) {
     }
 }
 
+static FILE_SINK: OnceCell<Mutex<mpsc::Sender<LogEntry>>> = OnceCell::new();
+static LOG_DIR: OnceCell<PathBuf> = OnceCell::new();
+
+/// 启动后台写入线程，日志按天写入 `logs/yuyubot-<日期>.jsonl`，单个文件过大时在同一天内继续编号
+fn start_file_sink(dir: PathBuf) {
+    let (tx, rx) = mpsc::channel::<LogEntry>();
+    if FILE_SINK.set(Mutex::new(tx)).is_err() {
+        return;
+    }
+    let _ = LOG_DIR.set(dir.clone());
+
+    std::thread::spawn(move || {
+        let mut writer = LogFileWriter::new(dir);
+        while let Ok(entry) = rx.recv() {
+            writer.write(&entry);
+            // 批量写入积压的日志后再刷新
+            while let Ok(entry) = rx.try_recv() {
+                writer.write(&entry);
+            }
+            writer.flush();
+        }
+    });
+}
+
+struct LogFileWriter {
+    dir: PathBuf,
+    max_file_bytes: u64,
+    date: String,
+    index: u32,
+    size: u64,
+    file: Option<std::io::BufWriter<std::fs::File>>,
+}
+
+impl LogFileWriter {
+    fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            max_file_bytes: MAX_LOG_FILE_BYTES,
+            date: String::new(),
+            index: 0,
+            size: 0,
+            file: None,
+        }
+    }
+
+    fn write(&mut self, entry: &LogEntry) {
+        let Ok(mut line) = serde_json::to_string(entry) else {
+            return;
+        };
+        line.push('\n');
+
+        let date = entry.time.get(..10).unwrap_or_default().to_string();
+        if date != self.date {
+            self.flush();
+            self.file = None;
+            self.date = date;
+            self.index = last_file_index(&self.dir, &self.date);
+            remove_expired_files(&self.dir);
+        }
+        if self.file.is_some() && self.size >= self.max_file_bytes {
+            self.flush();
+            self.file = None;
+            self.index += 1;
+        }
+
+        if self.file.is_none() && !self.open() {
+            return;
+        }
+        if let Some(file) = self.file.as_mut() {
+            if file.write_all(line.as_bytes()).is_err() {
+                self.file = None;
+                return;
+            }
+            self.size += line.len() as u64;
+        }
+    }
+
+    fn open(&mut self) -> bool {
+        if std::fs::create_dir_all(&self.dir).is_err() {
+            return false;
+        }
+        loop {
+            let path = log_file_path(&self.dir, &self.date, self.index);
+            let Ok(file) = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+            else {
+                return false;
+            };
+            let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+            if size >= self.max_file_bytes {
+                self.index += 1;
+                continue;
+            }
+            self.size = size;
+            self.file = Some(std::io::BufWriter::new(file));
+            return true;
+        }
+    }
+
+    fn flush(&mut self) {
+        if let Some(file) = self.file.as_mut() {
+            if file.flush().is_err() {
+                self.file = None;
+            }
+        }
+    }
+}
+
+fn log_file_path(dir: &Path, date: &str, index: u32) -> PathBuf {
+    if index == 0 {
+        dir.join(format!("{}{}.jsonl", LOG_FILE_PREFIX, date))
+    } else {
+        dir.join(format!("{}{}.{}.jsonl", LOG_FILE_PREFIX, date, index))
+    }
+}
+
+/// 解析日志文件名，返回（日期, 编号）
+fn parse_log_file_name(name: &str) -> Option<(String, u32)> {
+    let stem = name.strip_prefix(LOG_FILE_PREFIX)?.strip_suffix(".jsonl")?;
+    let (date, index) = match stem.split_once('.') {
+        Some((date, index)) => (date, index.parse().ok()?),
+        None => (stem, 0),
+    };
+    chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+    Some((date.to_string(), index))
+}
+
+/// 按时间从新到旧列出所有日志文件
+fn list_log_files(dir: &Path) -> Vec<(String, u32, PathBuf)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<(String, u32, PathBuf)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let (date, index) = parse_log_file_name(&name)?;
+            Some((date, index, entry.path()))
+        })
+        .collect();
+    files.sort_by(|a, b| (&b.0, b.1).cmp(&(&a.0, a.1)));
+    files
+}
+
+fn last_file_index(dir: &Path, date: &str) -> u32 {
+    list_log_files(dir)
+        .into_iter()
+        .filter(|(file_date, _, _)| file_date == date)
+        .map(|(_, index, _)| index)
+        .max()
+        .unwrap_or(0)
+}
+
+fn remove_expired_files(dir: &Path) {
+    let cutoff = (Local::now() - chrono::Duration::days(LOG_RETENTION_DAYS))
+        .format("%Y-%m-%d")
+        .to_string();
+    for (date, _, path) in list_log_files(dir) {
+        if date < cutoff {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// 历史日志查询条件
+#[derive(Debug, Default, rocket::FromForm)]
+pub struct LogQuery {
+    /// 最低级别，如 `warn` 表示只返回 warn 和 error
+    pub level: Option<String>,
+    pub source: Option<String>,
+    /// 毫秒级 Unix 时间戳（含）
+    pub since: Option<i64>,
+    /// 毫秒级 Unix 时间戳（含）
+    pub until: Option<i64>,
+    /// 跳过最新的若干条匹配记录，用于分页
+    pub offset: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+impl LogQuery {
+    pub fn is_empty(&self) -> bool {
+        self.level.is_none()
+            && self.source.is_none()
+            && self.since.is_none()
+            && self.until.is_none()
+            && self.offset.is_none()
+            && self.limit.is_none()
+    }
+
+    fn matches(&self, entry: &LogEntry) -> bool {
+        let min_level = self.level.as_deref().and_then(parse_level);
+        min_level.is_none_or(|min_level| {
+            parse_level(&entry.level).is_some_and(|level| level <= min_level)
+        }) && self
+            .source
+            .as_deref()
+            .is_none_or(|source| entry.source == source)
+            && self.until.is_none_or(|until| entry.timestamp <= until)
+    }
+}
+
+/// 从日志文件中查询历史日志，结果与内存中的日志一样按时间从旧到新排列
+pub fn query_log_history(query: &LogQuery) -> Result<Vec<LogEntry>, String> {
+    match LOG_DIR.get() {
+        Some(dir) => query_log_dir(dir, query),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// 从最新的记录开始向前扫描，凑够一页后再翻转为从旧到新的顺序
+fn query_log_dir(dir: &Path, query: &LogQuery) -> Result<Vec<LogEntry>, String> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_QUERY_LIMIT)
+        .clamp(1, MAX_QUERY_LIMIT);
+    let mut skip = query.offset.unwrap_or(0);
+    let mut result = Vec::new();
+
+    'files: for (_, _, path) in list_log_files(dir) {
+        let file = match std::fs::File::open(&path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(format!("Failed to read log file: {}", e)),
+        };
+        let lines: Vec<String> = BufReader::new(file).lines().map_while(Result::ok).collect();
+
+        for line in lines.iter().rev() {
+            let Ok(entry) = serde_json::from_str::<LogEntry>(line) else {
+                continue;
+            };
+            if query.since.is_some_and(|since| entry.timestamp < since) {
+                break 'files;
+            }
+            if !query.matches(&entry) {
+                continue;
+            }
+            if skip > 0 {
+                skip -= 1;
+                continue;
+            }
+            result.push(entry);
+            if result.len() >= limit {
+                break 'files;
+            }
+        }
+    }
+
+    result.reverse();
+    Ok(result)
+}
+
 pub fn get_logs() -> Vec<LogEntry> {
     LOGGER
         .lock()
@@ -167,3 +561,167 @@ macro_rules! log_debug {
         tracing::debug!(target: "[核心]", $($arg)*);
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    /// 每个测试使用独立的临时目录，结束时删除
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new() -> Self {
+            let suffix: u64 = rand::rng().random();
+            Self(std::env::temp_dir().join(format!("yuyubot-log-test-{:016x}", suffix)))
+        }
+
+        fn file_names(&self) -> Vec<String> {
+            let mut names: Vec<String> = std::fs::read_dir(&self.0)
+                .unwrap()
+                .flatten()
+                .map(|entry| entry.file_name().to_string_lossy().to_string())
+                .collect();
+            names.sort();
+            names
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn entry(date: &str, timestamp: i64, level: &str) -> LogEntry {
+        LogEntry {
+            time: format!("{} 12:00:00.000", date),
+            timestamp,
+            level: level.to_string(),
+            source: "[核心]".to_string(),
+            message: format!("message {}", timestamp),
+        }
+    }
+
+    fn timestamps(entries: &[LogEntry]) -> Vec<i64> {
+        entries.iter().map(|entry| entry.timestamp).collect()
+    }
+
+    #[test]
+    fn parse_log_file_name_accepts_dated_and_numbered_files() {
+        assert_eq!(
+            parse_log_file_name("yuyubot-2026-10-17.jsonl"),
+            Some(("2026-10-17".to_string(), 0))
+        );
+        assert_eq!(
+            parse_log_file_name("yuyubot-2026-10-17.3.jsonl"),
+            Some(("2026-10-17".to_string(), 3))
+        );
+        assert_eq!(parse_log_file_name("yuyubot-2026-13-01.jsonl"), None);
+        assert_eq!(parse_log_file_name("yuyubot-2026-10-17.x.jsonl"), None);
+        assert_eq!(parse_log_file_name("yuyubot-2026-10-17.log"), None);
+        assert_eq!(parse_log_file_name("other-2026-10-17.jsonl"), None);
+    }
+
+    #[test]
+    fn level_filters_use_source_overrides_and_most_verbose_level() {
+        let filters = LevelFilters::new(
+            &LogLevelConfig {
+                default: "WARN".to_string(),
+                sources: HashMap::from([
+                    ("[插件]".to_string(), "debug".to_string()),
+                    ("[核心]".to_string(), "verbose".to_string()),
+                ]),
+            }
+            .normalized(),
+        );
+
+        assert!(filters.enabled(&tracing::Level::ERROR, "[核心]"));
+        assert!(!filters.enabled(&tracing::Level::INFO, "[核心]"));
+        assert!(filters.enabled(&tracing::Level::DEBUG, "[插件]"));
+        assert!(!filters.enabled(&tracing::Level::TRACE, "[插件]"));
+        assert_eq!(filters.most_verbose(), tracing::Level::DEBUG);
+    }
+
+    #[test]
+    fn writer_rolls_over_by_date_and_size() {
+        let dir = TempDir::new();
+        let today = Local::now().format("%Y-%m-%d").to_string();
+        let yesterday = (Local::now() - chrono::Duration::days(1))
+            .format("%Y-%m-%d")
+            .to_string();
+
+        let mut writer = LogFileWriter::new(dir.0.clone());
+        writer.max_file_bytes = 1;
+        writer.write(&entry(&yesterday, 1, "info"));
+        writer.write(&entry(&today, 2, "info"));
+        writer.write(&entry(&today, 3, "info"));
+        writer.flush();
+        drop(writer);
+
+        assert_eq!(
+            dir.file_names(),
+            vec![
+                format!("yuyubot-{}.jsonl", yesterday),
+                format!("yuyubot-{}.1.jsonl", today),
+                format!("yuyubot-{}.jsonl", today),
+            ]
+        );
+
+        // 重启后从当天最后一个文件继续写入，已满的文件不会再追加
+        let mut writer = LogFileWriter::new(dir.0.clone());
+        writer.max_file_bytes = 1;
+        writer.write(&entry(&today, 4, "info"));
+        writer.flush();
+        assert!(dir
+            .file_names()
+            .contains(&format!("yuyubot-{}.2.jsonl", today)));
+    }
+
+    #[test]
+    fn query_pages_from_newest_and_returns_oldest_first() {
+        let dir = TempDir::new();
+        let today = Local::now().format("%Y-%m-%d").to_string();
+        let mut writer = LogFileWriter::new(dir.0.clone());
+        writer.max_file_bytes = 200;
+        for timestamp in 1..=6 {
+            let level = if timestamp % 2 == 0 { "warn" } else { "debug" };
+            writer.write(&entry(&today, timestamp, level));
+        }
+        writer.flush();
+        assert!(dir.file_names().len() > 1);
+
+        let query = |query: LogQuery| timestamps(&query_log_dir(&dir.0, &query).unwrap());
+        assert_eq!(
+            query(LogQuery {
+                limit: Some(2),
+                ..Default::default()
+            }),
+            vec![5, 6]
+        );
+        assert_eq!(
+            query(LogQuery {
+                offset: Some(2),
+                limit: Some(2),
+                ..Default::default()
+            }),
+            vec![3, 4]
+        );
+        assert_eq!(
+            query(LogQuery {
+                since: Some(2),
+                until: Some(4),
+                ..Default::default()
+            }),
+            vec![2, 3, 4]
+        );
+        assert_eq!(
+            query(LogQuery {
+                level: Some("warn".to_string()),
+                offset: Some(1),
+                ..Default::default()
+            }),
+            vec![2, 4]
+        );
+    }
+}
@@ -3,22 +3,30 @@
 #[macro_use]
 mod logger;
 mod error;
+mod headless;
 mod plus;
+mod rotating_log;
 mod runtime;
 mod server;
+#[cfg(windows)]
 mod window;
 
 use rust_embed::Embed;
+#[cfg(windows)]
 use std::ffi::OsStr;
+#[cfg(windows)]
 use std::os::windows::ffi::OsStrExt;
 
+#[cfg(windows)]
 use windows_sys::Win32::Foundation::{CloseHandle, GetLastError, ERROR_ALREADY_EXISTS, HANDLE};
+#[cfg(windows)]
 use windows_sys::Win32::System::Threading::{CreateEventW, CreateMutexW, SetEvent};
 
 #[derive(Embed)]
 #[folder = "res/"]
 pub struct Assets;
 
+#[cfg(windows)]
 mod windows_console {
     use windows_sys::Win32::System::Console::{
         SetStdHandle, STD_ERROR_HANDLE, STD_INPUT_HANDLE, STD_OUTPUT_HANDLE,
@@ -36,11 +44,13 @@ mod windows_console {
     }
 }
 
+#[cfg(windows)]
 struct SingleInstanceGuard {
     mutex_handle: usize,
     activate_event_handle: usize,
 }
 
+#[cfg(windows)]
 impl Drop for SingleInstanceGuard {
     fn drop(&mut self) {
         if self.activate_event_handle != 0 {
@@ -52,6 +62,7 @@ impl Drop for SingleInstanceGuard {
     }
 }
 
+#[cfg(windows)]
 fn fnv1a_hash(input: &str) -> u64 {
     let mut hash: u64 = 0xcbf29ce484222325;
     for b in input.bytes() {
@@ -61,6 +72,7 @@ fn fnv1a_hash(input: &str) -> u64 {
     hash
 }
 
+#[cfg(windows)]
 fn acquire_single_instance_or_exit() -> Result<SingleInstanceGuard, String> {
     let exe_path =
         std::env::current_exe().map_err(|e| format!("获取当前可执行文件路径失败: {}", e))?;
@@ -113,6 +125,19 @@ fn acquire_single_instance_or_exit() -> Result<SingleInstanceGuard, String> {
 
 fn main() {
     logger::init_logger();
+
+    #[cfg(windows)]
+    if !runtime::is_headless_mode() {
+        run_desktop();
+        return;
+    }
+
+    headless::run();
+}
+
+/// 桌面模式：单实例检测、托盘与 WebView 主窗口
+#[cfg(windows)]
+fn run_desktop() {
     let is_auto_start_launch = runtime::is_auto_start_launch();
 
     let single_instance_guard = match acquire_single_instance_or_exit() {
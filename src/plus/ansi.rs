@@ -0,0 +1,338 @@
+use serde::{Deserialize, Serialize};
+
+const ESC: char = '\u{1b}';
+const BEL: char = '\u{7}';
+
+/// 终端颜色：`0`-`255` 为调色板序号（`0`-`15` 为标准色与亮色），数组为 24 位真彩色
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum AnsiColor {
+    Palette(u8),
+    Rgb(u8, u8, u8),
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnsiStyle {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fg: Option<AnsiColor>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bg: Option<AnsiColor>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub bold: bool,
+}
+
+impl AnsiStyle {
+    fn is_plain(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// 应用一组 SGR 参数，不支持的参数（斜体、下划线等）和无法解析的参数会被忽略。
+    /// 参数之间用 `;` 分隔，同一参数的子参数用 `:` 分隔（如 ITU T.416 的 `38:2::r:g:b`）
+    fn apply_sgr(&mut self, params: &str) {
+        let params: Vec<Vec<Option<u16>>> = params
+            .split(';')
+            .map(|param| param.split(':').map(parse_sgr_number).collect())
+            .collect();
+
+        let mut i = 0;
+        while i < params.len() {
+            let param = &params[i];
+            i += 1;
+            let Some(code) = param[0] else {
+                continue;
+            };
+            match code {
+                0 => *self = Self::default(),
+                1 => self.bold = true,
+                22 => self.bold = false,
+                30..=37 => self.fg = Some(AnsiColor::Palette((code - 30) as u8)),
+                39 => self.fg = None,
+                40..=47 => self.bg = Some(AnsiColor::Palette((code - 40) as u8)),
+                49 => self.bg = None,
+                90..=97 => self.fg = Some(AnsiColor::Palette((code - 90 + 8) as u8)),
+                100..=107 => self.bg = Some(AnsiColor::Palette((code - 100 + 8) as u8)),
+                38 | 48 => {
+                    let color = if param.len() > 1 {
+                        // 冒号形式的颜色全部在子参数中，不占用后面的参数
+                        colon_color(&param[1..])
+                    } else {
+                        let rest: Vec<Option<u16>> =
+                            params[i..].iter().map(|param| param[0]).collect();
+                        let (color, used) = extended_color(&rest);
+                        i += used;
+                        color
+                    };
+                    if let Some(color) = color {
+                        if code == 38 {
+                            self.fg = Some(color);
+                        } else {
+                            self.bg = Some(color);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// 省略的参数按 0 处理，无法解析的参数返回 `None`
+fn parse_sgr_number(param: &str) -> Option<u16> {
+    if param.is_empty() {
+        Some(0)
+    } else {
+        param.parse().ok()
+    }
+}
+
+fn channel(values: &[Option<u16>], index: usize) -> Option<u8> {
+    values
+        .get(index)
+        .copied()
+        .flatten()
+        .and_then(|value| u8::try_from(value).ok())
+}
+
+/// 解析 `38;5;n` / `38;2;r;g;b` 之后的参数，返回颜色和消耗的参数个数
+fn extended_color(values: &[Option<u16>]) -> (Option<AnsiColor>, usize) {
+    match values.first() {
+        Some(Some(5)) => (channel(values, 1).map(AnsiColor::Palette), 2),
+        Some(Some(2)) if values.len() >= 4 => (rgb(values, 1), 4),
+        Some(Some(2)) => (None, values.len()),
+        _ => (None, 0),
+    }
+}
+
+/// 解析 `38:5:n`、`38:2:r:g:b` 以及带色彩空间序号的 `38:2:<色彩空间>:r:g:b` 中 38 之后的子参数
+fn colon_color(values: &[Option<u16>]) -> Option<AnsiColor> {
+    match values.first() {
+        Some(Some(5)) => channel(values, 1).map(AnsiColor::Palette),
+        Some(Some(2)) if values.len() >= 5 => rgb(values, 2),
+        Some(Some(2)) => rgb(values, 1),
+        _ => None,
+    }
+}
+
+fn rgb(values: &[Option<u16>], start: usize) -> Option<AnsiColor> {
+    Some(AnsiColor::Rgb(
+        channel(values, start)?,
+        channel(values, start + 1)?,
+        channel(values, start + 2)?,
+    ))
+}
+
+/// 一段样式相同的文本
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnsiSpan {
+    pub text: String,
+    #[serde(flatten)]
+    pub style: AnsiStyle,
+}
+
+/// 解析一行终端输出，返回去除控制序列后的纯文本和按样式切分的文本段；
+/// `style` 为上一行结束时的样式，解析后更新为本行结束时的样式，使跨行的颜色得以延续。
+/// 整行都没有样式时文本段为空，节省内存
+pub fn parse_line(raw: &str, style: &mut AnsiStyle) -> (String, Vec<AnsiSpan>) {
+    let mut plain = String::with_capacity(raw.len());
+    let mut spans: Vec<AnsiSpan> = Vec::new();
+    let mut chars = raw.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != ESC {
+            if c.is_control() && c != '\t' {
+                continue;
+            }
+            plain.push(c);
+            match spans.last_mut() {
+                Some(span) if span.style == *style => span.text.push(c),
+                _ => spans.push(AnsiSpan {
+                    text: c.to_string(),
+                    style: *style,
+                }),
+            }
+            continue;
+        }
+
+        match chars.peek().copied() {
+            // CSI：参数和中间字节之后以 0x40-0x7E 结尾，只有 `m` (SGR) 影响样式
+            Some('[') => {
+                chars.next();
+                let mut params = String::new();
+                for c in chars.by_ref() {
+                    if ('\u{40}'..='\u{7e}').contains(&c) {
+                        if c == 'm' {
+                            style.apply_sgr(&params);
+                        }
+                        break;
+                    }
+                    params.push(c);
+                }
+            }
+            // OSC（如设置窗口标题）：以 BEL 或 `ESC \` 结尾
+            Some(']') => {
+                chars.next();
+                while let Some(c) = chars.next() {
+                    if c == BEL || (c == ESC && chars.next_if_eq(&'\\').is_some()) {
+                        break;
+                    }
+                }
+            }
+            // nF（如切换字符集的 `ESC ( B`）：若干 0x20-0x2F 的中间字节后跟一个结尾字节
+            Some('\u{20}'..='\u{2f}') => {
+                while chars
+                    .next_if(|c| ('\u{20}'..='\u{2f}').contains(c))
+                    .is_some()
+                {}
+                chars.next_if(|c| ('\u{30}'..='\u{7e}').contains(c));
+            }
+            // Fp / Fe / Fs（如 `ESC =`、`ESC 7`、`ESC M`）只有一个字节
+            Some('\u{30}'..='\u{7e}') => {
+                chars.next();
+            }
+            // 不完整的转义序列只丢弃 ESC 本身，后面的字符照常输出
+            _ => {}
+        }
+    }
+
+    if spans.iter().all(|span| span.style.is_plain()) {
+        spans.clear();
+    }
+    (plain, spans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(raw: &str) -> (String, Vec<AnsiSpan>) {
+        parse_line(raw, &mut AnsiStyle::default())
+    }
+
+    fn fg(color: u8) -> AnsiStyle {
+        AnsiStyle {
+            fg: Some(AnsiColor::Palette(color)),
+            ..AnsiStyle::default()
+        }
+    }
+
+    #[test]
+    fn plain_text_has_no_spans() {
+        let (line, spans) = parse("hello\tworld");
+        assert_eq!(line, "hello\tworld");
+        assert!(spans.is_empty());
+    }
+
+    #[test]
+    fn sgr_colours_split_spans() {
+        let (line, spans) = parse("\x1b[31mred\x1b[0m plain \x1b[1;92mbold\x1b[m");
+        assert_eq!(line, "red plain bold");
+        assert_eq!(spans.len(), 3);
+        assert_eq!(spans[0].text, "red");
+        assert_eq!(spans[0].style, fg(1));
+        assert_eq!(spans[1].text, " plain ");
+        assert!(spans[1].style.is_plain());
+        assert_eq!(spans[2].text, "bold");
+        assert_eq!(
+            spans[2].style,
+            AnsiStyle {
+                bold: true,
+                ..fg(10)
+            }
+        );
+    }
+
+    #[test]
+    fn extended_colours() {
+        let (_, spans) = parse("\x1b[38;5;208;48;2;1;2;3mx");
+        assert_eq!(spans[0].style.fg, Some(AnsiColor::Palette(208)));
+        assert_eq!(spans[0].style.bg, Some(AnsiColor::Rgb(1, 2, 3)));
+    }
+
+    #[test]
+    fn colon_separated_colours() {
+        let (_, spans) = parse("\x1b[38:2::10:20:30;48:5:17mx");
+        assert_eq!(spans[0].style.fg, Some(AnsiColor::Rgb(10, 20, 30)));
+        assert_eq!(spans[0].style.bg, Some(AnsiColor::Palette(17)));
+
+        // 色彩空间序号不为空时同样跳过，省略色彩空间的写法也能识别
+        let (_, spans) = parse("\x1b[38:2:1:4:5:6;48:2:7:8:9;1mx");
+        assert_eq!(spans[0].style.fg, Some(AnsiColor::Rgb(4, 5, 6)));
+        assert_eq!(spans[0].style.bg, Some(AnsiColor::Rgb(7, 8, 9)));
+        assert!(spans[0].style.bold);
+    }
+
+    #[test]
+    fn invalid_codes_are_skipped_instead_of_resetting() {
+        let (_, spans) = parse("\x1b[31;1m\x1b[x;4?mtext");
+        assert_eq!(
+            spans[0].style,
+            AnsiStyle {
+                bold: true,
+                ..fg(1)
+            }
+        );
+
+        // 超出范围的颜色分量不生效，也不影响后面的参数
+        let (_, spans) = parse("\x1b[32m\x1b[38:2::300:0:0;1mtext");
+        assert_eq!(
+            spans[0].style,
+            AnsiStyle {
+                bold: true,
+                ..fg(2)
+            }
+        );
+
+        // 省略的参数仍按 0 处理
+        let (_, spans) = parse("\x1b[31m\x1b[;1mtext");
+        assert_eq!(
+            spans[0].style,
+            AnsiStyle {
+                bold: true,
+                ..AnsiStyle::default()
+            }
+        );
+    }
+
+    #[test]
+    fn non_sgr_sequences_are_stripped() {
+        let (line, spans) = parse("\x1b]0;title\x07\x1b[2K\x1b[1Gdone\x1b]8;;\x1b\\");
+        assert_eq!(line, "done");
+        assert!(spans.is_empty());
+    }
+
+    #[test]
+    fn nf_and_single_byte_escapes_do_not_leak() {
+        let (line, _) = parse("\x1b(Ba\x1b)0b\x1b=c\x1b>d\x1b7e\x1b8f\x1bMg");
+        assert_eq!(line, "abcdefg");
+
+        let (line, _) = parse("\x1b %Gtext");
+        assert_eq!(line, "text");
+    }
+
+    #[test]
+    fn incomplete_escape_keeps_following_text() {
+        let (line, _) = parse("a\x1b中文");
+        assert_eq!(line, "a中文");
+    }
+
+    #[test]
+    fn style_carries_across_lines() {
+        let mut style = AnsiStyle::default();
+
+        let (_, spans) = parse_line("\x1b[33mfirst", &mut style);
+        assert_eq!(spans[0].style, fg(3));
+        assert_eq!(style, fg(3));
+
+        let (line, spans) = parse_line("second", &mut style);
+        assert_eq!(line, "second");
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].style, fg(3));
+
+        let (_, spans) = parse_line("third\x1b[0m", &mut style);
+        assert_eq!(spans[0].style, fg(3));
+        assert!(style.is_plain());
+
+        let (_, spans) = parse_line("fourth", &mut style);
+        assert!(spans.is_empty());
+    }
+}
@@ -1,4 +1,5 @@
 use super::{PluginConfig, PluginManager};
+use crate::plus::plugin::{Plugin, RestartPolicy};
 
 impl PluginManager {
     pub(super) fn get_config_path(&self) -> std::path::PathBuf {
@@ -30,7 +31,7 @@ impl PluginManager {
     }
 
     pub async fn get_enabled_plugins(&self) -> Vec<String> {
-        let config = self.load_config().await;
+        let mut config = self.load_config().await;
         if config.enabled_plugins.is_empty() {
             return Vec::new();
         }
@@ -41,7 +42,7 @@ impl PluginManager {
         let mut config_changed = false;
 
         let plugins_root = self.get_plugins_root();
-        for plugin_id in config.enabled_plugins {
+        for plugin_id in std::mem::take(&mut config.enabled_plugins) {
             if plugins.contains_key(&plugin_id) {
                 loaded_enabled_plugins.push(plugin_id.clone());
                 new_config_enabled_plugins.push(plugin_id);
@@ -58,15 +59,49 @@ impl PluginManager {
         drop(plugins);
 
         if config_changed {
-            self.save_config(&PluginConfig {
-                enabled_plugins: new_config_enabled_plugins,
-            })
-            .await;
+            config.enabled_plugins = new_config_enabled_plugins;
+            self.save_config(&config).await;
         }
 
         loaded_enabled_plugins
     }
 
+    /// 插件生效的重启策略：`plugins.json` 中的覆盖优先，其次是 `app.json` 中的声明
+    pub(super) async fn get_restart_policy(&self, plugin: &Plugin) -> RestartPolicy {
+        let mut config = self.load_config().await;
+        config
+            .restart_policies
+            .remove(&plugin.id)
+            .or_else(|| plugin.manifest.restart.clone())
+            .unwrap_or_default()
+    }
+
+    pub(super) async fn set_approved_permissions_config(
+        &self,
+        plugin_id: &str,
+        permissions: Vec<String>,
+    ) {
+        let mut config = self.load_config().await;
+        if permissions.is_empty() {
+            config.approved_permissions.remove(plugin_id);
+        } else {
+            config
+                .approved_permissions
+                .insert(plugin_id.to_string(), permissions);
+        }
+        self.save_config(&config).await;
+    }
+
+    pub(super) async fn set_bot_bindings_config(&self, plugin_id: &str, bots: Vec<String>) {
+        let mut config = self.load_config().await;
+        if bots.is_empty() {
+            config.bot_bindings.remove(plugin_id);
+        } else {
+            config.bot_bindings.insert(plugin_id.to_string(), bots);
+        }
+        self.save_config(&config).await;
+    }
+
     pub async fn purge_enabled_plugin_if_absent(&self, plugin_id: &str) -> bool {
         if self.get_plugins_root().join(plugin_id).is_dir() {
             return false;
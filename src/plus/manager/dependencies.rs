@@ -0,0 +1,283 @@
+use super::{push_system_output, wait_tcp_ready, PluginManager};
+use crate::plus::plugin::{Plugin, ReadinessCheck};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+impl PluginManager {
+    /// 检查插件依赖的插件是否都在运行且已就绪
+    pub(super) async fn check_dependencies(&self, plugin: &Plugin) -> Result<(), String> {
+        for dependency_id in &plugin.manifest.depends_on {
+            let dependency = self.plugins.read().await.get(dependency_id).cloned();
+            let Some(dependency) = dependency else {
+                return Err(format!("Dependency '{}' is not installed", dependency_id));
+            };
+            if !dependency.is_process_alive().await {
+                return Err(format!("Dependency '{}' is not running", dependency_id));
+            }
+            if !dependency.is_ready().await {
+                return Err(format!("Dependency '{}' is not ready yet", dependency_id));
+            }
+        }
+        Ok(())
+    }
+
+    /// 插件进程启动后按 `ready` 声明标记就绪，`api` 方式由插件自己调用接口通知
+    pub(super) async fn watch_readiness(&self, plugin: &Arc<Plugin>, run_id: u64) {
+        match plugin.manifest.ready.clone() {
+            None => plugin.set_ready(true).await,
+            Some(ReadinessCheck::Api { .. }) => {}
+            Some(ReadinessCheck::Tcp { port, timeout_secs }) => {
+                let plugin = plugin.clone();
+                tokio::spawn(async move {
+                    let timeout = Duration::from_secs(timeout_secs);
+                    if wait_tcp_ready("127.0.0.1", port, timeout).await
+                        && plugin.is_current_run(run_id)
+                    {
+                        plugin.set_ready(true).await;
+                    }
+                });
+            }
+        }
+    }
+
+    /// 插件通过 `POST /api/plugin/ready` 通知已就绪
+    pub async fn mark_plugin_ready(&self, plugin_id: &str) -> Result<(), String> {
+        let plugin = self
+            .plugins
+            .read()
+            .await
+            .get(plugin_id)
+            .cloned()
+            .ok_or("Plugin not found".to_string())?;
+
+        if !plugin.is_process_alive().await {
+            return Err("Plugin is not running".to_string());
+        }
+
+        if !plugin.is_ready().await {
+            plugin.set_ready(true).await;
+            log_info!("插件 {}({}) 已就绪", plugin.manifest.name, plugin_id);
+        }
+        Ok(())
+    }
+
+    /// 等待插件就绪，超时或进程提前退出时返回错误
+    async fn wait_plugin_ready(&self, plugin: &Plugin) -> Result<(), String> {
+        let timeout = plugin
+            .manifest
+            .ready
+            .as_ref()
+            .map(ReadinessCheck::timeout)
+            .unwrap_or_default();
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            if plugin.is_ready().await {
+                return Ok(());
+            }
+            if !plugin.is_process_alive().await {
+                return Err("Plugin exited before becoming ready".to_string());
+            }
+            if Instant::now() >= deadline {
+                return Err(format!(
+                    "Plugin did not become ready within {} s",
+                    timeout.as_secs()
+                ));
+            }
+            tokio::time::sleep(Duration::from_millis(200)).await;
+        }
+    }
+
+    /// 按已安装插件声明的依赖排序，返回排好序的插件以及因循环依赖无法排序的插件
+    async fn resolve_start_order(&self, plugin_ids: &[String]) -> (Vec<String>, Vec<String>) {
+        let plugins = self.plugins.read().await;
+        let dependencies: HashMap<String, Vec<String>> = plugin_ids
+            .iter()
+            .filter_map(|plugin_id| {
+                let plugin = plugins.get(plugin_id)?;
+                Some((plugin_id.clone(), plugin.manifest.depends_on.clone()))
+            })
+            .collect();
+        drop(plugins);
+
+        sort_by_dependencies(plugin_ids, &dependencies)
+    }
+
+    /// 按依赖顺序启动插件，被依赖的插件就绪后才启动依赖它的插件
+    ///
+    /// 依赖启动失败、未在超时内就绪或存在循环依赖的插件不会被启动。
+    pub async fn start_plugins_in_order(&self, plugin_ids: Vec<String>) {
+        let (ordered, cyclic) = self.resolve_start_order(&plugin_ids).await;
+        let mut failed: HashSet<String> = HashSet::new();
+
+        for plugin_id in cyclic {
+            log_error!(
+                "Plugin {} has circular dependencies, not starting",
+                plugin_id
+            );
+            if let Some(plugin) = self.plugins.read().await.get(&plugin_id).cloned() {
+                push_system_output(
+                    &plugin,
+                    &self.output_sender,
+                    "[错误] 插件存在循环依赖，未启动".to_string(),
+                )
+                .await;
+            }
+            failed.insert(plugin_id);
+        }
+
+        let mut depended_on: HashSet<String> = HashSet::new();
+        for plugin_id in &ordered {
+            if let Some(plugin) = self.plugins.read().await.get(plugin_id) {
+                depended_on.extend(plugin.manifest.depends_on.iter().cloned());
+            }
+        }
+
+        for plugin_id in ordered {
+            let plugin = self.plugins.read().await.get(&plugin_id).cloned();
+            let name = plugin
+                .as_ref()
+                .map(|p| p.manifest.name.clone())
+                .unwrap_or_else(|| plugin_id.clone());
+
+            if let Some(plugin) = &plugin {
+                if let Some(dependency) = plugin
+                    .manifest
+                    .depends_on
+                    .iter()
+                    .find(|dependency| failed.contains(*dependency))
+                {
+                    log_error!(
+                        "Skipping plugin {}({}): dependency {} failed to start",
+                        name,
+                        plugin_id,
+                        dependency
+                    );
+                    push_system_output(
+                        plugin,
+                        &self.output_sender,
+                        format!("[错误] 依赖插件 {} 启动失败，未启动", dependency),
+                    )
+                    .await;
+                    failed.insert(plugin_id);
+                    continue;
+                }
+            }
+
+            log_info!("Auto-starting plugin: {}({})", name, plugin_id);
+            if let Err(e) = self.start_plugin(&plugin_id).await {
+                if e == "Plugin not found" && self.purge_enabled_plugin_if_absent(&plugin_id).await
+                {
+                    failed.insert(plugin_id);
+                    continue;
+                }
+                log_error!("Failed to auto-start plugin {}({}): {}", name, plugin_id, e);
+                failed.insert(plugin_id);
+                continue;
+            }
+
+            // 只有被其他插件依赖时才需要等待就绪
+            let Some(plugin) = plugin else {
+                continue;
+            };
+            if !depended_on.contains(&plugin_id) {
+                continue;
+            }
+            if let Err(e) = self.wait_plugin_ready(&plugin).await {
+                log_error!("Plugin {}({}) is not ready: {}", name, plugin_id, e);
+                push_system_output(
+                    &plugin,
+                    &self.output_sender,
+                    format!("[错误] 插件未能就绪: {}", e),
+                )
+                .await;
+                failed.insert(plugin_id);
+            }
+        }
+    }
+}
+
+/// 按依赖关系排序，没有依赖关系的插件保持原有顺序
+///
+/// 只考虑 `plugin_ids` 之间的依赖，返回排好序的插件以及因循环依赖无法排序的插件。
+fn sort_by_dependencies(
+    plugin_ids: &[String],
+    dependencies: &HashMap<String, Vec<String>>,
+) -> (Vec<String>, Vec<String>) {
+    let requested: HashSet<&str> = plugin_ids.iter().map(String::as_str).collect();
+
+    let mut pending: Vec<String> = Vec::new();
+    for plugin_id in plugin_ids {
+        if !pending.contains(plugin_id) {
+            pending.push(plugin_id.clone());
+        }
+    }
+
+    let mut ordered: Vec<String> = Vec::new();
+    while let Some(index) = pending.iter().position(|plugin_id| {
+        dependencies.get(plugin_id).is_none_or(|depends_on| {
+            depends_on.iter().all(|dependency| {
+                !requested.contains(dependency.as_str()) || ordered.contains(dependency)
+            })
+        })
+    }) {
+        ordered.push(pending.remove(index));
+    }
+
+    (ordered, pending)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    fn graph(edges: &[(&str, &[&str])]) -> HashMap<String, Vec<String>> {
+        edges
+            .iter()
+            .map(|(id, depends_on)| (id.to_string(), ids(depends_on)))
+            .collect()
+    }
+
+    #[test]
+    fn keeps_order_without_dependencies() {
+        let (ordered, cyclic) = sort_by_dependencies(&ids(&["c", "a", "b"]), &HashMap::new());
+        assert_eq!(ordered, ids(&["c", "a", "b"]));
+        assert!(cyclic.is_empty());
+    }
+
+    #[test]
+    fn starts_dependencies_first() {
+        let dependencies = graph(&[("app", &["db", "cache"]), ("cache", &["db"])]);
+        let (ordered, cyclic) = sort_by_dependencies(&ids(&["app", "cache", "db"]), &dependencies);
+        assert_eq!(ordered, ids(&["db", "cache", "app"]));
+        assert!(cyclic.is_empty());
+    }
+
+    #[test]
+    fn ignores_dependencies_outside_the_batch() {
+        let dependencies = graph(&[("app", &["missing"])]);
+        let (ordered, cyclic) = sort_by_dependencies(&ids(&["app"]), &dependencies);
+        assert_eq!(ordered, ids(&["app"]));
+        assert!(cyclic.is_empty());
+    }
+
+    #[test]
+    fn reports_cycles_and_their_dependents() {
+        let dependencies = graph(&[("a", &["b"]), ("b", &["a"]), ("c", &["a"]), ("d", &[])]);
+        let (ordered, cyclic) = sort_by_dependencies(&ids(&["a", "b", "c", "d"]), &dependencies);
+        assert_eq!(ordered, ids(&["d"]));
+        assert_eq!(cyclic, ids(&["a", "b", "c"]));
+    }
+
+    #[test]
+    fn drops_duplicate_ids() {
+        let dependencies = graph(&[("b", &["a"])]);
+        let (ordered, _) = sort_by_dependencies(&ids(&["b", "a", "b"]), &dependencies);
+        assert_eq!(ordered, ids(&["a", "b"]));
+    }
+}
@@ -1,8 +1,19 @@
 mod config;
+mod dependencies;
+mod permissions;
+mod process;
+mod restart;
 mod runtime;
 mod storage;
 
-use crate::plus::plugin::{Plugin, PluginStatus};
+pub use permissions::is_read_only_api;
+
+use process::{PlatformProcess, ProcessBackend};
+
+use crate::plus::ansi::{AnsiSpan, AnsiStyle};
+use crate::plus::output_line::PluginOutputLine;
+use crate::plus::output_log::PluginOutputLog;
+use crate::plus::plugin::{Plugin, PluginStatus, RestartPolicy};
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -11,11 +22,20 @@ use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicU16, Ordering};
 use std::sync::Arc;
 use tokio::net::TcpStream;
-use tokio::sync::{broadcast, Mutex, Notify, RwLock};
+use tokio::sync::{broadcast, mpsc, Mutex, Notify, RwLock};
 
 #[derive(Serialize, Deserialize, Default)]
 pub struct PluginConfig {
     pub enabled_plugins: Vec<String>,
+    /// 按插件 ID 覆盖 `app.json` 中声明的重启策略
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub restart_policies: HashMap<String, RestartPolicy>,
+    /// 运维人员已批准的插件 API 权限，按插件 ID 存储
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub approved_permissions: HashMap<String, Vec<String>>,
+    /// 插件绑定的 Bot 账号 ID，按插件 ID 存储，未设置时可以使用全部账号
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub bot_bindings: HashMap<String, Vec<String>>,
 }
 
 pub struct PluginManager {
@@ -26,7 +46,10 @@ pub struct PluginManager {
     pub(super) milky_proxy_api_port: AtomicU16,
     pub(super) milky_proxy_event_port: AtomicU16,
     pub(super) output_sender: broadcast::Sender<PluginOutputEvent>,
+    pub(super) output_log: Arc<PluginOutputLog>,
     pub(super) status_sender: broadcast::Sender<PluginStatusEvent>,
+    pub(super) exit_sender: mpsc::UnboundedSender<PluginExit>,
+    pub(super) exit_receiver: Mutex<Option<mpsc::UnboundedReceiver<PluginExit>>>,
     pub(super) port_ready: Notify,
     pub(super) milky_ready: Notify,
     pub(super) config_lock: Mutex<()>,
@@ -35,7 +58,23 @@ pub struct PluginManager {
 #[derive(Clone, Debug, serde::Serialize)]
 pub struct PluginOutputEvent {
     pub plugin_id: String,
-    pub line: String,
+    #[serde(flatten)]
+    pub output: PluginOutputLine,
+}
+
+impl PluginOutputEvent {
+    /// 主程序自己生成的消息，不受插件输出中未结束的颜色影响
+    pub fn new(plugin_id: impl Into<String>, line: String) -> Self {
+        Self::with_style(plugin_id, &line, &mut AnsiStyle::default())
+    }
+
+    /// 插件输出流中的一行，`style` 在同一次运行的各行之间延续
+    pub fn with_style(plugin_id: impl Into<String>, line: &str, style: &mut AnsiStyle) -> Self {
+        Self {
+            plugin_id: plugin_id.into(),
+            output: PluginOutputLine::new(line, style),
+        }
+    }
 }
 
 #[derive(Clone, Debug, serde::Serialize)]
@@ -44,6 +83,17 @@ pub struct PluginStatusEvent {
     pub status: PluginStatus,
     pub enabled: bool,
     pub webui_url: Option<String>,
+    pub crash_count: u32,
+}
+
+/// 插件进程非用户主动停止而退出时，由读取线程发给重启监督任务
+#[derive(Debug)]
+pub(super) struct PluginExit {
+    pub plugin_id: String,
+    pub run_id: u64,
+    /// 退出码非 0 或无法获取
+    pub failed: bool,
+    pub uptime: std::time::Duration,
 }
 
 #[derive(serde::Serialize)]
@@ -56,7 +106,21 @@ pub struct PluginInfo {
     pub status: PluginStatus,
     pub enabled: bool,
     pub output: Vec<String>,
+    /// 与 `output` 逐行对应的颜色文本段，没有颜色的行为空
+    pub output_spans: Vec<Vec<AnsiSpan>>,
     pub webui_url: Option<String>,
+    pub crash_count: u32,
+    pub permissions: Vec<PluginPermissionInfo>,
+    /// 绑定的 Bot 账号 ID，为空表示全部账号
+    pub bots: Vec<String>,
+    pub depends_on: Vec<String>,
+    pub ready: bool,
+}
+
+#[derive(serde::Serialize)]
+pub struct PluginPermissionInfo {
+    pub name: String,
+    pub approved: bool,
 }
 
 impl PluginManager {
@@ -68,7 +132,12 @@ impl PluginManager {
         milky_proxy_event_port: u16,
     ) -> Self {
         let (output_sender, _) = broadcast::channel(1000);
+        let output_log = PluginOutputLog::new(
+            exe_dir.join("logs").join("plugins"),
+            output_sender.subscribe(),
+        );
         let (status_sender, _) = broadcast::channel(100);
+        let (exit_sender, exit_receiver) = mpsc::unbounded_channel();
         Self {
             plugins: Arc::new(RwLock::new(HashMap::new())),
             exe_dir,
@@ -77,7 +146,10 @@ impl PluginManager {
             milky_proxy_api_port: AtomicU16::new(milky_proxy_api_port),
             milky_proxy_event_port: AtomicU16::new(milky_proxy_event_port),
             output_sender,
+            output_log,
             status_sender,
+            exit_sender,
+            exit_receiver: Mutex::new(Some(exit_receiver)),
             port_ready: Notify::new(),
             milky_ready: Notify::new(),
             config_lock: Mutex::new(()),
@@ -197,14 +269,7 @@ impl PluginManager {
                         let pid = plugin.get_pid().await;
                         if pid > 0 {
                             log_warn!("Force killing plugin process: {}", pid);
-                            use std::os::windows::process::CommandExt;
-                            let _ = std::process::Command::new("taskkill")
-                                .args(["/PID", &pid.to_string(), "/F", "/T"])
-                                .creation_flags(0x08000000)
-                                .stdin(std::process::Stdio::null())
-                                .stdout(std::process::Stdio::null())
-                                .stderr(std::process::Stdio::null())
-                                .output();
+                            PlatformProcess::force_kill(pid);
                         }
                     }
                 }
@@ -223,25 +288,34 @@ impl PluginManager {
     }
 }
 
+/// 向插件输出追加一行系统消息并推送给订阅者
+async fn push_system_output(
+    plugin: &Plugin,
+    sender: &broadcast::Sender<PluginOutputEvent>,
+    line: String,
+) {
+    let event = PluginOutputEvent::new(plugin.id.clone(), line);
+    plugin.add_output(event.output.clone()).await;
+    let _ = sender.send(event);
+}
+
 fn process_output(
     rt: &tokio::runtime::Handle,
     plugin: &Arc<Plugin>,
     sender: &broadcast::Sender<PluginOutputEvent>,
     plugin_id: &str,
     text: &str,
+    style: &mut AnsiStyle,
 ) {
     for line in text.lines() {
         if !line.is_empty() {
-            let line_clone = line.to_string();
+            let event = PluginOutputEvent::with_style(plugin_id, line, style);
             let plugin_clone = plugin.clone();
-            let line_for_async = line_clone.clone();
+            let line_for_async = event.output.clone();
             let _handle = rt.spawn(async move {
                 plugin_clone.add_output(line_for_async).await;
             });
-            let _ = sender.send(PluginOutputEvent {
-                plugin_id: plugin_id.to_string(),
-                line: line_clone,
-            });
+            let _ = sender.send(event);
         }
     }
 }
@@ -297,34 +371,3 @@ async fn wait_tcp_ready(host: &str, port: u16, timeout: std::time::Duration) ->
         tokio::time::sleep(std::time::Duration::from_millis(100)).await;
     }
 }
-
-fn try_send_ctrl_c(pid: u32) -> bool {
-    use windows_sys::Win32::System::Console::{
-        AttachConsole, FreeConsole, GenerateConsoleCtrlEvent, SetConsoleCtrlHandler, SetStdHandle,
-        CTRL_C_EVENT, STD_ERROR_HANDLE, STD_INPUT_HANDLE, STD_OUTPUT_HANDLE,
-    };
-
-    unsafe {
-        let _ = FreeConsole();
-        if AttachConsole(pid) == 0 {
-            return false;
-        }
-
-        if SetConsoleCtrlHandler(None, 1) == 0 {
-            let _ = FreeConsole();
-            return false;
-        }
-
-        let ok = GenerateConsoleCtrlEvent(CTRL_C_EVENT, pid) != 0;
-
-        std::thread::sleep(std::time::Duration::from_millis(50));
-        let _ = FreeConsole();
-        let _ = SetConsoleCtrlHandler(None, 0);
-
-        SetStdHandle(STD_INPUT_HANDLE, std::ptr::null_mut());
-        SetStdHandle(STD_OUTPUT_HANDLE, std::ptr::null_mut());
-        SetStdHandle(STD_ERROR_HANDLE, std::ptr::null_mut());
-
-        ok
-    }
-}
@@ -0,0 +1,108 @@
+use super::PluginManager;
+use std::collections::HashSet;
+
+/// 只读接口（`get_*`）无需在 `app.json` 中声明权限
+pub fn is_read_only_api(api: &str) -> bool {
+    api.starts_with("get_")
+}
+
+impl PluginManager {
+    /// 检查插件是否被允许调用指定的 Milky API
+    pub async fn check_api_permission(&self, plugin_id: &str, api: &str) -> Result<(), String> {
+        if is_read_only_api(api) {
+            return Ok(());
+        }
+
+        let plugins = self.plugins.read().await;
+        let plugin = plugins
+            .get(plugin_id)
+            .ok_or("Plugin not found".to_string())?
+            .clone();
+        drop(plugins);
+
+        if !plugin.manifest.permissions.iter().any(|p| p == api) {
+            return Err(format!(
+                "Plugin did not request permission for API '{}' in app.json",
+                api
+            ));
+        }
+
+        if !plugin.is_permission_granted(api).await {
+            return Err(format!(
+                "Permission for API '{}' has not been approved yet",
+                api
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// 批准插件在 `app.json` 中申请的全部权限
+    pub async fn approve_plugin_permissions(&self, plugin_id: &str) -> Result<(), String> {
+        let plugins = self.plugins.read().await;
+        let plugin = plugins
+            .get(plugin_id)
+            .ok_or("Plugin not found".to_string())?
+            .clone();
+        drop(plugins);
+
+        let permissions = plugin.manifest.permissions.clone();
+        plugin.set_approved_permissions(permissions.clone()).await;
+        self.set_approved_permissions_config(plugin_id, permissions)
+            .await;
+        Ok(())
+    }
+
+    /// 撤销插件已获批准的全部权限
+    pub async fn revoke_plugin_permissions(&self, plugin_id: &str) -> Result<(), String> {
+        let plugins = self.plugins.read().await;
+        let plugin = plugins
+            .get(plugin_id)
+            .ok_or("Plugin not found".to_string())?
+            .clone();
+        drop(plugins);
+
+        plugin.set_approved_permissions(Vec::new()).await;
+        self.set_approved_permissions_config(plugin_id, Vec::new())
+            .await;
+        Ok(())
+    }
+
+    /// 插件绑定的 Bot 账号，插件不存在时返回 `None`，空列表表示可以使用全部账号
+    pub async fn get_plugin_bot_bindings(&self, plugin_id: &str) -> Option<Vec<String>> {
+        let plugin = self.plugins.read().await.get(plugin_id)?.clone();
+        Some(plugin.get_bot_bindings().await)
+    }
+
+    /// 插件是否可以使用指定的 Bot 账号
+    pub async fn is_bot_allowed(&self, plugin_id: &str, bot_id: &str) -> bool {
+        self.get_plugin_bot_bindings(plugin_id)
+            .await
+            .is_some_and(|bots| bots.is_empty() || bots.iter().any(|id| id == bot_id))
+    }
+
+    /// 设置插件可以使用的 Bot 账号，传入空列表表示不限制
+    pub async fn set_plugin_bot_bindings(
+        &self,
+        plugin_id: &str,
+        bots: Vec<String>,
+    ) -> Result<(), String> {
+        let plugins = self.plugins.read().await;
+        let plugin = plugins
+            .get(plugin_id)
+            .ok_or("Plugin not found".to_string())?
+            .clone();
+        drop(plugins);
+
+        let mut seen = HashSet::new();
+        let bots: Vec<String> = bots
+            .into_iter()
+            .map(|id| id.trim().to_string())
+            .filter(|id| !id.is_empty() && seen.insert(id.clone()))
+            .collect();
+
+        plugin.set_bot_bindings(bots.clone()).await;
+        self.set_bot_bindings_config(plugin_id, bots).await;
+        Ok(())
+    }
+}
@@ -0,0 +1,230 @@
+use std::process::Command;
+
+/// 插件进程的平台相关控制
+///
+/// 负责启动前的命令配置、优雅停止信号以及强制结束，
+/// 让 `start_plugin` / `stop_all_plugins_and_wait` 不直接依赖具体平台 API。
+pub(super) trait ProcessBackend {
+    /// 在交给 expectrl 启动之前配置命令
+    fn configure_command(cmd: &mut Command);
+
+    /// 发送中断信号（Windows 上为 `Ctrl+C`，Unix 上为 `SIGINT`），返回是否发送成功
+    fn interrupt(pid: u32) -> bool;
+
+    /// 发送终止信号（Unix 上为 `SIGTERM`），不支持的平台返回 `false`
+    fn terminate(pid: u32) -> bool;
+
+    /// 强制结束进程及其子进程
+    fn force_kill(pid: u32);
+}
+
+#[cfg(windows)]
+pub(super) type PlatformProcess = WindowsProcess;
+
+#[cfg(unix)]
+pub(super) type PlatformProcess = UnixProcess;
+
+/// 获取 expectrl 会话对应的子进程 PID
+#[cfg(windows)]
+pub(super) fn session_pid(session: &expectrl::Session) -> u32 {
+    session.get_process().pid()
+}
+
+/// 获取 expectrl 会话对应的子进程 PID
+#[cfg(unix)]
+pub(super) fn session_pid(session: &expectrl::Session) -> u32 {
+    session.get_process().pid().as_raw() as u32
+}
+
+/// 跟踪插件进程是否存活，并在进程退出时记录退出码
+///
+/// Unix 下由这里负责回收子进程（`waitpid`），否则退出码会在 expectrl 的存活检查中丢失。
+pub(super) struct ExitTracker {
+    pid: u32,
+    exited: bool,
+    exit_code: Option<i32>,
+}
+
+impl ExitTracker {
+    pub(super) fn new(pid: u32) -> Self {
+        Self {
+            pid,
+            exited: false,
+            exit_code: None,
+        }
+    }
+
+    #[cfg(windows)]
+    pub(super) fn is_alive(&mut self, session: &mut expectrl::Session) -> bool {
+        use expectrl::process::Healthcheck;
+
+        if self.exited {
+            return false;
+        }
+        if session.is_alive().unwrap_or(false) {
+            return true;
+        }
+
+        self.exited = true;
+        self.exit_code = query_exit_code(self.pid);
+        false
+    }
+
+    #[cfg(unix)]
+    pub(super) fn is_alive(&mut self, _session: &mut expectrl::Session) -> bool {
+        if self.exited {
+            return false;
+        }
+
+        let Ok(pid) = libc::pid_t::try_from(self.pid) else {
+            self.exited = true;
+            return false;
+        };
+
+        let mut status: libc::c_int = 0;
+        let ret = unsafe { libc::waitpid(pid, &mut status, libc::WNOHANG) };
+        if ret == 0 {
+            return true;
+        }
+
+        self.exited = true;
+        if ret > 0 {
+            self.exit_code = if libc::WIFEXITED(status) {
+                Some(libc::WEXITSTATUS(status))
+            } else if libc::WIFSIGNALED(status) {
+                Some(128 + libc::WTERMSIG(status))
+            } else {
+                None
+            };
+        }
+        false
+    }
+
+    /// 进程退出码；进程仍在运行或无法获取时为 `None`
+    pub(super) fn exit_code(&self) -> Option<i32> {
+        self.exit_code
+    }
+}
+
+#[cfg(windows)]
+fn query_exit_code(pid: u32) -> Option<i32> {
+    use windows_sys::Win32::Foundation::{CloseHandle, STILL_ACTIVE};
+    use windows_sys::Win32::System::Threading::{
+        GetExitCodeProcess, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if handle.is_null() {
+            return None;
+        }
+
+        let mut code: u32 = 0;
+        let ok = GetExitCodeProcess(handle, &mut code) != 0;
+        CloseHandle(handle);
+
+        if !ok || code == STILL_ACTIVE as u32 {
+            None
+        } else {
+            Some(code as i32)
+        }
+    }
+}
+
+#[cfg(windows)]
+pub(super) struct WindowsProcess;
+
+#[cfg(windows)]
+impl ProcessBackend for WindowsProcess {
+    fn configure_command(cmd: &mut Command) {
+        use std::os::windows::process::CommandExt;
+        cmd.creation_flags(0x00000200); // CREATE_NEW_PROCESS_GROUP
+    }
+
+    fn interrupt(pid: u32) -> bool {
+        try_send_ctrl_c(pid)
+    }
+
+    fn terminate(_pid: u32) -> bool {
+        false
+    }
+
+    fn force_kill(pid: u32) {
+        use std::os::windows::process::CommandExt;
+        let _ = Command::new("taskkill")
+            .args(["/PID", &pid.to_string(), "/F", "/T"])
+            .creation_flags(0x08000000) // CREATE_NO_WINDOW
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .output();
+    }
+}
+
+#[cfg(windows)]
+fn try_send_ctrl_c(pid: u32) -> bool {
+    use windows_sys::Win32::System::Console::{
+        AttachConsole, FreeConsole, GenerateConsoleCtrlEvent, SetConsoleCtrlHandler, SetStdHandle,
+        CTRL_C_EVENT, STD_ERROR_HANDLE, STD_INPUT_HANDLE, STD_OUTPUT_HANDLE,
+    };
+
+    unsafe {
+        let _ = FreeConsole();
+        if AttachConsole(pid) == 0 {
+            return false;
+        }
+
+        if SetConsoleCtrlHandler(None, 1) == 0 {
+            let _ = FreeConsole();
+            return false;
+        }
+
+        let ok = GenerateConsoleCtrlEvent(CTRL_C_EVENT, pid) != 0;
+
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        let _ = FreeConsole();
+        let _ = SetConsoleCtrlHandler(None, 0);
+
+        SetStdHandle(STD_INPUT_HANDLE, std::ptr::null_mut());
+        SetStdHandle(STD_OUTPUT_HANDLE, std::ptr::null_mut());
+        SetStdHandle(STD_ERROR_HANDLE, std::ptr::null_mut());
+
+        ok
+    }
+}
+
+#[cfg(unix)]
+pub(super) struct UnixProcess;
+
+#[cfg(unix)]
+impl ProcessBackend for UnixProcess {
+    fn configure_command(_cmd: &mut Command) {
+        // expectrl 在子进程中调用 setsid 绑定 PTY，子进程天然成为新进程组的组长。
+        // 这里不能再设置 process_group，否则 setsid 会因进程已是组长而失败。
+    }
+
+    fn interrupt(pid: u32) -> bool {
+        signal_process_group(pid, libc::SIGINT)
+    }
+
+    fn terminate(pid: u32) -> bool {
+        signal_process_group(pid, libc::SIGTERM)
+    }
+
+    fn force_kill(pid: u32) {
+        let _ = signal_process_group(pid, libc::SIGKILL);
+    }
+}
+
+/// 向以 `pid` 为组长的进程组发送信号，进程组不存在时退回到只发给该进程
+#[cfg(unix)]
+fn signal_process_group(pid: u32, signal: libc::c_int) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    if pid <= 0 {
+        return false;
+    }
+
+    unsafe { libc::kill(-pid, signal) == 0 || libc::kill(pid, signal) == 0 }
+}
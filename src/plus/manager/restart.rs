@@ -0,0 +1,113 @@
+use super::{push_system_output, PluginExit, PluginManager, PluginStatusEvent};
+use crate::plus::plugin::{PluginStatus, RestartMode};
+use std::sync::Arc;
+
+impl PluginManager {
+    /// 重启监督任务：接收插件进程的意外退出通知，并按重启策略自动拉起插件
+    ///
+    /// 应在插件管理器创建后 spawn 一次，重复调用会直接返回。
+    pub async fn run_restart_supervisor(self: Arc<Self>) {
+        let Some(mut exit_receiver) = self.exit_receiver.lock().await.take() else {
+            return;
+        };
+
+        while let Some(exit) = exit_receiver.recv().await {
+            let manager = self.clone();
+            tokio::spawn(async move {
+                manager.handle_plugin_exit(exit).await;
+            });
+        }
+    }
+
+    async fn handle_plugin_exit(&self, exit: PluginExit) {
+        let plugin = {
+            let plugins = self.plugins.read().await;
+            match plugins.get(&exit.plugin_id) {
+                Some(p) => p.clone(),
+                None => return,
+            }
+        };
+
+        let policy = self.get_restart_policy(&plugin).await;
+        let should_restart = match policy.mode {
+            RestartMode::Never => false,
+            RestartMode::OnFailure => exit.failed,
+            RestartMode::Always => true,
+        };
+        if !should_restart {
+            return;
+        }
+
+        if exit.uptime >= policy.reset_window() {
+            plugin.reset_restart_attempts().await;
+        }
+
+        let attempt = plugin.next_restart_attempt().await;
+        if policy.max_retries > 0 && attempt > policy.max_retries {
+            log_warn!(
+                "Plugin {} reached max restart retries ({}), giving up",
+                exit.plugin_id,
+                policy.max_retries
+            );
+            push_system_output(
+                &plugin,
+                &self.output_sender,
+                format!(
+                    "[系统] 插件已连续重启 {} 次，不再自动重启",
+                    policy.max_retries
+                ),
+            )
+            .await;
+
+            if plugin.is_current_run(exit.run_id) {
+                plugin.set_status(PluginStatus::Error).await;
+                let _ = self.status_sender.send(PluginStatusEvent {
+                    plugin_id: exit.plugin_id.clone(),
+                    status: PluginStatus::Error,
+                    enabled: plugin.is_enabled().await,
+                    webui_url: None,
+                    crash_count: plugin.get_crash_count().await,
+                });
+            }
+            return;
+        }
+
+        let delay = policy.backoff_delay(attempt);
+        push_system_output(
+            &plugin,
+            &self.output_sender,
+            format!(
+                "[系统] 将在 {:.1} 秒后自动重启插件（第 {} 次）",
+                delay.as_secs_f64(),
+                attempt
+            ),
+        )
+        .await;
+
+        tokio::time::sleep(delay).await;
+
+        // 等待期间用户可能已手动启动、停止或卸载插件
+        if !plugin.is_current_run(exit.run_id)
+            || !plugin.is_enabled().await
+            || plugin.get_status().await == PluginStatus::Running
+        {
+            return;
+        }
+
+        log_info!(
+            "Auto-restarting plugin {}({}), attempt {}",
+            plugin.manifest.name,
+            exit.plugin_id,
+            attempt
+        );
+        if let Err(e) = self.start_plugin(&exit.plugin_id).await {
+            log_error!("Failed to auto-restart plugin {}: {}", exit.plugin_id, e);
+            push_system_output(
+                &plugin,
+                &self.output_sender,
+                format!("[错误] 自动重启插件失败: {}", e),
+            )
+            .await;
+        }
+    }
+}
@@ -1,29 +1,35 @@
+use super::process::{session_pid, ExitTracker, PlatformProcess, ProcessBackend};
 use super::{
-    generate_plugin_api_token, generate_tmp_run_suffix, process_output, try_send_ctrl_c,
-    wait_tcp_ready, PluginManager, PluginOutputEvent, PluginStatusEvent,
+    generate_plugin_api_token, generate_tmp_run_suffix, process_output, wait_tcp_ready, PluginExit,
+    PluginManager, PluginOutputEvent, PluginStatusEvent,
 };
+use crate::plus::ansi::AnsiStyle;
 use crate::plus::plugin::PluginStatus;
 use crate::runtime;
-use expectrl::{process::Healthcheck, Session};
+use expectrl::Session;
 use std::path::PathBuf;
 use std::process::Command;
 use std::sync::atomic::Ordering;
 use std::thread;
 
+/// 停止插件时依次发送的信号，以及发送后等待退出的秒数
+type GracefulSignal = (fn(u32) -> bool, u64);
+
 impl PluginManager {
     pub async fn start_plugin(&self, plugin_id: &str) -> Result<(), String> {
         self.wait_for_port().await;
         self.wait_for_milky().await;
 
-        let (plugin, run_id) = {
-            let plugins = self.plugins.read().await;
-            let plugin = plugins
-                .get(plugin_id)
-                .ok_or("Plugin not found".to_string())?
-                .clone();
-            let run_id = plugin.begin_run();
-            (plugin, run_id)
-        };
+        let plugin = self
+            .plugins
+            .read()
+            .await
+            .get(plugin_id)
+            .cloned()
+            .ok_or("Plugin not found".to_string())?;
+        self.check_dependencies(&plugin).await?;
+        let run_id = plugin.begin_run();
+
         let run_tmp_dir =
             plugin
                 .tmp_dir
@@ -68,6 +74,7 @@ impl PluginManager {
         let rt_handle = runtime::get_handle();
         let output_sender = self.output_sender.clone();
         let status_sender = self.status_sender.clone();
+        let exit_sender = self.exit_sender.clone();
 
         let plugin_clone = plugin.clone();
         let program_path_clone = program_path.clone();
@@ -124,52 +131,63 @@ impl PluginManager {
             cmd.env("YUYU_PORT", server_port.to_string());
             cmd.env("YUYU_TOKEN", &plugin_api_token_for_env);
 
-            use std::os::windows::process::CommandExt;
-            cmd.creation_flags(0x00000200);
+            PlatformProcess::configure_command(&mut cmd);
 
             match Session::spawn(cmd) {
                 Ok(mut session) => {
+                    let pid = session_pid(&session);
+                    let mut exit_tracker = ExitTracker::new(pid);
+                    if plugin_clone.is_current_run(run_id) {
+                        rt_handle.block_on(plugin_clone.set_pid(pid));
+                    }
+
                     if plugin_clone.is_current_run(run_id) {
                         let msg = format!("[系统] 插件已启动: {}", display_cmd);
                         let plugin_inner = plugin_clone.clone();
                         let sender = output_sender.clone();
                         let id = plugin_id_clone.clone();
-                        let msg_for_async = msg.clone();
+                        let event = PluginOutputEvent::new(id, msg);
+                        let msg_for_async = event.output.clone();
                         let _handle = rt_handle.spawn(async move {
                             plugin_inner.add_output(msg_for_async).await;
                         });
-                        let _ = sender.send(PluginOutputEvent {
-                            plugin_id: id,
-                            line: msg,
-                        });
+                        let _ = sender.send(event);
                     }
 
                     let mut buf = [0u8; 4096];
+                    // 插件设置的颜色可能跨越多行，直到遇到重置序列
+                    let mut output_style = AnsiStyle::default();
                     session.set_expect_timeout(Some(std::time::Duration::from_millis(500)));
 
                     loop {
                         if plugin_clone.should_stop_run(run_id) {
-                            let pid = session.get_process().pid();
-                            let sent = try_send_ctrl_c(pid);
-                            if sent {
-                                let deadline =
-                                    std::time::Instant::now() + std::time::Duration::from_secs(3);
-                                while session.is_alive().unwrap_or(false)
+                            // 先中断（Ctrl+C / SIGINT），仍未退出再尝试 SIGTERM，最后强制结束
+                            let graceful_signals: [GracefulSignal; 2] = [
+                                (PlatformProcess::interrupt, 3),
+                                (PlatformProcess::terminate, 2),
+                            ];
+                            for (send_signal, wait_secs) in graceful_signals {
+                                if !exit_tracker.is_alive(&mut session) || !send_signal(pid) {
+                                    continue;
+                                }
+
+                                let deadline = std::time::Instant::now()
+                                    + std::time::Duration::from_secs(wait_secs);
+                                while exit_tracker.is_alive(&mut session)
                                     && std::time::Instant::now() < deadline
                                 {
                                     match session.try_read(&mut buf) {
                                         Ok(0) => break,
                                         Ok(n) => {
                                             let bytes = &buf[..n];
-                                            let stripped = strip_ansi_escapes::strip(bytes);
-                                            let text =
-                                                String::from_utf8_lossy(&stripped).to_string();
+                                            let text = String::from_utf8_lossy(bytes).to_string();
                                             process_output(
                                                 &rt_handle,
                                                 &plugin_clone,
                                                 &output_sender,
                                                 &plugin_id_clone,
                                                 &text,
+                                                &mut output_style,
                                             );
                                         }
                                         Err(ref e)
@@ -180,20 +198,12 @@ impl PluginManager {
                                 }
                             }
 
-                            if session.is_alive().unwrap_or(false) {
-                                let pid_str = pid.to_string();
-                                use std::os::windows::process::CommandExt;
-                                let _ = std::process::Command::new("taskkill")
-                                    .args(["/PID", pid_str.as_str(), "/F", "/T"])
-                                    .creation_flags(0x08000000)
-                                    .stdin(std::process::Stdio::null())
-                                    .stdout(std::process::Stdio::null())
-                                    .stderr(std::process::Stdio::null())
-                                    .output();
+                            if exit_tracker.is_alive(&mut session) {
+                                PlatformProcess::force_kill(pid);
                             }
                         }
 
-                        if !session.is_alive().unwrap_or(false) {
+                        if !exit_tracker.is_alive(&mut session) {
                             if plugin_clone.is_current_run(run_id) {
                                 rt_handle.block_on(plugin_clone.set_process_alive(false));
                             }
@@ -202,14 +212,14 @@ impl PluginManager {
                                     Ok(0) => break,
                                     Ok(n) => {
                                         let bytes = &buf[..n];
-                                        let stripped = strip_ansi_escapes::strip(bytes);
-                                        let text = String::from_utf8_lossy(&stripped).to_string();
+                                        let text = String::from_utf8_lossy(bytes).to_string();
                                         process_output(
                                             &rt_handle,
                                             &plugin_clone,
                                             &output_sender,
                                             &plugin_id_clone,
                                             &text,
+                                            &mut output_style,
                                         );
                                     }
                                     Err(_) => break,
@@ -227,14 +237,14 @@ impl PluginManager {
                             }
                             Ok(n) => {
                                 let bytes = &buf[..n];
-                                let stripped = strip_ansi_escapes::strip(bytes);
-                                let text = String::from_utf8_lossy(&stripped).to_string();
+                                let text = String::from_utf8_lossy(bytes).to_string();
                                 process_output(
                                     &rt_handle,
                                     &plugin_clone,
                                     &output_sender,
                                     &plugin_id_clone,
                                     &text,
+                                    &mut output_style,
                                 );
                             }
                             Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
@@ -245,7 +255,7 @@ impl PluginManager {
                                 if plugin_clone.should_stop_run(run_id) {
                                     break;
                                 }
-                                if !session.is_alive().unwrap_or(false) {
+                                if !exit_tracker.is_alive(&mut session) {
                                     if plugin_clone.is_current_run(run_id) {
                                         rt_handle.block_on(plugin_clone.set_process_alive(false));
                                     }
@@ -255,14 +265,12 @@ impl PluginManager {
                                 let plugin_inner = plugin_clone.clone();
                                 let sender = output_sender.clone();
                                 let id = plugin_id_clone.clone();
-                                let err_msg_for_async = err_msg.clone();
+                                let event = PluginOutputEvent::new(id, err_msg);
+                                let err_msg_for_async = event.output.clone();
                                 let _handle = rt_handle.spawn(async move {
                                     plugin_inner.add_output(err_msg_for_async).await;
                                 });
-                                let _ = sender.send(PluginOutputEvent {
-                                    plugin_id: id,
-                                    line: err_msg,
-                                });
+                                let _ = sender.send(event);
                                 if plugin_clone.is_current_run(run_id) {
                                     rt_handle.block_on(plugin_clone.set_process_alive(false));
                                 }
@@ -271,6 +279,16 @@ impl PluginManager {
                         }
                     }
 
+                    // 输出结束时进程可能尚未被回收，稍等片刻以便拿到退出码
+                    let exit_deadline =
+                        std::time::Instant::now() + std::time::Duration::from_secs(1);
+                    while exit_tracker.is_alive(&mut session)
+                        && std::time::Instant::now() < exit_deadline
+                    {
+                        std::thread::sleep(std::time::Duration::from_millis(50));
+                    }
+                    let exit_code = exit_tracker.exit_code();
+
                     if run_tmp_dir.exists() {
                         std::thread::sleep(std::time::Duration::from_millis(200));
                         let _ = std::fs::remove_dir_all(&run_tmp_dir);
@@ -283,14 +301,23 @@ impl PluginManager {
                     if plugin_clone.is_current_run(run_id) {
                         let plugin_inner = plugin_clone.clone();
                         let was_stopped = plugin_clone.should_stop_run(run_id);
+                        let failed = exit_code != Some(0);
+                        let crash_count = if !was_stopped && failed {
+                            rt_handle.block_on(plugin_clone.record_crash())
+                        } else {
+                            rt_handle.block_on(plugin_clone.get_crash_count())
+                        };
                         let (msg, new_enabled) = if was_stopped {
                             ("[系统] 插件已被用户停止".to_string(), false)
+                        } else if let Some(code) = exit_code {
+                            (format!("[系统] 插件进程已退出 (退出码: {})", code), true)
                         } else {
                             ("[系统] 插件进程已退出".to_string(), true)
                         };
                         let sender = output_sender.clone();
                         let id = plugin_id_clone.clone();
-                        let msg_for_async = msg.clone();
+                        let event = PluginOutputEvent::new(id.clone(), msg);
+                        let msg_for_async = event.output.clone();
                         let _handle = rt_handle.spawn(async move {
                             plugin_inner.add_output(msg_for_async).await;
                             plugin_inner.set_status(PluginStatus::Stopped).await;
@@ -300,16 +327,24 @@ impl PluginManager {
                                 plugin_inner.set_enabled(false).await;
                             }
                         });
-                        let _ = sender.send(PluginOutputEvent {
-                            plugin_id: id.clone(),
-                            line: msg,
-                        });
+                        let _ = sender.send(event);
                         let _ = status_sender.send(PluginStatusEvent {
-                            plugin_id: id,
+                            plugin_id: id.clone(),
                             status: PluginStatus::Stopped,
                             enabled: new_enabled,
                             webui_url: None,
+                            crash_count,
                         });
+
+                        if !was_stopped {
+                            let uptime = rt_handle.block_on(plugin_clone.get_uptime());
+                            let _ = exit_sender.send(PluginExit {
+                                plugin_id: id,
+                                run_id,
+                                failed,
+                                uptime,
+                            });
+                        }
                     }
                 }
                 Err(e) => {
@@ -326,28 +361,30 @@ impl PluginManager {
                     let sender = output_sender.clone();
                     let id = plugin_id_clone.clone();
                     if plugin_clone.is_current_run(run_id) {
-                        let err_msg_for_async = err_msg.clone();
+                        let event = PluginOutputEvent::new(id.clone(), err_msg);
+                        let err_msg_for_async = event.output.clone();
                         let _handle = rt_handle.spawn(async move {
                             plugin_inner.add_output(err_msg_for_async).await;
                             plugin_inner.set_status(PluginStatus::Error).await;
                             plugin_inner.set_api_token(None).await;
                             plugin_inner.clear_webui().await;
                         });
-                        let _ = sender.send(PluginOutputEvent {
-                            plugin_id: id.clone(),
-                            line: err_msg,
-                        });
+                        let _ = sender.send(event);
+                        let crash_count = rt_handle.block_on(plugin_clone.get_crash_count());
                         let _ = status_sender.send(PluginStatusEvent {
                             plugin_id: id,
                             status: PluginStatus::Error,
                             enabled: true,
                             webui_url: None,
+                            crash_count,
                         });
                     }
                 }
             }
         });
 
+        self.watch_readiness(&plugin, run_id).await;
+
         Ok(())
     }
 
@@ -362,6 +399,7 @@ impl PluginManager {
         let stop_run_id = plugin.request_stop_current_run();
 
         if is_user_action && plugin.is_current_run(stop_run_id) {
+            plugin.reset_restart_attempts().await;
             plugin.set_enabled(false).await;
             plugin.set_api_token(None).await;
             plugin.clear_webui().await;
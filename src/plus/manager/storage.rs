@@ -1,6 +1,9 @@
-use super::{copy_dir_all, PluginInfo, PluginManager};
+use super::{copy_dir_all, PluginInfo, PluginManager, PluginPermissionInfo};
 use crate::error::AppResult;
+use crate::plus::output_line::PluginOutputLine;
+use crate::plus::output_log::{PluginOutputQuery, PluginOutputRecord};
 use crate::plus::plugin::{Plugin, PluginManifest, PluginStatus};
+use crate::rotating_log::Retention;
 use crate::runtime;
 use std::path::Path;
 use tokio::task::spawn_blocking;
@@ -27,14 +30,23 @@ impl PluginManager {
             return Ok(());
         }
 
+        let mut config = self.load_config().await;
         let mut plugins = self.plugins.write().await;
 
         for path in dir_entries {
             if let Ok(plugin) = self.load_plugin_from_dir(&path).await {
                 let id = plugin.id.clone();
-                plugins
-                    .entry(id)
-                    .or_insert_with(|| std::sync::Arc::new(plugin));
+                if plugins.contains_key(&id) {
+                    continue;
+                }
+
+                if let Some(approved) = config.approved_permissions.remove(&id) {
+                    plugin.set_approved_permissions(approved).await;
+                }
+                if let Some(bots) = config.bot_bindings.remove(&id) {
+                    plugin.set_bot_bindings(bots).await;
+                }
+                plugins.insert(id, std::sync::Arc::new(plugin));
             }
         }
 
@@ -123,8 +135,23 @@ impl PluginManager {
         for plugin in plugins.values() {
             let status = plugin.get_status().await;
             let enabled = plugin.is_enabled().await;
-            let output = plugin.get_output().await;
+            let lines = plugin.get_output().await;
+            let output_spans = lines.iter().map(|line| line.spans.clone()).collect();
+            let output = lines.into_iter().map(|line| line.line).collect();
             let webui_url = plugin.get_webui_url().await;
+            let crash_count = plugin.get_crash_count().await;
+            let approved_permissions = plugin.get_approved_permissions().await;
+            let permissions = plugin
+                .manifest
+                .permissions
+                .iter()
+                .map(|name| PluginPermissionInfo {
+                    name: name.clone(),
+                    approved: approved_permissions.contains(name),
+                })
+                .collect();
+            let bots = plugin.get_bot_bindings().await;
+            let ready = plugin.is_ready().await;
 
             result.push(PluginInfo {
                 id: plugin.id.clone(),
@@ -135,14 +162,23 @@ impl PluginManager {
                 status,
                 enabled,
                 output,
+                output_spans,
                 webui_url,
+                crash_count,
+                permissions,
+                bots,
+                depends_on: plugin.manifest.depends_on.clone(),
+                ready,
             });
         }
 
         Ok(result)
     }
 
-    pub async fn get_plugin_output(&self, plugin_id: &str) -> Result<Vec<String>, String> {
+    pub async fn get_plugin_output(
+        &self,
+        plugin_id: &str,
+    ) -> Result<Vec<PluginOutputLine>, String> {
         let plugins = self.plugins.read().await;
         let plugin = plugins
             .get(plugin_id)
@@ -153,11 +189,38 @@ impl PluginManager {
         Ok(plugin.get_output().await)
     }
 
+    /// 查询持久化的插件输出历史，插件卸载后历史仍可查询
+    pub async fn query_plugin_output(
+        &self,
+        plugin_id: &str,
+        query: &PluginOutputQuery,
+    ) -> Result<Vec<PluginOutputRecord>, String> {
+        self.output_log.query(plugin_id, query).await
+    }
+
+    pub fn set_output_retention(&self, retention: Retention) {
+        self.output_log.set_retention(retention);
+    }
+
     pub async fn get_plugin_name(&self, plugin_id: &str) -> Option<String> {
         let plugins = self.plugins.read().await;
         plugins.get(plugin_id).map(|p| p.manifest.name.clone())
     }
 
+    /// 插件在 `app.json` 中声明的事件类型，`None` 表示插件不存在
+    pub async fn get_plugin_event_types(&self, plugin_id: &str) -> Option<Vec<String>> {
+        let plugins = self.plugins.read().await;
+        plugins.get(plugin_id).map(|p| p.manifest.events.clone())
+    }
+
+    pub async fn is_plugin_running(&self, plugin_id: &str) -> bool {
+        let plugin = self.plugins.read().await.get(plugin_id).cloned();
+        match plugin {
+            Some(plugin) => plugin.is_process_alive().await,
+            None => false,
+        }
+    }
+
     pub async fn clear_plugin_output(&self, plugin_id: &str) -> Result<(), String> {
         let plugins = self.plugins.read().await;
         let plugin = plugins
@@ -191,11 +254,13 @@ impl PluginManager {
         let status = plugin.get_status().await;
         let enabled = plugin.is_enabled().await;
         let webui_url = plugin.get_webui_url().await;
+        let crash_count = plugin.get_crash_count().await;
         let _ = self.status_sender.send(super::PluginStatusEvent {
             plugin_id: plugin_id.to_string(),
             status,
             enabled,
             webui_url,
+            crash_count,
         });
         Ok(())
     }
@@ -1,4 +1,7 @@
+pub mod ansi;
 pub mod manager;
+pub mod output_line;
+pub mod output_log;
 pub mod plugin;
 
 pub use manager::PluginManager;
@@ -0,0 +1,269 @@
+use crate::plus::ansi::{self, AnsiSpan, AnsiStyle};
+use rocket::FromFormField;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// 识别 JSON 日志时，作为日志级别的字段名
+const LEVEL_KEYS: [&str; 4] = ["level", "lvl", "severity", "levelname"];
+/// 识别 JSON 日志时，作为消息正文的字段名
+const MESSAGE_KEYS: [&str; 2] = ["msg", "message"];
+/// 识别 JSON 日志时，作为插件自身记录时间的字段名
+const TIME_KEYS: [&str; 4] = ["time", "timestamp", "ts", "@timestamp"];
+/// 只在行首的前几个词中查找级别前缀，避免把正文中的单词误认为级别
+const MAX_PREFIX_TOKENS: usize = 4;
+
+/// 插件输出的日志级别，按严重程度从低到高排列
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Serialize,
+    Deserialize,
+    FromFormField,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum PluginLogLevel {
+    Trace,
+    Debug,
+    #[default]
+    Info,
+    Warn,
+    Error,
+}
+
+impl PluginLogLevel {
+    fn from_word(word: &str) -> Option<Self> {
+        match word.to_ascii_lowercase().as_str() {
+            "trace" | "trc" | "verbose" => Some(Self::Trace),
+            "debug" | "dbg" | "调试" => Some(Self::Debug),
+            "info" | "inf" | "information" | "notice" | "信息" | "系统" => Some(Self::Info),
+            "warn" | "wrn" | "warning" | "警告" => Some(Self::Warn),
+            "error" | "err" | "fatal" | "critical" | "crit" | "panic" | "错误" => {
+                Some(Self::Error)
+            }
+            _ => None,
+        }
+    }
+
+    /// pino / bunyan 使用的数字级别
+    fn from_number(level: f64) -> Self {
+        if level <= 10.0 {
+            Self::Trace
+        } else if level <= 20.0 {
+            Self::Debug
+        } else if level <= 30.0 {
+            Self::Info
+        } else if level <= 40.0 {
+            Self::Warn
+        } else {
+            Self::Error
+        }
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::String(word) => Self::from_word(word.trim()),
+            Value::Number(number) => number.as_f64().map(Self::from_number),
+            _ => None,
+        }
+    }
+}
+
+/// 一行插件输出，附带从内容中解析出的级别和结构化字段
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginOutputLine {
+    /// 去除终端控制序列后的纯文本
+    pub line: String,
+    /// 带颜色的输出按样式切分的文本段，拼接后与 `line` 相同；没有颜色时为空
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub spans: Vec<AnsiSpan>,
+    /// 主程序收到该行时的毫秒级 Unix 时间戳
+    pub timestamp: i64,
+    pub level: PluginLogLevel,
+    /// JSON 日志中的消息正文
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// JSON 日志中插件自己记录的时间，原样保留
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time: Option<Value>,
+    /// JSON 日志中除级别、消息和时间以外的字段
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub fields: Map<String, Value>,
+}
+
+impl PluginOutputLine {
+    /// 解析一行终端输出：先提取颜色，再优先按 JSON 日志解析，否则查找行首的级别前缀，
+    /// 都没有时视为 info。`style` 为同一输出流上一行结束时的颜色，解析后随本行更新
+    pub fn new(raw: &str, style: &mut AnsiStyle) -> Self {
+        let (line, spans) = ansi::parse_line(raw, style);
+        let mut output = Self {
+            line,
+            spans,
+            timestamp: chrono::Local::now().timestamp_millis(),
+            level: PluginLogLevel::Info,
+            message: None,
+            time: None,
+            fields: Map::new(),
+        };
+
+        if let Some(fields) = parse_json_object(&output.line) {
+            output.apply_json(fields);
+        } else if let Some(level) = detect_prefix_level(&output.line) {
+            output.level = level;
+        }
+        output
+    }
+
+    fn apply_json(&mut self, mut fields: Map<String, Value>) {
+        if let Some(level) = take_first(&mut fields, &LEVEL_KEYS) {
+            self.level = PluginLogLevel::from_value(&level).unwrap_or_default();
+        }
+        self.message = take_first(&mut fields, &MESSAGE_KEYS).map(|message| match message {
+            Value::String(message) => message,
+            other => other.to_string(),
+        });
+        self.time = take_first(&mut fields, &TIME_KEYS);
+        self.fields = fields;
+    }
+
+    pub fn is_at_least(&self, level: Option<PluginLogLevel>) -> bool {
+        level.is_none_or(|level| self.level >= level)
+    }
+}
+
+fn parse_json_object(line: &str) -> Option<Map<String, Value>> {
+    let trimmed = line.trim();
+    if !trimmed.starts_with('{') || !trimmed.ends_with('}') {
+        return None;
+    }
+    match serde_json::from_str(trimmed) {
+        Ok(Value::Object(fields)) => Some(fields),
+        _ => None,
+    }
+}
+
+/// 按顺序取出第一个存在的字段，其余同义字段一并移除
+fn take_first(fields: &mut Map<String, Value>, keys: &[&str]) -> Option<Value> {
+    let mut found = None;
+    for key in keys {
+        if let Some(value) = fields.remove(*key) {
+            found.get_or_insert(value);
+        }
+    }
+    found
+}
+
+/// 识别 `[WARN]`、`<error>`、`INFO:`、`level=debug` 以及单独的大写级别单词，
+/// 如 `2024-01-01 12:00:00 [ERROR] ...`、Python logging 默认的 `WARNING:root:...`
+fn detect_prefix_level(line: &str) -> Option<PluginLogLevel> {
+    line.split_whitespace()
+        .take(MAX_PREFIX_TOKENS)
+        .find_map(token_level)
+}
+
+fn token_level(token: &str) -> Option<PluginLogLevel> {
+    if let Some(value) = token
+        .strip_prefix("level=")
+        .or_else(|| token.strip_prefix("lvl="))
+    {
+        return PluginLogLevel::from_word(value.trim_matches('"'));
+    }
+
+    for (open, close) in [('[', ']'), ('<', '>'), ('(', ')')] {
+        if let Some(inner) = token
+            .strip_prefix(open)
+            .and_then(|rest| rest.trim_end_matches(':').strip_suffix(close))
+        {
+            return PluginLogLevel::from_word(inner.trim());
+        }
+    }
+
+    if let Some((head, _)) = token.split_once(':') {
+        return PluginLogLevel::from_word(head);
+    }
+
+    let is_upper_word = !token.is_empty() && token.chars().all(|c| c.is_ascii_uppercase());
+    if is_upper_word {
+        return PluginLogLevel::from_word(token);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn level(raw: &str) -> PluginLogLevel {
+        PluginOutputLine::new(raw, &mut AnsiStyle::default()).level
+    }
+
+    #[test]
+    fn prefix_levels() {
+        assert_eq!(level("[WARN] disk almost full"), PluginLogLevel::Warn);
+        assert_eq!(
+            level("2024-01-01 12:00:00 [ERROR] boom"),
+            PluginLogLevel::Error
+        );
+        assert_eq!(level("<debug> tick"), PluginLogLevel::Debug);
+        assert_eq!(level("WARNING:root:deprecated"), PluginLogLevel::Warn);
+        assert_eq!(level("time=1 level=trace msg=x"), PluginLogLevel::Trace);
+        assert_eq!(level("12:00:00 INFO started"), PluginLogLevel::Info);
+        assert_eq!(level("[错误] 启动插件失败"), PluginLogLevel::Error);
+    }
+
+    #[test]
+    fn plain_lines_default_to_info() {
+        assert_eq!(level("hello world"), PluginLogLevel::Info);
+        assert_eq!(level(""), PluginLogLevel::Info);
+    }
+
+    #[test]
+    fn level_words_later_in_the_line_are_ignored() {
+        assert_eq!(
+            level("user said this is an ERROR in the text"),
+            PluginLogLevel::Info
+        );
+        assert_eq!(level("a b c d ERROR"), PluginLogLevel::Info);
+    }
+
+    #[test]
+    fn level_prefix_after_colour_codes() {
+        assert_eq!(
+            level("\x1b[31m[ERROR]\x1b[0m failed"),
+            PluginLogLevel::Error
+        );
+    }
+
+    #[test]
+    fn json_log_lines() {
+        let output = PluginOutputLine::new(
+            r#"{"level":"warn","msg":"slow","time":"2024-01-01","latency":120}"#,
+            &mut AnsiStyle::default(),
+        );
+        assert_eq!(output.level, PluginLogLevel::Warn);
+        assert_eq!(output.message.as_deref(), Some("slow"));
+        assert_eq!(output.time, Some(Value::from("2024-01-01")));
+        assert_eq!(output.fields.get("latency"), Some(&Value::from(120)));
+        assert!(!output.fields.contains_key("level"));
+    }
+
+    #[test]
+    fn json_numeric_levels() {
+        assert_eq!(level(r#"{"level":50,"msg":"x"}"#), PluginLogLevel::Error);
+        assert_eq!(level(r#"{"level":20,"msg":"x"}"#), PluginLogLevel::Debug);
+        assert_eq!(
+            level(r#"{"severity":"critical","message":"x"}"#),
+            PluginLogLevel::Error
+        );
+    }
+
+    #[test]
+    fn unknown_json_level_falls_back_to_info() {
+        assert_eq!(level(r#"{"level":"loud"}"#), PluginLogLevel::Info);
+    }
+}
@@ -0,0 +1,419 @@
+use crate::plus::ansi::AnsiSpan;
+use crate::plus::manager::PluginOutputEvent;
+use crate::plus::output_line::PluginLogLevel;
+use crate::rotating_log::{self, JsonlWriter, Retention, RotatingJsonl};
+use chrono::{Local, TimeZone};
+use regex::Regex;
+use rocket::FromForm;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use tokio::sync::{broadcast, mpsc};
+
+/// 持久化的一行插件输出
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginOutputRecord {
+    pub time: String,
+    /// 毫秒级 Unix 时间戳
+    pub timestamp: i64,
+    pub line: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub spans: Vec<AnsiSpan>,
+    /// 旧版本写入的记录没有级别，按 info 处理
+    #[serde(default)]
+    pub level: PluginLogLevel,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// JSON 日志中插件自己记录的时间，原样保留；`time` 为主程序收到该行的时间
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_time: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub fields: serde_json::Map<String, serde_json::Value>,
+}
+
+impl From<&PluginOutputEvent> for PluginOutputRecord {
+    fn from(event: &PluginOutputEvent) -> Self {
+        let output = &event.output;
+        let time = Local
+            .timestamp_millis_opt(output.timestamp)
+            .single()
+            .unwrap_or_else(Local::now);
+        Self {
+            time: time.format("%Y-%m-%d %H:%M:%S%.3f").to_string(),
+            timestamp: output.timestamp,
+            line: output.line.clone(),
+            spans: output.spans.clone(),
+            level: output.level,
+            message: output.message.clone(),
+            log_time: output.time.clone(),
+            fields: output.fields.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Default, FromForm)]
+pub struct PluginOutputQuery {
+    /// 毫秒级 Unix 时间戳（含）
+    pub since: Option<i64>,
+    /// 毫秒级 Unix 时间戳（含）
+    pub until: Option<i64>,
+    /// 按子串搜索，`regex=true` 时按正则表达式搜索
+    pub q: Option<String>,
+    pub regex: Option<bool>,
+    /// 最低日志级别，如 `warn` 只返回 warn 和 error
+    pub level: Option<PluginLogLevel>,
+    /// 跳过最新的若干条匹配记录，用于分页
+    pub offset: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+enum LineMatcher {
+    All,
+    Substring(String),
+    Regex(Regex),
+}
+
+impl LineMatcher {
+    fn new(query: &PluginOutputQuery) -> Result<Self, String> {
+        let Some(pattern) = query.q.as_deref().filter(|q| !q.is_empty()) else {
+            return Ok(Self::All);
+        };
+        if query.regex.unwrap_or(false) {
+            Regex::new(pattern)
+                .map(Self::Regex)
+                .map_err(|e| format!("Invalid regex: {}", e))
+        } else {
+            Ok(Self::Substring(pattern.to_string()))
+        }
+    }
+
+    fn matches(&self, line: &str) -> bool {
+        match self {
+            Self::All => true,
+            Self::Substring(pattern) => line.contains(pattern.as_str()),
+            Self::Regex(regex) => regex.is_match(line),
+        }
+    }
+}
+
+/// 插件输出日志，按插件写入 `logs/plugins/<插件ID>/` 并按保留策略轮转，重启后仍可查询
+pub struct PluginOutputLog {
+    dir: PathBuf,
+    /// 所有插件共用的保留策略
+    retention: Arc<RwLock<Retention>>,
+}
+
+impl PluginOutputLog {
+    /// 创建输出日志并启动后台写入任务，需要在 tokio 运行时中调用
+    pub fn new(dir: PathBuf, events: broadcast::Receiver<PluginOutputEvent>) -> Arc<Self> {
+        let (writer, receiver) = mpsc::unbounded_channel();
+        let retention = Arc::new(RwLock::new(Retention::default()));
+
+        tokio::spawn(forward_events(events, writer));
+        tokio::spawn(run_writer(dir.clone(), retention.clone(), receiver));
+
+        Arc::new(Self { dir, retention })
+    }
+
+    /// 修改保留策略，在各插件下次轮转或重新打开文件时生效
+    pub fn set_retention(&self, retention: Retention) {
+        if let Ok(mut current) = self.retention.write() {
+            *current = retention.normalized();
+        }
+    }
+
+    /// 按条件查询插件输出，结果按时间从新到旧排列
+    pub async fn query(
+        &self,
+        plugin_id: &str,
+        query: &PluginOutputQuery,
+    ) -> Result<Vec<PluginOutputRecord>, String> {
+        if plugin_id.is_empty() || plugin_id.contains(['/', '\\']) || plugin_id.starts_with('.') {
+            return Err(format!("Invalid plugin id '{}'", plugin_id));
+        }
+
+        let matcher = LineMatcher::new(query)?;
+        let limit = rotating_log::query_limit(query.limit);
+        let mut skip = query.offset.unwrap_or(0);
+        let mut result = Vec::new();
+
+        output_files(&self.dir, plugin_id, &self.retention)
+            .scan_newest_first(|record: PluginOutputRecord| {
+                if query.since.is_some_and(|since| record.timestamp < since) {
+                    return ControlFlow::Break(());
+                }
+                if query.until.is_some_and(|until| record.timestamp > until)
+                    || query.level.is_some_and(|level| record.level < level)
+                    || !matcher.matches(&record.line)
+                {
+                    return ControlFlow::Continue(());
+                }
+
+                if skip > 0 {
+                    skip -= 1;
+                    return ControlFlow::Continue(());
+                }
+                result.push(record);
+                if result.len() >= limit {
+                    return ControlFlow::Break(());
+                }
+                ControlFlow::Continue(())
+            })
+            .await
+            .map_err(|e| format!("Failed to read plugin output log: {}", e))?;
+
+        Ok(result)
+    }
+}
+
+/// 每个插件的输出写入各自目录下的 `output.jsonl`
+fn output_files(dir: &Path, plugin_id: &str, retention: &Arc<RwLock<Retention>>) -> RotatingJsonl {
+    RotatingJsonl::with_retention(dir.join(plugin_id), "output", retention.clone())
+}
+
+/// 把输出事件转入无界队列，写文件较慢时也不会因广播通道积压而丢失输出
+async fn forward_events(
+    mut events: broadcast::Receiver<PluginOutputEvent>,
+    writer: mpsc::UnboundedSender<PluginOutputEvent>,
+) {
+    loop {
+        match events.recv().await {
+            Ok(event) => {
+                if writer.send(event).is_err() {
+                    break;
+                }
+            }
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                log_warn!("插件输出过多，{} 行未能写入输出日志", skipped);
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
+
+async fn run_writer(
+    dir: PathBuf,
+    retention: Arc<RwLock<Retention>>,
+    mut receiver: mpsc::UnboundedReceiver<PluginOutputEvent>,
+) {
+    let mut writers: HashMap<String, JsonlWriter> = HashMap::new();
+
+    while let Some(event) = receiver.recv().await {
+        let writer = writers
+            .entry(event.plugin_id.clone())
+            .or_insert_with(|| JsonlWriter::new(output_files(&dir, &event.plugin_id, &retention)));
+        if let Err(e) = writer.append(&PluginOutputRecord::from(&event)).await {
+            log_error!("Failed to write plugin output log: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    /// 每个测试使用独立的临时目录，结束时删除
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new() -> Self {
+            let suffix: u64 = rand::rng().random();
+            Self(std::env::temp_dir().join(format!("yuyubot-output-test-{:016x}", suffix)))
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn record(timestamp: i64, line: &str, level: PluginLogLevel) -> PluginOutputRecord {
+        PluginOutputRecord {
+            time: String::new(),
+            timestamp,
+            line: line.to_string(),
+            spans: Vec::new(),
+            level,
+            message: None,
+            log_time: None,
+            fields: serde_json::Map::new(),
+        }
+    }
+
+    /// 直接写入输出文件，不经过插件事件
+    async fn output_log(dir: &TempDir) -> PluginOutputLog {
+        let log = PluginOutputLog {
+            dir: dir.0.clone(),
+            retention: Arc::new(RwLock::new(Retention::default())),
+        };
+        let mut writer = JsonlWriter::new(output_files(&log.dir, "demo", &log.retention));
+        let lines = [
+            (1, "starting", PluginLogLevel::Info),
+            (2, "connected to server", PluginLogLevel::Debug),
+            (3, "request 17 failed", PluginLogLevel::Error),
+            (4, "request 18 ok", PluginLogLevel::Info),
+            (5, "request 19 failed", PluginLogLevel::Warn),
+        ];
+        for (timestamp, line, level) in lines {
+            writer
+                .append(&record(timestamp, line, level))
+                .await
+                .unwrap();
+        }
+        log
+    }
+
+    async fn query(log: &PluginOutputLog, query: PluginOutputQuery) -> Vec<i64> {
+        log.query("demo", &query)
+            .await
+            .unwrap()
+            .iter()
+            .map(|record| record.timestamp)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn query_filters_by_time_range_newest_first() {
+        let dir = TempDir::new();
+        let log = output_log(&dir).await;
+
+        assert_eq!(
+            query(&log, PluginOutputQuery::default()).await,
+            vec![5, 4, 3, 2, 1]
+        );
+        assert_eq!(
+            query(
+                &log,
+                PluginOutputQuery {
+                    since: Some(2),
+                    until: Some(4),
+                    ..Default::default()
+                }
+            )
+            .await,
+            vec![4, 3, 2]
+        );
+    }
+
+    #[tokio::test]
+    async fn query_pages_with_offset_and_limit() {
+        let dir = TempDir::new();
+        let log = output_log(&dir).await;
+
+        assert_eq!(
+            query(
+                &log,
+                PluginOutputQuery {
+                    offset: Some(1),
+                    limit: Some(2),
+                    ..Default::default()
+                }
+            )
+            .await,
+            vec![4, 3]
+        );
+        assert_eq!(
+            query(
+                &log,
+                PluginOutputQuery {
+                    level: Some(PluginLogLevel::Info),
+                    offset: Some(2),
+                    ..Default::default()
+                }
+            )
+            .await,
+            vec![3, 1]
+        );
+    }
+
+    #[tokio::test]
+    async fn query_matches_substring_and_regex() {
+        let dir = TempDir::new();
+        let log = output_log(&dir).await;
+
+        assert_eq!(
+            query(
+                &log,
+                PluginOutputQuery {
+                    q: Some("failed".to_string()),
+                    ..Default::default()
+                }
+            )
+            .await,
+            vec![5, 3]
+        );
+        assert_eq!(
+            query(
+                &log,
+                PluginOutputQuery {
+                    q: Some(r"request \d+ (ok|failed)".to_string()),
+                    regex: Some(true),
+                    until: Some(4),
+                    ..Default::default()
+                }
+            )
+            .await,
+            vec![4, 3]
+        );
+        // 未开启正则时按原样匹配子串
+        assert!(query(
+            &log,
+            PluginOutputQuery {
+                q: Some(r"request \d+".to_string()),
+                ..Default::default()
+            }
+        )
+        .await
+        .is_empty());
+
+        let error = log
+            .query(
+                "demo",
+                &PluginOutputQuery {
+                    q: Some("(".to_string()),
+                    regex: Some(true),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap_err();
+        assert!(error.starts_with("Invalid regex"));
+    }
+
+    #[test]
+    fn record_keeps_the_time_reported_by_the_plugin() {
+        let event = PluginOutputEvent::new(
+            "demo",
+            r#"{"level":"warn","msg":"slow","time":"2024-01-01T08:00:00Z"}"#.to_string(),
+        );
+        let record = PluginOutputRecord::from(&event);
+        assert_eq!(
+            record.log_time,
+            Some(serde_json::Value::from("2024-01-01T08:00:00Z"))
+        );
+        assert_eq!(record.timestamp, event.output.timestamp);
+
+        let json = serde_json::to_value(&record).unwrap();
+        assert_eq!(json["logTime"], "2024-01-01T08:00:00Z");
+        let parsed: PluginOutputRecord = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.log_time, record.log_time);
+    }
+
+    #[tokio::test]
+    async fn query_rejects_plugin_ids_outside_the_log_directory() {
+        let dir = TempDir::new();
+        let log = output_log(&dir).await;
+
+        for plugin_id in ["", "../demo", "a/b", ".hidden"] {
+            assert!(log
+                .query(plugin_id, &PluginOutputQuery::default())
+                .await
+                .is_err());
+        }
+    }
+}
@@ -1,6 +1,9 @@
+use crate::plus::output_line::PluginOutputLine;
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
 
 /// 插件输出最大行数限制
@@ -14,6 +17,129 @@ pub struct PluginManifest {
     pub version: String,
     #[serde(default)]
     pub author: Option<String>,
+    #[serde(default)]
+    pub restart: Option<RestartPolicy>,
+    /// 插件需要调用的有副作用的 Milky API（`get_*` 只读接口无需声明）
+    #[serde(default)]
+    pub permissions: Vec<String>,
+    /// 插件需要接收的事件类型（如 `message_receive`），为空时接收全部事件
+    #[serde(default)]
+    pub events: Vec<String>,
+    /// 依赖的其他插件 ID，这些插件启动并就绪后才会启动本插件
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// 插件启动后如何判断已就绪，未声明时进程启动即视为就绪
+    #[serde(default)]
+    pub ready: Option<ReadinessCheck>,
+}
+
+fn default_ready_timeout_secs() -> u64 {
+    30
+}
+
+/// 插件就绪检查方式
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ReadinessCheck {
+    /// 插件调用主程序的 `POST /api/plugin/ready` 通知已就绪
+    Api {
+        #[serde(default = "default_ready_timeout_secs")]
+        timeout_secs: u64,
+    },
+    /// 插件在本机监听的 TCP 端口可以连接时视为就绪
+    Tcp {
+        port: u16,
+        #[serde(default = "default_ready_timeout_secs")]
+        timeout_secs: u64,
+    },
+}
+
+impl ReadinessCheck {
+    pub fn timeout(&self) -> Duration {
+        match self {
+            Self::Api { timeout_secs } | Self::Tcp { timeout_secs, .. } => {
+                Duration::from_secs(*timeout_secs)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum RestartMode {
+    /// 从不自动重启
+    #[default]
+    Never,
+    /// 仅在进程以非 0 退出码（或无法获取退出码）退出时重启
+    OnFailure,
+    /// 只要不是用户主动停止，进程退出后总是重启
+    Always,
+}
+
+fn default_restart_max_retries() -> u32 {
+    5
+}
+
+fn default_restart_initial_backoff_ms() -> u64 {
+    1000
+}
+
+fn default_restart_max_backoff_ms() -> u64 {
+    60_000
+}
+
+fn default_restart_reset_after_secs() -> u64 {
+    300
+}
+
+/// 插件进程意外退出后的自动重启策略
+///
+/// 可在 `app.json` 的 `restart` 字段中声明，也可在 `config/plugins.json` 的
+/// `restart_policies` 中按插件 ID 覆盖。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestartPolicy {
+    #[serde(default)]
+    pub mode: RestartMode,
+    /// 连续重启的最大次数，0 表示不限制
+    #[serde(default = "default_restart_max_retries")]
+    pub max_retries: u32,
+    /// 第一次重启前的等待时间，之后每次翻倍
+    #[serde(default = "default_restart_initial_backoff_ms")]
+    pub initial_backoff_ms: u64,
+    /// 重启等待时间的上限
+    #[serde(default = "default_restart_max_backoff_ms")]
+    pub max_backoff_ms: u64,
+    /// 进程持续运行超过该时长后，连续重启计数清零
+    #[serde(default = "default_restart_reset_after_secs")]
+    pub reset_after_secs: u64,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            mode: RestartMode::default(),
+            max_retries: default_restart_max_retries(),
+            initial_backoff_ms: default_restart_initial_backoff_ms(),
+            max_backoff_ms: default_restart_max_backoff_ms(),
+            reset_after_secs: default_restart_reset_after_secs(),
+        }
+    }
+}
+
+impl RestartPolicy {
+    /// 第 `attempt` 次（从 1 开始）重启前的等待时间
+    pub fn backoff_delay(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(31);
+        let delay = self
+            .initial_backoff_ms
+            .saturating_mul(1u64 << exponent)
+            .min(self.max_backoff_ms.max(self.initial_backoff_ms));
+        Duration::from_millis(delay)
+    }
+
+    pub fn reset_window(&self) -> Duration {
+        Duration::from_secs(self.reset_after_secs)
+    }
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
@@ -33,10 +159,22 @@ pub struct PluginState {
     pub status: PluginStatus,
     pub is_alive: bool,
     pub pid: u32,
-    pub output: Vec<String>,
+    /// 最近的输出，环形缓冲区，完整历史见输出日志文件
+    pub output: VecDeque<PluginOutputLine>,
     pub enabled: bool,
     pub api_token: Option<String>,
     pub webui: Option<PluginWebUi>,
+    /// 本次框架运行期间插件意外退出的次数
+    pub crash_count: u32,
+    /// 当前连续自动重启的次数
+    pub restart_attempts: u32,
+    pub started_at: Option<Instant>,
+    /// 运维人员已批准的权限，与 `manifest.permissions` 取交集后生效
+    pub approved_permissions: Vec<String>,
+    /// 插件可以使用的 Bot 账号 ID，为空表示可以使用全部账号
+    pub bot_bindings: Vec<String>,
+    /// 本次运行是否已通过就绪检查
+    pub ready: bool,
 }
 
 pub struct Plugin {
@@ -68,10 +206,16 @@ impl Plugin {
                 status: PluginStatus::Stopped,
                 is_alive: false,
                 pid: 0,
-                output: Vec::new(),
+                output: VecDeque::with_capacity(MAX_OUTPUT_LINES),
                 enabled: false,
                 api_token: None,
                 webui: None,
+                crash_count: 0,
+                restart_attempts: 0,
+                started_at: None,
+                approved_permissions: Vec::new(),
+                bot_bindings: Vec::new(),
+                ready: false,
             }),
         }
     }
@@ -111,11 +255,28 @@ impl Plugin {
     pub async fn set_process_alive(&self, alive: bool) {
         let mut state = self.state.lock().await;
         state.is_alive = alive;
-        if !alive {
+        state.ready = false;
+        if alive {
+            state.started_at = Some(Instant::now());
+        } else {
             state.pid = 0;
         }
     }
 
+    /// 距离最近一次启动经过的时间
+    pub async fn get_uptime(&self) -> Duration {
+        self.state
+            .lock()
+            .await
+            .started_at
+            .map(|t| t.elapsed())
+            .unwrap_or_default()
+    }
+
+    pub async fn set_pid(&self, pid: u32) {
+        self.state.lock().await.pid = pid;
+    }
+
     pub async fn get_pid(&self) -> u32 {
         self.state.lock().await.pid
     }
@@ -124,6 +285,16 @@ impl Plugin {
         self.state.lock().await.is_alive
     }
 
+    pub async fn set_ready(&self, ready: bool) {
+        self.state.lock().await.ready = ready;
+    }
+
+    /// 进程仍在运行且已通过就绪检查
+    pub async fn is_ready(&self) -> bool {
+        let state = self.state.lock().await;
+        state.is_alive && state.ready
+    }
+
     pub async fn is_enabled(&self) -> bool {
         self.state.lock().await.enabled
     }
@@ -132,17 +303,17 @@ impl Plugin {
         self.state.lock().await.enabled = enabled;
     }
 
-    pub async fn get_output(&self) -> Vec<String> {
-        self.state.lock().await.output.clone()
+    pub async fn get_output(&self) -> Vec<PluginOutputLine> {
+        self.state.lock().await.output.iter().cloned().collect()
     }
 
-    pub async fn add_output(&self, line: String) {
+    pub async fn add_output(&self, line: PluginOutputLine) {
         let mut state = self.state.lock().await;
-        state.output.push(line);
         // 限制最大行数
-        if state.output.len() > MAX_OUTPUT_LINES {
-            state.output.remove(0);
+        if state.output.len() >= MAX_OUTPUT_LINES {
+            state.output.pop_front();
         }
+        state.output.push_back(line);
     }
 
     pub async fn clear_output(&self) {
@@ -165,6 +336,55 @@ impl Plugin {
         self.state.lock().await.webui = None;
     }
 
+    pub async fn record_crash(&self) -> u32 {
+        let mut state = self.state.lock().await;
+        state.crash_count += 1;
+        state.crash_count
+    }
+
+    pub async fn get_crash_count(&self) -> u32 {
+        self.state.lock().await.crash_count
+    }
+
+    /// 递增连续重启计数并返回本次是第几次重启
+    pub async fn next_restart_attempt(&self) -> u32 {
+        let mut state = self.state.lock().await;
+        state.restart_attempts += 1;
+        state.restart_attempts
+    }
+
+    pub async fn reset_restart_attempts(&self) {
+        self.state.lock().await.restart_attempts = 0;
+    }
+
+    pub async fn set_approved_permissions(&self, permissions: Vec<String>) {
+        self.state.lock().await.approved_permissions = permissions;
+    }
+
+    pub async fn get_approved_permissions(&self) -> Vec<String> {
+        self.state.lock().await.approved_permissions.clone()
+    }
+
+    pub async fn set_bot_bindings(&self, bots: Vec<String>) {
+        self.state.lock().await.bot_bindings = bots;
+    }
+
+    pub async fn get_bot_bindings(&self) -> Vec<String> {
+        self.state.lock().await.bot_bindings.clone()
+    }
+
+    /// 权限既在 `app.json` 中申请过，又已被运维人员批准
+    pub async fn is_permission_granted(&self, permission: &str) -> bool {
+        self.manifest.permissions.iter().any(|p| p == permission)
+            && self
+                .state
+                .lock()
+                .await
+                .approved_permissions
+                .iter()
+                .any(|p| p == permission)
+    }
+
     pub async fn get_webui_url(&self) -> Option<String> {
         let state = self.state.lock().await;
         let webui = state.webui.as_ref()?;
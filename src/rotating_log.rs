@@ -0,0 +1,344 @@
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::ops::ControlFlow;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+use tokio::io::AsyncWriteExt;
+
+/// 允许配置的保留文件数量上限
+const MAX_RETAINED_FILES: usize = 100;
+const DEFAULT_QUERY_LIMIT: usize = 200;
+const MAX_QUERY_LIMIT: usize = 2000;
+
+/// 查询返回的记录条数，未指定时为 200，最多 2000
+pub fn query_limit(limit: Option<usize>) -> usize {
+    limit
+        .unwrap_or(DEFAULT_QUERY_LIMIT)
+        .clamp(1, MAX_QUERY_LIMIT)
+}
+
+fn default_max_file_mb() -> u64 {
+    4
+}
+
+fn default_max_files() -> usize {
+    5
+}
+
+fn default_max_age_days() -> u32 {
+    14
+}
+
+/// 轮转文件的保留策略，文件数量和保存天数任一超出都会删除最旧的历史文件
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Retention {
+    /// 单个文件的大小上限（MB），超过后轮转
+    #[serde(default = "default_max_file_mb")]
+    pub max_file_mb: u64,
+    /// 保留的文件数量（含当前文件）
+    #[serde(default = "default_max_files")]
+    pub max_files: usize,
+    /// 历史文件最后写入后保留的天数，0 表示只按数量清理
+    #[serde(default = "default_max_age_days")]
+    pub max_age_days: u32,
+}
+
+impl Default for Retention {
+    fn default() -> Self {
+        Self {
+            max_file_mb: default_max_file_mb(),
+            max_files: default_max_files(),
+            max_age_days: default_max_age_days(),
+        }
+    }
+}
+
+impl Retention {
+    /// 把超出范围的值限制到可用的范围内
+    pub fn normalized(mut self) -> Self {
+        self.max_file_mb = self.max_file_mb.max(1);
+        self.max_files = self.max_files.clamp(1, MAX_RETAINED_FILES);
+        self
+    }
+
+    fn max_file_bytes(&self) -> u64 {
+        self.max_file_mb.saturating_mul(1024 * 1024)
+    }
+
+    fn max_age(&self) -> Option<Duration> {
+        (self.max_age_days > 0).then(|| Duration::from_secs(u64::from(self.max_age_days) * 86400))
+    }
+}
+
+/// 一组按大小轮转的 JSON Lines 文件，审计日志和插件输出日志共用
+#[derive(Debug, Clone)]
+pub struct RotatingJsonl {
+    dir: PathBuf,
+    name: &'static str,
+    retention: Arc<RwLock<Retention>>,
+}
+
+impl RotatingJsonl {
+    /// 文件写入 `dir/<name>.jsonl`，轮转后的历史文件为 `<name>.1.jsonl`、`<name>.2.jsonl` 等
+    pub fn new(dir: PathBuf, name: &'static str) -> Self {
+        Self::with_retention(dir, name, Arc::new(RwLock::new(Retention::default())))
+    }
+
+    /// 多组文件共用同一份保留策略，修改后在下次轮转时生效
+    pub fn with_retention(
+        dir: PathBuf,
+        name: &'static str,
+        retention: Arc<RwLock<Retention>>,
+    ) -> Self {
+        Self {
+            dir,
+            name,
+            retention,
+        }
+    }
+
+    fn retention(&self) -> Retention {
+        self.retention
+            .read()
+            .map(|retention| *retention)
+            .unwrap_or_default()
+            .normalized()
+    }
+
+    /// 第 0 个为当前写入的文件，其余为轮转后的历史文件
+    fn file_path(&self, index: usize) -> PathBuf {
+        if index == 0 {
+            self.dir.join(format!("{}.jsonl", self.name))
+        } else {
+            self.dir.join(format!("{}.{}.jsonl", self.name, index))
+        }
+    }
+
+    /// 列出已轮转的历史文件编号，从新到旧排列
+    async fn rotated_indexes(&self) -> Vec<usize> {
+        let mut indexes = Vec::new();
+        let Ok(mut entries) = tokio::fs::read_dir(&self.dir).await else {
+            return indexes;
+        };
+        let prefix = format!("{}.", self.name);
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let index = name
+                .strip_prefix(&prefix)
+                .and_then(|rest| rest.strip_suffix(".jsonl"))
+                .and_then(|index| index.parse::<usize>().ok())
+                .filter(|index| *index > 0);
+            indexes.extend(index);
+        }
+        indexes.sort_unstable();
+        indexes
+    }
+
+    async fn rotate(&self) {
+        let retention = self.retention();
+        let mut indexes = self.rotated_indexes().await;
+        // 从最旧的文件开始重命名，避免覆盖
+        indexes.reverse();
+        for index in indexes {
+            if index + 1 >= retention.max_files {
+                let _ = tokio::fs::remove_file(self.file_path(index)).await;
+            } else {
+                let _ = tokio::fs::rename(self.file_path(index), self.file_path(index + 1)).await;
+            }
+        }
+        if retention.max_files > 1 {
+            let _ = tokio::fs::rename(self.file_path(0), self.file_path(1)).await;
+        } else {
+            let _ = tokio::fs::remove_file(self.file_path(0)).await;
+        }
+        self.remove_expired().await;
+    }
+
+    /// 删除超出数量或超过保存天数的历史文件
+    async fn remove_expired(&self) {
+        let retention = self.retention();
+        let max_age = retention.max_age();
+        for index in self.rotated_indexes().await {
+            let path = self.file_path(index);
+            let expired = index >= retention.max_files
+                || match (max_age, tokio::fs::metadata(&path).await) {
+                    (Some(max_age), Ok(metadata)) => metadata
+                        .modified()
+                        .ok()
+                        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+                        .is_some_and(|age| age > max_age),
+                    _ => false,
+                };
+            if expired {
+                let _ = tokio::fs::remove_file(path).await;
+            }
+        }
+    }
+
+    async fn open_current(&self) -> std::io::Result<(tokio::fs::File, u64)> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+        // 重启后先按当前的保留策略清理一次
+        self.remove_expired().await;
+        let file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.file_path(0))
+            .await?;
+        let size = file.metadata().await?.len();
+        Ok((file, size))
+    }
+
+    /// 从最新的记录开始逐条交给 `visit`，返回 `Break` 时停止；无法解析的行会被跳过
+    pub async fn scan_newest_first<T: DeserializeOwned>(
+        &self,
+        mut visit: impl FnMut(T) -> ControlFlow<()>,
+    ) -> std::io::Result<()> {
+        let indexes = std::iter::once(0).chain(self.rotated_indexes().await);
+        for index in indexes {
+            let content = match tokio::fs::read_to_string(self.file_path(index)).await {
+                Ok(content) => content,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e),
+            };
+
+            for line in content.lines().rev() {
+                let Ok(record) = serde_json::from_str::<T>(line) else {
+                    continue;
+                };
+                if visit(record).is_break() {
+                    return Ok(());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 轮转文件的写入端，打开或写入失败后会在下一条记录时重新打开文件
+pub struct JsonlWriter {
+    files: RotatingJsonl,
+    current: Option<(tokio::fs::File, u64)>,
+}
+
+impl JsonlWriter {
+    pub fn new(files: RotatingJsonl) -> Self {
+        Self {
+            files,
+            current: None,
+        }
+    }
+
+    pub async fn append<T: Serialize>(&mut self, record: &T) -> std::io::Result<()> {
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+
+        let current = match self.current.take() {
+            Some(current) => current,
+            None => self.files.open_current().await?,
+        };
+        let (file, size) = self.current.insert(current);
+
+        // tokio 的文件写入在后台线程完成，flush 后查询接口才能读到最新记录
+        let written = match file.write_all(line.as_bytes()).await {
+            Ok(()) => file.flush().await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = written {
+            self.current = None;
+            return Err(e);
+        }
+        *size += line.len() as u64;
+
+        if *size >= self.files.retention().max_file_bytes() {
+            self.current = None;
+            self.files.rotate().await;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    /// 每个测试使用独立的临时目录，结束时删除
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new() -> Self {
+            let suffix: u64 = rand::rng().random();
+            Self(std::env::temp_dir().join(format!("yuyubot-jsonl-test-{:016x}", suffix)))
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    async fn scan_all(files: &RotatingJsonl) -> Vec<u64> {
+        let mut values = Vec::new();
+        files
+            .scan_newest_first(|value: u64| {
+                values.push(value);
+                ControlFlow::Continue(())
+            })
+            .await
+            .unwrap();
+        values
+    }
+
+    #[tokio::test]
+    async fn rotation_keeps_the_configured_number_of_files() {
+        let dir = TempDir::new();
+        let retention = Arc::new(RwLock::new(Retention {
+            max_file_mb: 1,
+            max_files: 3,
+            max_age_days: 0,
+        }));
+        let files = RotatingJsonl::with_retention(dir.0.clone(), "test", retention.clone());
+        let mut writer = JsonlWriter::new(files.clone());
+
+        // 每条记录都写满一个文件
+        let padding = vec![0u8; 1024 * 1024];
+        for _ in 0..4 {
+            writer.append(&padding).await.unwrap();
+        }
+        assert_eq!(files.rotated_indexes().await, vec![1, 2]);
+
+        // 调小数量后，下次打开文件时清理多出的历史文件
+        retention.write().unwrap().max_files = 2;
+        let mut writer = JsonlWriter::new(files.clone());
+        writer.append(&1u64).await.unwrap();
+        assert_eq!(files.rotated_indexes().await, vec![1]);
+        assert_eq!(scan_all(&files).await, vec![1]);
+    }
+
+    #[tokio::test]
+    async fn expired_history_files_are_removed() {
+        let dir = TempDir::new();
+        let retention = Arc::new(RwLock::new(Retention {
+            max_file_mb: 1,
+            max_files: 5,
+            max_age_days: 1,
+        }));
+        let files = RotatingJsonl::with_retention(dir.0.clone(), "test", retention);
+        tokio::fs::create_dir_all(&dir.0).await.unwrap();
+        tokio::fs::write(files.file_path(1), "2\n").await.unwrap();
+        tokio::fs::write(files.file_path(2), "3\n").await.unwrap();
+        let old = SystemTime::now() - Duration::from_secs(2 * 86400);
+        std::fs::File::options()
+            .write(true)
+            .open(files.file_path(2))
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+
+        let mut writer = JsonlWriter::new(files.clone());
+        writer.append(&1u64).await.unwrap();
+        assert_eq!(scan_all(&files).await, vec![1, 2]);
+    }
+}
@@ -1,8 +1,11 @@
 use once_cell::sync::OnceCell;
+#[cfg(windows)]
 use std::path::Path;
 use std::sync::Arc;
 use tokio::runtime::Runtime;
+#[cfg(windows)]
 use winreg::enums::{HKEY_CURRENT_USER, KEY_READ, KEY_SET_VALUE};
+#[cfg(windows)]
 use winreg::RegKey;
 
 /// 全局共享的 Tokio Runtime
@@ -13,6 +16,7 @@ use winreg::RegKey;
 /// 2. 避免多 Runtime 混用导致的问题
 /// 3. 优雅退出时能正确清理所有异步任务
 static GLOBAL_RUNTIME: OnceCell<Arc<Runtime>> = OnceCell::new();
+#[cfg(windows)]
 const AUTO_START_REG_PATH: &str = "Software\\Microsoft\\Windows\\CurrentVersion\\Run";
 
 /// 初始化全局 Runtime
@@ -69,16 +73,19 @@ pub fn get_exe_dir() -> std::path::PathBuf {
         .unwrap_or_else(|| std::path::PathBuf::from("."))
 }
 
+#[cfg(windows)]
 fn auto_start_command() -> Result<String, String> {
     let exe_path =
         std::env::current_exe().map_err(|e| format!("Failed to resolve executable path: {}", e))?;
     Ok(format!("\"{}\" --autostart", exe_path.display()))
 }
 
+#[cfg(windows)]
 fn normalize_path_for_key(path: &Path) -> String {
     path.to_string_lossy().to_lowercase()
 }
 
+#[cfg(windows)]
 fn fnv1a_hash(input: &str) -> u64 {
     let mut hash: u64 = 0xcbf29ce484222325;
     for byte in input.bytes() {
@@ -88,6 +95,7 @@ fn fnv1a_hash(input: &str) -> u64 {
     hash
 }
 
+#[cfg(windows)]
 fn auto_start_value_name() -> Result<String, String> {
     let exe_path =
         std::env::current_exe().map_err(|e| format!("Failed to resolve executable path: {}", e))?;
@@ -95,6 +103,7 @@ fn auto_start_value_name() -> Result<String, String> {
     Ok(format!("YuyuBot_{:016x}", fnv1a_hash(&normalized)))
 }
 
+#[cfg(windows)]
 fn registry_value_matches_command(
     run_key: &RegKey,
     value_name: &str,
@@ -110,6 +119,7 @@ fn registry_value_matches_command(
     }
 }
 
+#[cfg(windows)]
 pub fn is_auto_start_enabled() -> Result<bool, String> {
     let command = auto_start_command()?;
     let value_name = auto_start_value_name()?;
@@ -126,6 +136,7 @@ pub fn is_auto_start_enabled() -> Result<bool, String> {
     registry_value_matches_command(&run_key, &value_name, &command)
 }
 
+#[cfg(windows)]
 pub fn set_auto_start_enabled(enabled: bool) -> Result<(), String> {
     let command = auto_start_command()?;
     let value_name = auto_start_value_name()?;
@@ -156,18 +167,48 @@ pub fn set_auto_start_enabled(enabled: bool) -> Result<(), String> {
     }
 }
 
+#[cfg(not(windows))]
+pub fn is_auto_start_enabled() -> Result<bool, String> {
+    Ok(false)
+}
+
+#[cfg(not(windows))]
+pub fn set_auto_start_enabled(enabled: bool) -> Result<(), String> {
+    if enabled {
+        Err("Auto-start is only supported on Windows".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(windows)]
 pub fn is_auto_start_launch() -> bool {
     std::env::args().any(|arg| arg == "--autostart")
 }
 
+/// 是否以无界面模式运行
+///
+/// 非 Windows 平台始终为无界面模式；Windows 下通过 `--headless` 参数开启。
+pub fn is_headless_mode() -> bool {
+    !cfg!(windows) || std::env::args().any(|arg| arg == "--headless")
+}
+
 /// 在文件资源管理器中打开指定路径
 pub fn open_in_explorer<P: AsRef<std::path::Path>>(path: P) {
     let path = path.as_ref();
-    let mut cmd = std::process::Command::new("explorer");
-    cmd.arg(path);
 
-    use std::os::windows::process::CommandExt;
-    cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+    #[cfg(windows)]
+    let mut cmd = {
+        use std::os::windows::process::CommandExt;
+        let mut cmd = std::process::Command::new("explorer");
+        cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+        cmd
+    };
+
+    #[cfg(not(windows))]
+    let mut cmd = std::process::Command::new("xdg-open");
+
+    cmd.arg(path);
 
     let rsp = cmd.spawn();
     if let Err(e) = rsp {
@@ -1,11 +1,14 @@
 use crate::logger;
 use crate::plus::PluginManager;
+use crate::rotating_log::Retention;
+use rand::Rng;
 use rocket::{
     http::Status,
     request::{FromRequest, Outcome},
     Request,
 };
 use serde::{Deserialize, Serialize};
+use std::fmt::Write;
 use std::sync::atomic::AtomicU16;
 use std::sync::Arc;
 
@@ -14,17 +17,20 @@ mod plugins;
 mod system;
 
 pub use bot::{
-    bot_status_stream, check_and_auto_connect, disconnect_bot, get_bot_config, get_bot_status,
-    get_login_info, load_bot_config_from_disk, save_bot_config, BotConfig, BotStatusResponse,
+    bot_status_stream, check_and_auto_connect, delete_bot, disconnect_bot, get_bot_config,
+    get_bot_status, get_login_info, list_bots, load_bot_configs_from_disk, save_bot_config,
+    BotConfig, BotScheme, BotStatusResponse, BotTlsConfig, EventTransport, LoginInfo,
 };
 pub use plugins::{
-    clear_plugin_output, export_plugin, get_plugin_output, import_plugin, list_plugins,
-    open_plugin_data_dir, open_plugin_dir, plugin_output_stream, plugins_events_stream,
-    plugins_status_stream, start_plugin, stop_plugin, uninstall_plugin,
+    approve_plugin_permissions, clear_plugin_output, export_plugin, get_plugin_output,
+    import_plugin, list_plugins, open_plugin_data_dir, open_plugin_dir, plugin_output_stream,
+    plugin_ready, plugins_events_stream, plugins_status_stream, revoke_plugin_permissions,
+    search_plugin_output, set_plugin_bots, start_plugin, stop_plugin, uninstall_plugin,
 };
 pub use system::{
-    clear_logs, get_app_info, get_app_nums, get_logs, get_system_info, get_ui_state, logs_stream,
-    open_data_dir, open_plugins_dir, restart_program, save_system_config, save_ui_state, set_webui,
+    clear_logs, get_app_info, get_app_nums, get_logs, get_system_info, get_ui_state,
+    load_system_config_from_disk, logs_stream, open_data_dir, open_plugins_dir, restart_program,
+    save_system_config, save_ui_state, set_webui,
 };
 #[derive(Serialize)]
 pub struct ApiResponse<T: Serialize> {
@@ -54,6 +60,12 @@ pub struct AppInfo {
 pub struct SystemConfig {
     #[serde(default)]
     pub auto_start: bool,
+    /// 日志最低级别，保存时未提供则保留原有配置
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_levels: Option<logger::LogLevelConfig>,
+    /// 插件输出日志的保留策略，保存时未提供则保留原有配置
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plugin_output_retention: Option<Retention>,
 }
 
 fn default_ui_last_page() -> String {
@@ -125,6 +137,80 @@ impl<'r> FromRequest<'r> for PluginCaller {
     }
 }
 
+/// 本次启动生成的 WebUI 会话令牌，只通过主窗口地址或无界面模式的标准输出交给使用者，
+/// 浏览器带着令牌打开首页后改用 Cookie 认证
+pub struct WebUiSession {
+    token: String,
+}
+
+impl WebUiSession {
+    pub const COOKIE: &'static str = "yuyu_session";
+
+    pub fn generate() -> Self {
+        let mut bytes = [0u8; 32];
+        rand::rng().fill(&mut bytes);
+        let token = bytes
+            .iter()
+            .fold(String::with_capacity(64), |mut token, b| {
+                let _ = write!(&mut token, "{:02x}", b);
+                token
+            });
+        Self { token }
+    }
+
+    /// 打开后即可登录 WebUI 的地址
+    pub fn login_url(&self, port: u16) -> String {
+        format!("http://127.0.0.1:{}/?session={}", port, self.token)
+    }
+
+    /// 按固定时间比较，避免通过响应时间逐字节猜出令牌
+    pub fn verify(&self, token: &str) -> bool {
+        token.len() == self.token.len()
+            && token
+                .bytes()
+                .zip(self.token.bytes())
+                .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+                == 0
+    }
+
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+}
+
+/// 仅限 WebUI 调用的接口使用，要求请求携带本次启动的 WebUI 会话 Cookie，
+/// 插件没有该 Cookie，无法自行批准权限或修改安全配置
+pub struct WebUiCaller;
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for WebUiCaller {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let session = match req.rocket().state::<Arc<WebUiSession>>() {
+            Some(s) => s,
+            None => return Outcome::Error((Status::InternalServerError, ())),
+        };
+        let authorized = req
+            .cookies()
+            .get(WebUiSession::COOKIE)
+            .is_some_and(|cookie| session.verify(cookie.value()));
+        if authorized {
+            return Outcome::Success(WebUiCaller);
+        }
+
+        if let Outcome::Success(caller) = PluginCaller::from_request(req).await {
+            log_warn!(
+                "插件 {} 尝试调用仅限 WebUI 的接口 {}",
+                caller.plugin_id,
+                req.uri()
+            );
+            return Outcome::Error((Status::Forbidden, ()));
+        }
+        Outcome::Error((Status::Unauthorized, ()))
+    }
+}
+
 #[derive(Deserialize)]
 pub struct SetWebuiRequest {
     pub webui: String,
@@ -137,4 +223,6 @@ pub struct SystemInfoResponse {
     #[serde(rename = "plugins_root")]
     pub plugins_root: String,
     pub auto_start: bool,
+    pub log_levels: logger::LogLevelConfig,
+    pub plugin_output_retention: Retention,
 }
@@ -1,200 +1,192 @@
-use super::connection::connect_bot_sse;
-use super::types::{default_bot_config, parse_url, BotConfig, LegacyBotConfig};
-use super::BotStatusResponse;
+use super::connection::{connect_bot_sse, stop_bot_connection};
+use super::types::{
+    default_bot_config, migrate_legacy_config, validate_bot_id, BotAccountInfo, BotConfig,
+    BotConfigFile, LegacyBotConfig,
+};
 use crate::runtime;
-use crate::server::api::{ApiResponse, SystemInfo};
+use crate::server::api::ApiResponse;
+use crate::server::BotRegistry;
 use rocket::{get, post, serde::json::Json, State};
+use std::collections::HashSet;
+use std::path::Path;
 use std::sync::Arc;
-use tokio::sync::RwLock;
 
-pub fn load_bot_config_from_disk(exe_dir: &std::path::Path) -> BotConfig {
-    let config_file = exe_dir.join("config").join("config.json");
+fn bot_config_path(exe_dir: &Path) -> std::path::PathBuf {
+    exe_dir.join("config").join("config.json")
+}
 
-    let Ok(content) = std::fs::read_to_string(&config_file) else {
-        return default_bot_config();
-    };
+/// 解析 `config.json`，兼容只保存单个账号的旧版配置
+fn parse_bot_configs(content: &str) -> Option<Vec<BotConfig>> {
+    if let Ok(file) = serde_json::from_str::<BotConfigFile>(content) {
+        return Some(file.bots);
+    }
 
-    if let Ok(config) = serde_json::from_str::<BotConfig>(&content) {
-        return config;
+    if let Ok(config) = serde_json::from_str::<BotConfig>(content) {
+        return Some(vec![config]);
     }
 
-    if let Ok(legacy_config) = serde_json::from_str::<LegacyBotConfig>(&content) {
-        if let (Some(host), Some(api_port), Some(event_port)) = (
-            legacy_config.host,
-            legacy_config.api_port,
-            legacy_config.event_port,
-        ) {
-            return BotConfig {
-                host,
-                api_port,
-                event_port,
-                token: legacy_config.token,
-                auto_connect: legacy_config.auto_connect,
-            };
-        }
+    serde_json::from_str::<LegacyBotConfig>(content)
+        .ok()
+        .map(|legacy| vec![migrate_legacy_config(legacy)])
+}
 
-        if let (Some(api), Some(event_sse)) = (legacy_config.api, legacy_config.event_sse) {
-            let (host, api_port) = parse_url(&api).unwrap_or(("localhost".to_string(), 3010));
-            let (_, event_port) = parse_url(&event_sse).unwrap_or(("localhost".to_string(), 3011));
-
-            return BotConfig {
-                host,
-                api_port,
-                event_port,
-                token: legacy_config.token,
-                auto_connect: legacy_config.auto_connect,
-            };
+pub fn load_bot_configs_from_disk(exe_dir: &Path) -> Vec<BotConfig> {
+    let Ok(content) = std::fs::read_to_string(bot_config_path(exe_dir)) else {
+        return vec![default_bot_config()];
+    };
+
+    let Some(mut configs) = parse_bot_configs(&content) else {
+        return vec![default_bot_config()];
+    };
+
+    let mut seen = HashSet::new();
+    configs.retain(|config| {
+        if let Err(e) = validate_bot_id(&config.id) {
+            log_warn!("Ignoring bot account '{}': {}", config.id, e);
+            return false;
         }
+        seen.insert(config.id.clone())
+    });
+    configs
+}
 
-        return BotConfig {
-            token: legacy_config.token,
-            ..default_bot_config()
-        };
+/// 把所有账号的当前配置写回 `config.json`
+pub(super) async fn write_bot_configs(registry: &BotRegistry) -> Result<(), String> {
+    let _guard = registry.config_write_lock.lock().await;
+
+    let file = BotConfigFile {
+        bots: registry.configs().await,
+    };
+    let json_str = serde_json::to_string_pretty(&file)
+        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+
+    let config_file = bot_config_path(&runtime::get_exe_dir());
+    if let Some(config_dir) = config_file.parent() {
+        tokio::fs::create_dir_all(config_dir)
+            .await
+            .map_err(|e| format!("Failed to create config directory: {}", e))?;
     }
 
-    default_bot_config()
+    tokio::fs::write(&config_file, json_str)
+        .await
+        .map_err(|e| format!("Failed to write config file: {}", e))
 }
 
-#[get("/bot/get_config")]
-pub async fn get_bot_config() -> Json<ApiResponse<BotConfig>> {
-    let exe_dir = runtime::get_exe_dir();
-    let config_file = exe_dir.join("config").join("config.json");
-
-    let (retcode, config) = match tokio::fs::read_to_string(&config_file).await {
-        Ok(content) => {
-            if let Ok(config) = serde_json::from_str::<BotConfig>(&content) {
-                (0, config)
-            } else {
-                match serde_json::from_str::<LegacyBotConfig>(&content) {
-                    Ok(legacy_config) => {
-                        let config = if let (Some(host), Some(api_port), Some(event_port)) = (
-                            legacy_config.host,
-                            legacy_config.api_port,
-                            legacy_config.event_port,
-                        ) {
-                            BotConfig {
-                                host,
-                                api_port,
-                                event_port,
-                                token: legacy_config.token,
-                                auto_connect: legacy_config.auto_connect,
-                            }
-                        } else if let (Some(api), Some(event_sse)) =
-                            (legacy_config.api, legacy_config.event_sse)
-                        {
-                            let (host, api_port) =
-                                parse_url(&api).unwrap_or(("localhost".to_string(), 3010));
-                            let (_, event_port) =
-                                parse_url(&event_sse).unwrap_or(("localhost".to_string(), 3011));
-
-                            BotConfig {
-                                host,
-                                api_port,
-                                event_port,
-                                token: legacy_config.token,
-                                auto_connect: legacy_config.auto_connect,
-                            }
-                        } else {
-                            BotConfig {
-                                token: legacy_config.token,
-                                ..default_bot_config()
-                            }
-                        };
-
-                        (0, config)
-                    }
-                    Err(_) => (1, default_bot_config()),
-                }
-            }
-        }
-        Err(_) => (0, default_bot_config()),
-    };
+#[get("/bot/list")]
+pub async fn list_bots(
+    registry: &State<Arc<BotRegistry>>,
+) -> Json<ApiResponse<Vec<BotAccountInfo>>> {
+    let mut result = Vec::new();
+    for bot in registry.list().await {
+        let status = bot.status();
+        result.push(BotAccountInfo {
+            config: bot.config.read().await.clone(),
+            connected: status.connected,
+            connecting: status.connecting,
+            login_info: bot.login_info.read().await.clone(),
+        });
+    }
 
     Json(ApiResponse {
-        retcode,
-        data: config,
+        retcode: 0,
+        data: result,
     })
 }
 
+#[get("/bot/get_config?<id>")]
+pub async fn get_bot_config(
+    id: Option<&str>,
+    registry: &State<Arc<BotRegistry>>,
+) -> Json<ApiResponse<BotConfig>> {
+    match registry.get_or_default(id).await {
+        Some(bot) => Json(ApiResponse {
+            retcode: 0,
+            data: bot.config.read().await.clone(),
+        }),
+        None => Json(ApiResponse {
+            retcode: 1,
+            data: default_bot_config(),
+        }),
+    }
+}
+
 #[post("/bot/save_config", format = "json", data = "<config>")]
 pub async fn save_bot_config(
     config: Json<BotConfig>,
-    _system_info: &State<Arc<SystemInfo>>,
-    bot_state: &State<Arc<crate::server::BotConnectionState>>,
-    bot_config_state: &State<Arc<RwLock<BotConfig>>>,
+    registry: &State<Arc<BotRegistry>>,
 ) -> Json<ApiResponse<String>> {
-    let config_inner = config.into_inner();
-    let json_str = match serde_json::to_string_pretty(&config_inner) {
-        Ok(s) => s,
-        Err(e) => {
-            log_error!("Failed to serialize config: {}", e);
-            return Json(ApiResponse {
-                retcode: 1,
-                data: format!("Failed to serialize config: {}", e),
-            });
-        }
-    };
-
-    let exe_dir = runtime::get_exe_dir();
-    let config_dir = exe_dir.join("config");
-    let config_file = config_dir.join("config.json");
-
-    {
-        let _guard = bot_state.config_write_lock.lock().await;
-
-        if let Err(e) = tokio::fs::create_dir_all(&config_dir).await {
-            log_error!("Failed to create config directory: {}", e);
-            return Json(ApiResponse {
-                retcode: 1,
-                data: format!("Failed to create config directory: {}", e),
-            });
-        }
-
-        if let Err(e) = tokio::fs::write(&config_file, &json_str).await {
-            log_error!("Failed to write config file: {}", e);
-            return Json(ApiResponse {
-                retcode: 1,
-                data: format!("Failed to write config file: {}", e),
-            });
-        }
+    let mut config_inner = config.into_inner();
+    config_inner.id = config_inner.id.trim().to_string();
+    config_inner.name = config_inner.name.trim().to_string();
+
+    if let Err(e) = validate_bot_id(&config_inner.id) {
+        return Json(ApiResponse {
+            retcode: 1,
+            data: e,
+        });
     }
 
-    *bot_config_state.write().await = config_inner.clone();
+    let registry = registry.inner().clone();
+    let bot = registry.upsert(config_inner.clone()).await;
 
-    let bot_state_clone = bot_state.inner().clone();
+    if let Err(e) = write_bot_configs(&registry).await {
+        log_error!("{}", e);
+        return Json(ApiResponse {
+            retcode: 1,
+            data: e,
+        });
+    }
 
-    if let Some(cancel) = bot_state_clone.cancel_sender.lock().await.take() {
+    if let Some(cancel) = bot.cancel_sender.lock().await.take() {
         let _ = cancel.send(());
     }
-    if let Some(handle) = bot_state_clone.connection_task.lock().await.take() {
+    if let Some(handle) = bot.connection_task.lock().await.take() {
         handle.abort();
     }
 
-    bot_state_clone
-        .should_connect
+    bot.should_connect
         .store(true, std::sync::atomic::Ordering::SeqCst);
-    bot_state_clone
-        .is_connecting
+    bot.is_connecting
         .store(true, std::sync::atomic::Ordering::SeqCst);
+    bot.notify_status(false, true);
 
-    let status = BotStatusResponse {
-        connected: false,
-        connecting: true,
-    };
-    let _ = bot_state_clone.status_sender.send(status);
-
-    let bot_state_for_task = bot_state_clone.clone();
     let (cancel_tx, cancel_rx) = tokio::sync::oneshot::channel();
-    *bot_state_clone.cancel_sender.lock().await = Some(cancel_tx);
+    *bot.cancel_sender.lock().await = Some(cancel_tx);
 
-    let config_for_task = config_inner.clone();
+    let bot_for_task = bot.clone();
     let handle = tokio::spawn(async move {
-        connect_bot_sse(config_for_task, bot_state_for_task, cancel_rx).await;
+        connect_bot_sse(config_inner, registry, bot_for_task, cancel_rx).await;
     });
-    *bot_state_clone.connection_task.lock().await = Some(handle);
+    *bot.connection_task.lock().await = Some(handle);
 
     Json(ApiResponse {
         retcode: 0,
         data: "Config saved successfully".to_string(),
     })
 }
+
+#[post("/bot/delete?<id>")]
+pub async fn delete_bot(id: &str, registry: &State<Arc<BotRegistry>>) -> Json<ApiResponse<String>> {
+    let Some(bot) = registry.remove(id).await else {
+        return Json(ApiResponse {
+            retcode: 1,
+            data: "Bot not found".to_string(),
+        });
+    };
+
+    stop_bot_connection(&bot).await;
+
+    if let Err(e) = write_bot_configs(registry).await {
+        log_error!("{}", e);
+        return Json(ApiResponse {
+            retcode: 1,
+            data: e,
+        });
+    }
+
+    log_info!("账号 {} 已删除", id);
+    Json(ApiResponse {
+        retcode: 0,
+        data: "Bot deleted".to_string(),
+    })
+}
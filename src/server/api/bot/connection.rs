@@ -1,8 +1,10 @@
+use super::config::write_bot_configs;
 use super::fetch_bot_login_info_from_config;
 use super::sse::handle_bot_sse_stream;
 use super::types::{BotConfig, BotStatusResponse, LoginInfo};
-use crate::runtime;
 use crate::server::api::ApiResponse;
+use crate::server::upstream::UpstreamEventStream;
+use crate::server::{BotConnectionState, BotRegistry};
 use rocket::{
     get, post,
     response::stream::{Event, EventStream},
@@ -11,86 +13,88 @@ use rocket::{
 };
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::RwLock;
 
-#[post("/bot/disconnect")]
-pub async fn disconnect_bot(
-    bot_state: &State<Arc<crate::server::BotConnectionState>>,
-    bot_config_state: &State<Arc<RwLock<BotConfig>>>,
-) -> Json<ApiResponse<String>> {
-    bot_state
-        .is_connected
+/// 停止账号的连接任务并标记为未连接
+pub(super) async fn stop_bot_connection(bot: &BotConnectionState) {
+    bot.is_connected
         .store(false, std::sync::atomic::Ordering::SeqCst);
-    bot_state
-        .is_connecting
+    bot.is_connecting
         .store(false, std::sync::atomic::Ordering::SeqCst);
-    bot_state
-        .should_connect
+    bot.should_connect
         .store(false, std::sync::atomic::Ordering::SeqCst);
 
-    if let Some(cancel) = bot_state.cancel_sender.lock().await.take() {
+    if let Some(cancel) = bot.cancel_sender.lock().await.take() {
         let _ = cancel.send(());
     }
 
-    if let Some(handle) = bot_state.connection_task.lock().await.take() {
+    if let Some(handle) = bot.connection_task.lock().await.take() {
         let _ = tokio::time::timeout(std::time::Duration::from_secs(2), handle).await;
     }
 
-    update_auto_connect_status(bot_state, false).await;
-
-    {
-        let mut config = bot_config_state.write().await;
-        config.auto_connect = false;
-    }
+    *bot.login_info.write().await = None;
+    bot.notify_status(false, false);
+}
 
-    let status = BotStatusResponse {
-        connected: false,
-        connecting: false,
+#[post("/bot/disconnect?<id>")]
+pub async fn disconnect_bot(
+    id: Option<&str>,
+    registry: &State<Arc<BotRegistry>>,
+) -> Json<ApiResponse<String>> {
+    let Some(bot) = registry.get_or_default(id).await else {
+        return Json(ApiResponse {
+            retcode: 1,
+            data: "Bot not found".to_string(),
+        });
     };
-    let _ = bot_state.status_sender.send(status);
 
-    log_info!("连接断开");
+    stop_bot_connection(&bot).await;
+    update_auto_connect_status(registry, &bot, false).await;
+
+    log_info!("[{}] 连接断开", bot.id);
     Json(ApiResponse {
         retcode: 0,
         data: "Disconnected".to_string(),
     })
 }
 
-#[get("/bot/get_status")]
-pub fn get_bot_status(
-    bot_state: &State<Arc<crate::server::BotConnectionState>>,
-) -> Json<ApiResponse<BotStatusResponse>> {
-    let connected = bot_state
-        .is_connected
-        .load(std::sync::atomic::Ordering::SeqCst);
-    let connecting = bot_state
-        .is_connecting
-        .load(std::sync::atomic::Ordering::SeqCst);
+#[get("/bot/get_status?<id>")]
+pub async fn get_bot_status(
+    id: Option<&str>,
+    registry: &State<Arc<BotRegistry>>,
+) -> Json<ApiResponse<Option<BotStatusResponse>>> {
+    let status = registry.get_or_default(id).await.map(|bot| bot.status());
     Json(ApiResponse {
-        retcode: 0,
-        data: BotStatusResponse {
-            connected,
-            connecting,
-        },
+        retcode: if status.is_some() { 0 } else { 1 },
+        data: status,
     })
 }
 
-#[get("/bot/status_stream")]
-pub fn bot_status_stream(
-    bot_state: &State<Arc<crate::server::BotConnectionState>>,
+/// 推送账号连接状态，指定 `id` 时只推送该账号，否则推送全部账号
+#[get("/bot/status_stream?<id>")]
+pub async fn bot_status_stream(
+    id: Option<String>,
+    registry: &State<Arc<BotRegistry>>,
 ) -> EventStream![Event + 'static] {
-    let bot_state = bot_state.inner().clone();
-    EventStream! {
-        let mut rx = bot_state.status_sender.subscribe();
+    let mut rx = registry.status_sender.subscribe();
+    let initial: Vec<BotStatusResponse> = registry
+        .list()
+        .await
+        .iter()
+        .map(|bot| bot.status())
+        .filter(|status| id.as_deref().is_none_or(|id| status.id == id))
+        .collect();
 
-        let connected = bot_state.is_connected.load(std::sync::atomic::Ordering::SeqCst);
-        let connecting = bot_state.is_connecting.load(std::sync::atomic::Ordering::SeqCst);
-        let status = BotStatusResponse { connected, connecting };
-        if let Ok(json) = serde_json::to_string(&status) {
-            yield Event::data(json);
+    EventStream! {
+        for status in initial {
+            if let Ok(json) = serde_json::to_string(&status) {
+                yield Event::data(json);
+            }
         }
 
         while let Ok(status) = rx.recv().await {
+            if id.as_deref().is_some_and(|id| status.id != id) {
+                continue;
+            }
             if let Ok(json) = serde_json::to_string(&status) {
                 yield Event::data(json);
             }
@@ -98,26 +102,30 @@ pub fn bot_status_stream(
     }
 }
 
-#[post("/get_login_info", format = "json", data = "<_body>")]
+#[post("/get_login_info?<id>", format = "json", data = "<_body>")]
 pub async fn get_login_info(
-    _bot_state: &State<Arc<crate::server::BotConnectionState>>,
+    id: Option<&str>,
+    registry: &State<Arc<BotRegistry>>,
     _body: Json<serde_json::Value>,
 ) -> Json<ApiResponse<LoginInfo>> {
-    let exe_dir = runtime::get_exe_dir();
-    let config_file = exe_dir.join("config").join("config.json");
+    if let Some(bot) = registry.get_or_default(id).await {
+        if let Some(login_info) = bot.login_info.read().await.clone() {
+            return Json(ApiResponse {
+                retcode: 0,
+                data: login_info,
+            });
+        }
 
-    if let Ok(content) = std::fs::read_to_string(&config_file) {
-        if let Ok(config) = serde_json::from_str::<BotConfig>(&content) {
-            match fetch_bot_login_info_from_config(&config).await {
-                Ok(login_info) => {
-                    return Json(ApiResponse {
-                        retcode: 0,
-                        data: login_info,
-                    });
-                }
-                Err(e) => {
-                    log_error!("Failed to get login info: {}", e);
-                }
+        let config = bot.config.read().await.clone();
+        match fetch_bot_login_info_from_config(&config).await {
+            Ok(login_info) => {
+                return Json(ApiResponse {
+                    retcode: 0,
+                    data: login_info,
+                });
+            }
+            Err(e) => {
+                log_error!("[{}] Failed to get login info: {}", bot.id, e);
             }
         }
     }
@@ -131,78 +139,66 @@ pub async fn get_login_info(
     })
 }
 
-pub async fn check_and_auto_connect(bot_state: Arc<crate::server::BotConnectionState>) {
-    let config_result = tokio::task::spawn_blocking(|| {
-        let exe_dir = runtime::get_exe_dir();
-        let config_file = exe_dir.join("config").join("config.json");
-
-        if let Ok(content) = std::fs::read_to_string(&config_file) {
-            if let Ok(config) = serde_json::from_str::<BotConfig>(&content) {
-                if config.auto_connect && !config.host.is_empty() {
-                    return Some(config);
-                }
-            }
+/// 启动所有开启了自动连接的账号
+pub async fn check_and_auto_connect(registry: Arc<BotRegistry>) {
+    for bot in registry.list().await {
+        let config = bot.config.read().await.clone();
+        if !config.auto_connect || config.host.is_empty() {
+            continue;
         }
-        None
-    })
-    .await;
 
-    if let Ok(Some(config)) = config_result {
-        bot_state
-            .should_connect
+        bot.should_connect
             .store(true, std::sync::atomic::Ordering::SeqCst);
-        bot_state
-            .is_connecting
+        bot.is_connecting
             .store(true, std::sync::atomic::Ordering::SeqCst);
+        bot.notify_status(false, true);
 
-        let status = BotStatusResponse {
-            connected: false,
-            connecting: true,
-        };
-        let _ = bot_state.status_sender.send(status);
-
-        if let Some(cancel) = bot_state.cancel_sender.lock().await.take() {
+        if let Some(cancel) = bot.cancel_sender.lock().await.take() {
             let _ = cancel.send(());
         }
-        if let Some(handle) = bot_state.connection_task.lock().await.take() {
+        if let Some(handle) = bot.connection_task.lock().await.take() {
             handle.abort();
         }
 
-        let bot_state_for_task = bot_state.clone();
         let (cancel_tx, cancel_rx) = tokio::sync::oneshot::channel();
-        *bot_state.cancel_sender.lock().await = Some(cancel_tx);
+        *bot.cancel_sender.lock().await = Some(cancel_tx);
 
-        let config_for_task = config.clone();
+        let registry_for_task = registry.clone();
+        let bot_for_task = bot.clone();
         let handle = tokio::spawn(async move {
-            connect_bot_sse(config_for_task, bot_state_for_task, cancel_rx).await;
+            connect_bot_sse(config, registry_for_task, bot_for_task, cancel_rx).await;
         });
-        *bot_state.connection_task.lock().await = Some(handle);
+        *bot.connection_task.lock().await = Some(handle);
     }
 }
 
 pub(super) async fn update_auto_connect_status(
-    bot_state: &Arc<crate::server::BotConnectionState>,
+    registry: &BotRegistry,
+    bot: &BotConnectionState,
     auto_connect: bool,
 ) {
-    let _guard = bot_state.config_write_lock.lock().await;
-
-    let exe_dir = runtime::get_exe_dir();
-    let config_file = exe_dir.join("config").join("config.json");
+    {
+        let mut config = bot.config.write().await;
+        if config.auto_connect == auto_connect {
+            return;
+        }
+        config.auto_connect = auto_connect;
+    }
 
-    if let Ok(content) = tokio::fs::read_to_string(&config_file).await {
-        if let Ok(mut config) = serde_json::from_str::<BotConfig>(&content) {
-            config.auto_connect = auto_connect;
+    // 账号已被删除时不再写回配置
+    if registry.get(&bot.id).await.is_none() {
+        return;
+    }
 
-            if let Ok(json_str) = serde_json::to_string_pretty(&config) {
-                let _ = tokio::fs::write(&config_file, json_str).await;
-            }
-        }
+    if let Err(e) = write_bot_configs(registry).await {
+        log_error!("{}", e);
     }
 }
 
 pub(super) async fn connect_bot_sse(
     config: BotConfig,
-    bot_state: Arc<crate::server::BotConnectionState>,
+    registry: Arc<BotRegistry>,
+    bot_state: Arc<BotConnectionState>,
     mut cancel_rx: tokio::sync::oneshot::Receiver<()>,
 ) {
     loop {
@@ -213,116 +209,72 @@ pub(super) async fn connect_bot_sse(
             break;
         }
 
-        let sse_url = config.get_event_url();
-
-        let client = reqwest::Client::builder()
-            .no_proxy()
-            .build()
-            .expect("Failed to build reqwest client");
-        let mut request_builder = client
-            .get(&sse_url)
-            .header("Accept", "text/event-stream")
-            .header("Cache-Control", "no-cache");
-
-        if let Some(token_str) = config.token.as_deref() {
-            request_builder =
-                request_builder.header("Authorization", format!("Bearer {}", token_str));
-        }
-
-        match request_builder.send().await {
-            Ok(response) => {
-                if response.status().is_success() {
-                    match fetch_bot_login_info_from_config(&config).await {
-                        Ok(_) => {
-                            log_info!("连接成功");
-                            bot_state
-                                .is_connected
-                                .store(true, std::sync::atomic::Ordering::SeqCst);
-                            bot_state
-                                .is_connecting
-                                .store(false, std::sync::atomic::Ordering::SeqCst);
-
-                            update_auto_connect_status(&bot_state, true).await;
-
-                            let status = BotStatusResponse {
-                                connected: true,
-                                connecting: false,
-                            };
-                            let _ = bot_state.status_sender.send(status);
-
-                            let _ =
-                                handle_bot_sse_stream(response, bot_state.clone(), &mut cancel_rx)
-                                    .await;
-
-                            bot_state
-                                .is_connected
-                                .store(false, std::sync::atomic::Ordering::SeqCst);
-                        }
-                        Err(e) => {
-                            bot_state
-                                .is_connected
-                                .store(false, std::sync::atomic::Ordering::SeqCst);
-                            log_error!(
-                                "Event SSE connected but API validation failed, reconnecting... ({})",
-                                e
-                            );
-                        }
-                    }
-                } else {
-                    log_error!("连接断开，重连中... (HTTP {})", response.status());
-                }
+        // 每次重连时重新构建客户端，CA 证书文件更新后无需重启即可生效
+        let connected = match config.build_http_client(None) {
+            Ok(client) => UpstreamEventStream::connect(&client, &config).await,
+            Err(e) => Err(e),
+        };
 
-                if !bot_state
-                    .should_connect
-                    .load(std::sync::atomic::Ordering::SeqCst)
-                {
+        match connected {
+            Ok(stream) => match fetch_bot_login_info_from_config(&config).await {
+                Ok(login_info) => {
+                    log_info!(
+                        "[{}] 连接成功: {}({})",
+                        bot_state.id,
+                        login_info.nickname,
+                        login_info.uin
+                    );
+                    *bot_state.login_info.write().await = Some(login_info);
+                    bot_state
+                        .is_connected
+                        .store(true, std::sync::atomic::Ordering::SeqCst);
                     bot_state
                         .is_connecting
                         .store(false, std::sync::atomic::Ordering::SeqCst);
 
-                    update_auto_connect_status(&bot_state, false).await;
-
-                    let status = BotStatusResponse {
-                        connected: false,
-                        connecting: false,
-                    };
-                    let _ = bot_state.status_sender.send(status);
-                    break;
-                }
-
-                bot_state
-                    .is_connecting
-                    .store(true, std::sync::atomic::Ordering::SeqCst);
+                    update_auto_connect_status(&registry, &bot_state, true).await;
+                    bot_state.notify_status(true, false);
 
-                let status = BotStatusResponse {
-                    connected: false,
-                    connecting: true,
-                };
-                let _ = bot_state.status_sender.send(status);
+                    let _ = handle_bot_sse_stream(stream, bot_state.clone(), &mut cancel_rx).await;
 
-                tokio::time::sleep(Duration::from_secs(5)).await;
-            }
-            Err(e) => {
-                log_error!("连接断开，重连中... (错误: {})", e);
-                if !bot_state
-                    .should_connect
-                    .load(std::sync::atomic::Ordering::SeqCst)
-                {
                     bot_state
-                        .is_connecting
+                        .is_connected
                         .store(false, std::sync::atomic::Ordering::SeqCst);
-
-                    update_auto_connect_status(&bot_state, false).await;
-
-                    let status = BotStatusResponse {
-                        connected: false,
-                        connecting: false,
-                    };
-                    let _ = bot_state.status_sender.send(status);
-                    break;
                 }
-                tokio::time::sleep(Duration::from_secs(5)).await;
+                Err(e) => {
+                    bot_state
+                        .is_connected
+                        .store(false, std::sync::atomic::Ordering::SeqCst);
+                    log_error!(
+                        "[{}] Event stream connected but API validation failed, reconnecting... ({})",
+                        bot_state.id,
+                        e
+                    );
+                }
+            },
+            Err(e) => {
+                log_error!("[{}] 连接断开，重连中... ({})", bot_state.id, e);
             }
         }
+
+        if !bot_state
+            .should_connect
+            .load(std::sync::atomic::Ordering::SeqCst)
+        {
+            bot_state
+                .is_connecting
+                .store(false, std::sync::atomic::Ordering::SeqCst);
+
+            update_auto_connect_status(&registry, &bot_state, false).await;
+            bot_state.notify_status(false, false);
+            break;
+        }
+
+        bot_state
+            .is_connecting
+            .store(true, std::sync::atomic::Ordering::SeqCst);
+        bot_state.notify_status(false, true);
+
+        tokio::time::sleep(Duration::from_secs(5)).await;
     }
 }
@@ -3,22 +3,20 @@ mod connection;
 mod sse;
 mod types;
 
-pub use config::{get_bot_config, load_bot_config_from_disk, save_bot_config};
+pub use config::{
+    delete_bot, get_bot_config, list_bots, load_bot_configs_from_disk, save_bot_config,
+};
 pub use connection::{
     bot_status_stream, check_and_auto_connect, disconnect_bot, get_bot_status, get_login_info,
 };
-pub use types::{BotConfig, BotStatusResponse, LoginInfo};
+pub use types::{BotConfig, BotScheme, BotStatusResponse, BotTlsConfig, EventTransport, LoginInfo};
 
 use std::time::Duration;
 
 async fn fetch_bot_login_info_from_config(config: &BotConfig) -> Result<LoginInfo, String> {
     let api_url = format!("{}/get_login_info", config.get_api_url());
 
-    let client = reqwest::Client::builder()
-        .no_proxy()
-        .timeout(Duration::from_secs(5))
-        .build()
-        .map_err(|e| format!("Failed to build reqwest client: {}", e))?;
+    let client = config.build_http_client(Some(Duration::from_secs(5)))?;
 
     let mut request_builder = client
         .post(&api_url)
@@ -1,7 +1,14 @@
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct BotConfig {
+    /// 账号 ID，插件通过它指定调用哪个账号
+    #[serde(default = "default_bot_id")]
+    pub id: String,
+    /// 在 WebUI 中显示的名称，为空时显示账号 ID
+    #[serde(default)]
+    pub name: String,
     pub host: String,
     #[serde(rename = "apiPort")]
     pub api_port: u16,
@@ -10,6 +17,104 @@ pub struct BotConfig {
     pub token: Option<String>,
     #[serde(default)]
     pub auto_connect: bool,
+    #[serde(rename = "eventTransport", default)]
+    pub event_transport: EventTransport,
+    #[serde(default)]
+    pub scheme: BotScheme,
+    /// 反向代理下的路径前缀（如 `/milky`），为空时直接使用 `/api` 与 `/event`
+    #[serde(rename = "basePath", default)]
+    pub base_path: Option<String>,
+    #[serde(rename = "tls", default)]
+    pub tls: BotTlsConfig,
+    /// 没有插件订阅时是否仍把事件写入补发缓冲区，关闭后只缓冲插件断开不久内的事件
+    #[serde(rename = "keepEventStream", default = "default_keep_event_stream")]
+    pub keep_event_stream: bool,
+}
+
+/// 从 Milky 服务端接收事件的方式
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum EventTransport {
+    #[default]
+    Sse,
+    #[serde(rename = "websocket")]
+    WebSocket,
+}
+
+/// 连接 Milky 服务端使用的协议，`https` 时事件 WebSocket 使用 `wss`
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum BotScheme {
+    #[default]
+    Http,
+    Https,
+}
+
+/// HTTPS/WSS 连接的证书校验设置
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Default)]
+pub struct BotTlsConfig {
+    /// 额外信任的 CA 证书（PEM 文件路径），用于自签名证书
+    #[serde(rename = "caCertPath", default)]
+    pub ca_cert_path: Option<String>,
+    /// 跳过证书校验，仅建议在内网调试时使用
+    #[serde(rename = "skipVerify", default)]
+    pub skip_verify: bool,
+}
+
+impl BotTlsConfig {
+    fn load_ca_cert(&self) -> Result<Option<Vec<u8>>, String> {
+        let Some(path) = self
+            .ca_cert_path
+            .as_deref()
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+        else {
+            return Ok(None);
+        };
+
+        std::fs::read(path)
+            .map(Some)
+            .map_err(|e| format!("Failed to read CA certificate {}: {}", path, e))
+    }
+
+    /// 按当前设置构建 `reqwest` 客户端
+    pub fn build_http_client(&self, timeout: Option<Duration>) -> Result<reqwest::Client, String> {
+        let mut builder = reqwest::Client::builder().no_proxy();
+
+        if let Some(timeout) = timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(pem) = self.load_ca_cert()? {
+            let cert = reqwest::Certificate::from_pem(&pem)
+                .map_err(|e| format!("Invalid CA certificate: {}", e))?;
+            builder = builder.add_root_certificate(cert);
+        }
+        if self.skip_verify {
+            builder = builder.danger_accept_invalid_certs(true);
+        }
+
+        builder
+            .build()
+            .map_err(|e| format!("Failed to build reqwest client: {}", e))
+    }
+
+    /// 按当前设置构建 WebSocket 使用的 TLS 连接器
+    pub fn build_tls_connector(&self) -> Result<native_tls::TlsConnector, String> {
+        let mut builder = native_tls::TlsConnector::builder();
+
+        if let Some(pem) = self.load_ca_cert()? {
+            let cert = native_tls::Certificate::from_pem(&pem)
+                .map_err(|e| format!("Invalid CA certificate: {}", e))?;
+            builder.add_root_certificate(cert);
+        }
+        if self.skip_verify {
+            builder.danger_accept_invalid_certs(true);
+        }
+
+        builder
+            .build()
+            .map_err(|e| format!("Failed to build TLS connector: {}", e))
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -20,8 +125,25 @@ pub struct LoginInfo {
 
 #[derive(Serialize, Clone)]
 pub struct BotStatusResponse {
+    pub id: String,
+    pub connected: bool,
+    pub connecting: bool,
+}
+
+/// `config/config.json` 的内容，保存全部 Bot 账号
+#[derive(Serialize, Deserialize)]
+pub(super) struct BotConfigFile {
+    pub bots: Vec<BotConfig>,
+}
+
+/// WebUI 账号列表中的一项
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BotAccountInfo {
+    pub config: BotConfig,
     pub connected: bool,
     pub connecting: bool,
+    pub login_info: Option<LoginInfo>,
 }
 
 #[derive(Deserialize)]
@@ -40,33 +162,163 @@ pub(super) struct LegacyBotConfig {
 }
 
 impl BotConfig {
+    fn base_url(&self, scheme: &str, port: u16) -> String {
+        let base_path = self
+            .base_path
+            .as_deref()
+            .map(|p| p.trim().trim_matches('/'))
+            .filter(|p| !p.is_empty());
+
+        match base_path {
+            Some(path) => format!("{}://{}:{}/{}", scheme, self.host, port, path),
+            None => format!("{}://{}:{}", scheme, self.host, port),
+        }
+    }
+
+    fn http_scheme(&self) -> &'static str {
+        match self.scheme {
+            BotScheme::Http => "http",
+            BotScheme::Https => "https",
+        }
+    }
+
     pub fn get_api_url(&self) -> String {
-        format!("http://{}:{}/api", self.host, self.api_port)
+        format!("{}/api", self.base_url(self.http_scheme(), self.api_port))
     }
 
     pub fn get_event_url(&self) -> String {
-        format!("http://{}:{}/event", self.host, self.event_port)
+        format!(
+            "{}/event",
+            self.base_url(self.http_scheme(), self.event_port)
+        )
+    }
+
+    pub fn get_event_ws_url(&self) -> String {
+        let scheme = match self.scheme {
+            BotScheme::Http => "ws",
+            BotScheme::Https => "wss",
+        };
+        format!("{}/event", self.base_url(scheme, self.event_port))
+    }
+
+    /// 构建访问该 Milky 服务端的 `reqwest` 客户端（应用 CA 与证书校验设置）
+    pub fn build_http_client(&self, timeout: Option<Duration>) -> Result<reqwest::Client, String> {
+        self.tls.build_http_client(timeout)
+    }
+}
+
+fn default_bot_id() -> String {
+    "default".to_string()
+}
+
+fn default_keep_event_stream() -> bool {
+    true
+}
+
+/// 账号 ID 只允许字母、数字、`-` 和 `_`，最长 32 个字符
+pub(super) fn validate_bot_id(id: &str) -> Result<(), String> {
+    if id.is_empty() || id.len() > 32 {
+        return Err("Bot id must be 1 to 32 characters long".to_string());
+    }
+    if !id
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err("Bot id may only contain letters, digits, '-' and '_'".to_string());
     }
+    Ok(())
 }
 
 pub(super) fn default_bot_config() -> BotConfig {
     BotConfig {
+        id: default_bot_id(),
+        name: String::new(),
         host: "localhost".to_string(),
         api_port: 3010,
         event_port: 3011,
         token: None,
         auto_connect: false,
+        event_transport: EventTransport::Sse,
+        scheme: BotScheme::Http,
+        base_path: None,
+        tls: BotTlsConfig::default(),
+        keep_event_stream: default_keep_event_stream(),
     }
 }
 
-pub(super) fn parse_url(url: &str) -> Option<(String, u16)> {
-    if let Ok(parsed) = url::Url::parse(url) {
-        let host = parsed.host_str()?.to_string();
-        let port = parsed
-            .port()
-            .unwrap_or(if parsed.scheme() == "https" { 443 } else { 80 });
-        Some((host, port))
-    } else {
-        None
+/// 将旧版配置迁移为当前的 `BotConfig`
+///
+/// 最早的版本保存的是完整 URL（`api` / `eventSse`），从中还原协议、主机、端口和路径前缀。
+pub(super) fn migrate_legacy_config(legacy: LegacyBotConfig) -> BotConfig {
+    if let (Some(host), Some(api_port), Some(event_port)) =
+        (legacy.host, legacy.api_port, legacy.event_port)
+    {
+        return BotConfig {
+            host,
+            api_port,
+            event_port,
+            token: legacy.token,
+            auto_connect: legacy.auto_connect,
+            ..default_bot_config()
+        };
+    }
+
+    if let (Some(api), Some(event_sse)) = (legacy.api, legacy.event_sse) {
+        let api_url = parse_url(&api, "/api");
+        let event_url = parse_url(&event_sse, "/event");
+
+        return BotConfig {
+            host: api_url
+                .as_ref()
+                .map(|u| u.host.clone())
+                .unwrap_or_else(|| "localhost".to_string()),
+            api_port: api_url.as_ref().map(|u| u.port).unwrap_or(3010),
+            event_port: event_url.as_ref().map(|u| u.port).unwrap_or(3011),
+            token: legacy.token,
+            auto_connect: legacy.auto_connect,
+            scheme: api_url.as_ref().map(|u| u.scheme).unwrap_or_default(),
+            base_path: api_url.and_then(|u| u.base_path),
+            ..default_bot_config()
+        };
+    }
+
+    BotConfig {
+        token: legacy.token,
+        ..default_bot_config()
     }
 }
+
+struct ParsedUrl {
+    scheme: BotScheme,
+    host: String,
+    port: u16,
+    base_path: Option<String>,
+}
+
+/// 解析旧版配置中的完整 URL，`endpoint` 为 URL 末尾的 `/api` 或 `/event`
+fn parse_url(url: &str, endpoint: &str) -> Option<ParsedUrl> {
+    let parsed = url::Url::parse(url).ok()?;
+    let scheme = if parsed.scheme() == "https" {
+        BotScheme::Https
+    } else {
+        BotScheme::Http
+    };
+    let host = parsed.host_str()?.to_string();
+    let port = parsed.port().unwrap_or(match scheme {
+        BotScheme::Https => 443,
+        BotScheme::Http => 80,
+    });
+
+    let path = parsed.path().trim_end_matches('/');
+    let path = path.strip_suffix(endpoint).unwrap_or(path);
+    let base_path = Some(path.trim_matches('/'))
+        .filter(|p| !p.is_empty())
+        .map(|p| format!("/{}", p));
+
+    Some(ParsedUrl {
+        scheme,
+        host,
+        port,
+        base_path,
+    })
+}
@@ -6,7 +6,8 @@ use crate::server::api::ApiResponse;
 
 pub use package::{export_plugin, import_plugin};
 pub use routes::{
-    clear_plugin_output, get_plugin_output, list_plugins, open_plugin_data_dir, open_plugin_dir,
-    start_plugin, stop_plugin, uninstall_plugin,
+    approve_plugin_permissions, clear_plugin_output, get_plugin_output, list_plugins,
+    open_plugin_data_dir, open_plugin_dir, plugin_ready, revoke_plugin_permissions,
+    search_plugin_output, set_plugin_bots, start_plugin, stop_plugin, uninstall_plugin,
 };
 pub use stream::{plugin_output_stream, plugins_events_stream, plugins_status_stream};
@@ -2,8 +2,11 @@ use super::ApiResponse;
 use crate::plus::PluginManager;
 use rocket::{post, serde::json::Json, State};
 use serde::Deserialize;
+use std::path::PathBuf;
 use std::sync::Arc;
 
+const HEADLESS_DIALOG_ERROR: &str = "File dialogs are not available in headless mode";
+
 #[derive(Deserialize)]
 pub struct ExportPluginRequest {
     pub plugin_id: String,
@@ -29,11 +32,7 @@ pub async fn export_plugin(
     let plugin_id_clone = plugin_id.clone();
 
     let result = tokio::task::spawn_blocking(move || {
-        let target_path = rfd::FileDialog::new()
-            .set_file_name(format!("{}.yuyu.7z", plugin_id_clone))
-            .add_filter("Yuyu Plugin", &["yuyu.7z"])
-            .add_filter("7z Archive", &["7z"])
-            .save_file();
+        let target_path = pick_export_target(&plugin_id_clone)?;
 
         let target_path = match target_path {
             Some(p) => p,
@@ -70,9 +69,7 @@ pub async fn import_plugin(
     let plugins_root = plugin_manager.get_plugins_root();
 
     let result = tokio::task::spawn_blocking(move || {
-        let target_path = rfd::FileDialog::new()
-            .add_filter("Yuyu Plugin", &["yuyu.7z"])
-            .pick_file();
+        let target_path = pick_import_source()?;
 
         let target_path = match target_path {
             Some(p) => p,
@@ -158,3 +155,37 @@ pub async fn import_plugin(
         }),
     }
 }
+
+#[cfg(windows)]
+fn pick_export_target(plugin_id: &str) -> Result<Option<PathBuf>, String> {
+    if crate::runtime::is_headless_mode() {
+        return Err(HEADLESS_DIALOG_ERROR.to_string());
+    }
+
+    Ok(rfd::FileDialog::new()
+        .set_file_name(format!("{}.yuyu.7z", plugin_id))
+        .add_filter("Yuyu Plugin", &["yuyu.7z"])
+        .add_filter("7z Archive", &["7z"])
+        .save_file())
+}
+
+#[cfg(not(windows))]
+fn pick_export_target(_plugin_id: &str) -> Result<Option<PathBuf>, String> {
+    Err(HEADLESS_DIALOG_ERROR.to_string())
+}
+
+#[cfg(windows)]
+fn pick_import_source() -> Result<Option<PathBuf>, String> {
+    if crate::runtime::is_headless_mode() {
+        return Err(HEADLESS_DIALOG_ERROR.to_string());
+    }
+
+    Ok(rfd::FileDialog::new()
+        .add_filter("Yuyu Plugin", &["yuyu.7z"])
+        .pick_file())
+}
+
+#[cfg(not(windows))]
+fn pick_import_source() -> Result<Option<PathBuf>, String> {
+    Err(HEADLESS_DIALOG_ERROR.to_string())
+}
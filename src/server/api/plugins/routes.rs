@@ -1,5 +1,7 @@
 use super::ApiResponse;
+use crate::plus::output_log::PluginOutputQuery;
 use crate::plus::PluginManager;
+use crate::server::api::{PluginCaller, WebUiCaller};
 use rocket::{get, post, serde::json::Json, State};
 use std::sync::Arc;
 
@@ -86,7 +88,7 @@ pub async fn get_plugin_output(
     match manager.get_plugin_output(&plugin_id).await {
         Ok(output) => Json(ApiResponse {
             retcode: 0,
-            data: output,
+            data: output.into_iter().map(|output| output.line).collect(),
         }),
         Err(e) => {
             log_error!("Failed to get plugin output: {}", e);
@@ -98,6 +100,29 @@ pub async fn get_plugin_output(
     }
 }
 
+/// 查询插件输出历史（含之前运行的输出），按时间从新到旧分页返回，失败时 `data` 为错误信息
+#[get("/plugins/<plugin_id>/output/search?<query..>")]
+pub async fn search_plugin_output(
+    plugin_id: String,
+    query: PluginOutputQuery,
+    manager: &State<Arc<PluginManager>>,
+) -> Json<ApiResponse<serde_json::Value>> {
+    let result = manager
+        .query_plugin_output(&plugin_id, &query)
+        .await
+        .and_then(|output| serde_json::to_value(output).map_err(|e| e.to_string()));
+    match result {
+        Ok(data) => Json(ApiResponse { retcode: 0, data }),
+        Err(e) => {
+            log_error!("Failed to search plugin output: {}", e);
+            Json(ApiResponse {
+                retcode: 1,
+                data: serde_json::Value::String(e),
+            })
+        }
+    }
+}
+
 #[post("/plugins/<plugin_id>/output/clear")]
 pub async fn clear_plugin_output(
     plugin_id: String,
@@ -174,3 +199,88 @@ pub async fn uninstall_plugin(
         }
     }
 }
+
+#[post("/plugins/<plugin_id>/permissions/approve")]
+pub async fn approve_plugin_permissions(
+    plugin_id: String,
+    _webui: WebUiCaller,
+    manager: &State<Arc<PluginManager>>,
+) -> Json<ApiResponse<String>> {
+    match manager.approve_plugin_permissions(&plugin_id).await {
+        Ok(_) => {
+            log_info!("Plugin {} permissions approved", plugin_id);
+            Json(ApiResponse {
+                retcode: 0,
+                data: "Permissions approved".to_string(),
+            })
+        }
+        Err(e) => Json(ApiResponse {
+            retcode: 1,
+            data: e,
+        }),
+    }
+}
+
+#[post("/plugins/<plugin_id>/permissions/revoke")]
+pub async fn revoke_plugin_permissions(
+    plugin_id: String,
+    _webui: WebUiCaller,
+    manager: &State<Arc<PluginManager>>,
+) -> Json<ApiResponse<String>> {
+    match manager.revoke_plugin_permissions(&plugin_id).await {
+        Ok(_) => {
+            log_info!("Plugin {} permissions revoked", plugin_id);
+            Json(ApiResponse {
+                retcode: 0,
+                data: "Permissions revoked".to_string(),
+            })
+        }
+        Err(e) => Json(ApiResponse {
+            retcode: 1,
+            data: e,
+        }),
+    }
+}
+
+#[post("/plugins/<plugin_id>/bots", format = "json", data = "<bots>")]
+pub async fn set_plugin_bots(
+    plugin_id: String,
+    _webui: WebUiCaller,
+    bots: Json<Vec<String>>,
+    manager: &State<Arc<PluginManager>>,
+) -> Json<ApiResponse<String>> {
+    match manager
+        .set_plugin_bot_bindings(&plugin_id, bots.into_inner())
+        .await
+    {
+        Ok(_) => {
+            log_info!("Plugin {} bot bindings updated", plugin_id);
+            Json(ApiResponse {
+                retcode: 0,
+                data: "Bot bindings updated".to_string(),
+            })
+        }
+        Err(e) => Json(ApiResponse {
+            retcode: 1,
+            data: e,
+        }),
+    }
+}
+
+/// 插件在 `app.json` 中声明 `"ready": { "type": "api" }` 时，初始化完成后调用该接口
+#[post("/plugin/ready")]
+pub async fn plugin_ready(
+    caller: PluginCaller,
+    manager: &State<Arc<PluginManager>>,
+) -> Json<ApiResponse<String>> {
+    match manager.mark_plugin_ready(&caller.plugin_id).await {
+        Ok(_) => Json(ApiResponse {
+            retcode: 0,
+            data: "ok".to_string(),
+        }),
+        Err(e) => Json(ApiResponse {
+            retcode: 1,
+            data: e,
+        }),
+    }
+}
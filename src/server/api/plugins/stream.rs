@@ -1,3 +1,4 @@
+use crate::plus::output_line::PluginLogLevel;
 use crate::plus::PluginManager;
 use rocket::{
     get,
@@ -15,8 +16,12 @@ pub enum PluginUnifiedEvent {
     Status(crate::plus::manager::PluginStatusEvent),
 }
 
-#[get("/plugins/events_stream")]
-pub fn plugins_events_stream(manager: &State<Arc<PluginManager>>) -> EventStream![Event + 'static] {
+/// `level` 为插件输出的最低级别，只过滤输出事件，状态事件始终推送
+#[get("/plugins/events_stream?<level>")]
+pub fn plugins_events_stream(
+    level: Option<PluginLogLevel>,
+    manager: &State<Arc<PluginManager>>,
+) -> EventStream![Event + 'static] {
     let manager = manager.inner().clone();
     EventStream! {
         let mut rx_output = manager.subscribe_output();
@@ -25,6 +30,7 @@ pub fn plugins_events_stream(manager: &State<Arc<PluginManager>>) -> EventStream
         loop {
             let event = tokio::select! {
                 res = rx_output.recv() => match res {
+                    Ok(e) if !e.output.is_at_least(level) => continue,
                     Ok(e) => Some(PluginUnifiedEvent::Output(e)),
                     Err(tokio::sync::broadcast::error::RecvError::Closed) => return,
                     Err(_) => None,
@@ -68,17 +74,20 @@ pub fn plugins_status_stream(manager: &State<Arc<PluginManager>>) -> EventStream
     }
 }
 
-#[get("/plugins/<plugin_id>/output/stream")]
+/// 每条事件为一行完整的结构化输出（与 `events_stream` 中输出事件的内容相同，含颜色文本段），
+/// `level` 为最低输出级别，如 `warn` 只推送 warn 和 error
+#[get("/plugins/<plugin_id>/output/stream?<level>")]
 pub fn plugin_output_stream(
     plugin_id: String,
+    level: Option<PluginLogLevel>,
     manager: &State<Arc<PluginManager>>,
 ) -> EventStream![Event + 'static] {
     let manager = manager.inner().clone();
     let target_plugin = plugin_id.clone();
     EventStream! {
         if let Ok(output) = manager.get_plugin_output(&plugin_id).await {
-            for line in &output {
-                if let Ok(json) = serde_json::to_string(&line) {
+            for line in output.iter().filter(|line| line.is_at_least(level)) {
+                if let Ok(json) = serde_json::to_string(line) {
                     yield Event::data(json);
                 }
             }
@@ -89,8 +98,8 @@ pub fn plugin_output_stream(
         loop {
             match rx.recv().await {
                 Ok(event) => {
-                    if event.plugin_id == target_plugin {
-                        if let Ok(json) = serde_json::to_string(&event.line) {
+                    if event.plugin_id == target_plugin && event.output.is_at_least(level) {
+                        if let Ok(json) = serde_json::to_string(&event.output) {
                             yield Event::data(json);
                         }
                     }
@@ -4,9 +4,9 @@ use super::{
 };
 use crate::logger;
 use crate::plus::PluginManager;
+use crate::rotating_log::Retention;
 use crate::runtime;
 use crate::server::MainProxy;
-use crate::window::UserEvent;
 use rocket::{
     get, post,
     response::stream::{Event, EventStream},
@@ -41,7 +41,7 @@ fn system_config_path(exe_dir: &Path) -> PathBuf {
     exe_dir.join("config").join("system.json")
 }
 
-fn load_system_config_from_disk(exe_dir: &Path) -> SystemConfig {
+pub fn load_system_config_from_disk(exe_dir: &Path) -> SystemConfig {
     let config_file = system_config_path(exe_dir);
 
     let Ok(content) = std::fs::read_to_string(&config_file) else {
@@ -55,7 +55,10 @@ fn resolve_system_config(exe_dir: &Path) -> SystemConfig {
     let fallback = load_system_config_from_disk(exe_dir);
 
     match runtime::is_auto_start_enabled() {
-        Ok(auto_start) => SystemConfig { auto_start },
+        Ok(auto_start) => SystemConfig {
+            auto_start,
+            ..fallback
+        },
         Err(err) => {
             log_warn!("Failed to read auto-start state from registry: {}", err);
             fallback
@@ -125,13 +128,34 @@ pub fn get_app_nums() -> Json<ApiResponse<i32>> {
     })
 }
 
-#[get("/logs")]
-pub fn get_logs() -> Json<ApiResponse<LogsResponse>> {
-    let logs = logger::get_logs();
-    Json(ApiResponse {
-        retcode: 0,
-        data: LogsResponse { logs },
-    })
+/// 不带参数时返回内存中最近的日志；带任意筛选或分页参数时查询日志文件中的历史，两种情况都按时间从旧到新排列
+#[get("/logs?<query..>")]
+pub async fn get_logs(query: logger::LogQuery) -> Json<ApiResponse<LogsResponse>> {
+    if query.is_empty() {
+        return Json(ApiResponse {
+            retcode: 0,
+            data: LogsResponse {
+                logs: logger::get_logs(),
+            },
+        });
+    }
+
+    let result = tokio::task::spawn_blocking(move || logger::query_log_history(&query))
+        .await
+        .unwrap_or_else(|e| Err(e.to_string()));
+    match result {
+        Ok(logs) => Json(ApiResponse {
+            retcode: 0,
+            data: LogsResponse { logs },
+        }),
+        Err(e) => {
+            log_error!("Failed to query log history: {}", e);
+            Json(ApiResponse {
+                retcode: 1,
+                data: LogsResponse { logs: Vec::new() },
+            })
+        }
+    }
 }
 
 #[post("/logs/clear")]
@@ -167,6 +191,8 @@ pub fn get_system_info(
         data_dir: system_info.data_dir.clone(),
         plugins_root: system_info.plugins_root.clone(),
         auto_start: system_config.auto_start,
+        log_levels: system_config.log_levels.unwrap_or_default(),
+        plugin_output_retention: system_config.plugin_output_retention.unwrap_or_default(),
     };
     Json(ApiResponse {
         retcode: 0,
@@ -175,8 +201,21 @@ pub fn get_system_info(
 }
 
 #[post("/system/save_config", format = "json", data = "<config>")]
-pub async fn save_system_config(config: Json<SystemConfig>) -> Json<ApiResponse<String>> {
-    let config_inner = config.into_inner();
+pub async fn save_system_config(
+    config: Json<SystemConfig>,
+    manager: &State<Arc<PluginManager>>,
+) -> Json<ApiResponse<String>> {
+    let mut config_inner = config.into_inner();
+    let exe_dir = runtime::get_exe_dir();
+    let saved = load_system_config_from_disk(&exe_dir);
+    config_inner.log_levels = config_inner
+        .log_levels
+        .or(saved.log_levels)
+        .map(logger::LogLevelConfig::normalized);
+    config_inner.plugin_output_retention = config_inner
+        .plugin_output_retention
+        .or(saved.plugin_output_retention)
+        .map(Retention::normalized);
 
     if let Err(err) = runtime::set_auto_start_enabled(config_inner.auto_start) {
         log_error!("Failed to update auto-start setting: {}", err);
@@ -186,7 +225,6 @@ pub async fn save_system_config(config: Json<SystemConfig>) -> Json<ApiResponse<
         });
     }
 
-    let exe_dir = runtime::get_exe_dir();
     let config_file = system_config_path(&exe_dir);
 
     if let Some(config_dir) = config_file.parent() {
@@ -218,6 +256,9 @@ pub async fn save_system_config(config: Json<SystemConfig>) -> Json<ApiResponse<
         });
     }
 
+    logger::set_log_levels(config_inner.log_levels.unwrap_or_default());
+    manager.set_output_retention(config_inner.plugin_output_retention.unwrap_or_default());
+
     Json(ApiResponse {
         retcode: 0,
         data: "System config saved".to_string(),
@@ -253,9 +294,11 @@ pub async fn open_plugins_dir(manager: &State<Arc<PluginManager>>) -> Json<ApiRe
 
 #[post("/restart_program")]
 pub async fn restart_program(main_proxy: &State<Arc<MainProxy>>) -> Json<ApiResponse<String>> {
-    let proxy_lock = main_proxy.proxy.read().await;
-    if let Some(proxy) = &*proxy_lock {
-        let _ = proxy.send_event(UserEvent::RestartRequested);
+    if !main_proxy.request_restart().await {
+        return Json(ApiResponse {
+            retcode: 1,
+            data: "Restart is not available in headless mode".to_string(),
+        });
     }
 
     Json(ApiResponse {
@@ -0,0 +1,578 @@
+use crate::plus::manager::is_read_only_api;
+use crate::runtime;
+use crate::server::api::{ApiResponse, WebUiCaller};
+use futures_util::future::{BoxFuture, FutureExt, Shared};
+use rocket::http::{ContentType, Status};
+use rocket::{get, post, serde::json::Json, State};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, RwLock};
+
+/// 缓存条目数量上限，超过时先清理过期条目，仍然超出则不再写入
+const MAX_CACHE_ENTRIES: usize = 4096;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiCacheConfig {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// 允许缓存的只读 API 及其缓存时间（秒）
+    #[serde(default)]
+    pub apis: HashMap<String, u64>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl Default for ApiCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            apis: HashMap::from([
+                ("get_login_info".to_string(), 300),
+                ("get_friend_list".to_string(), 60),
+                ("get_group_list".to_string(), 60),
+                ("get_group_info".to_string(), 60),
+                ("get_group_member_list".to_string(), 30),
+                ("get_group_member_info".to_string(), 30),
+            ]),
+        }
+    }
+}
+
+impl ApiCacheConfig {
+    /// 只保留只读接口（`get_*`），丢弃缓存时间为 0 的条目
+    pub fn normalized(mut self) -> Self {
+        self.apis
+            .retain(|api, ttl| is_read_only_api(api.trim()) && *ttl > 0);
+        self
+    }
+
+    fn ttl(&self, api: &str) -> Option<Duration> {
+        if !self.enabled {
+            return None;
+        }
+        self.apis.get(api).map(|ttl| Duration::from_secs(*ttl))
+    }
+}
+
+/// 把请求体规范化为缓存键：JSON 按字段名排序后重新序列化，其他内容原样使用
+pub fn normalize_body(body: &[u8]) -> String {
+    match serde_json::from_slice::<serde_json::Value>(body) {
+        Ok(value) => canonical_json(&value),
+        Err(_) => String::from_utf8_lossy(body).into_owned(),
+    }
+}
+
+fn canonical_json(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Object(map) => {
+            let sorted: BTreeMap<&String, String> = map
+                .iter()
+                .map(|(key, value)| (key, canonical_json(value)))
+                .collect();
+            let fields: Vec<String> = sorted
+                .into_iter()
+                .map(|(key, value)| format!("{}:{}", serde_json::Value::String(key.clone()), value))
+                .collect();
+            format!("{{{}}}", fields.join(","))
+        }
+        serde_json::Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(canonical_json).collect();
+            format!("[{}]", items.join(","))
+        }
+        other => other.to_string(),
+    }
+}
+
+/// 某个账号上的一次 API 调用
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ApiCallKey {
+    pub bot_id: String,
+    pub api: String,
+    pub body: String,
+}
+
+impl ApiCallKey {
+    pub fn new(bot_id: &str, api: &str, body: &[u8]) -> Self {
+        Self {
+            bot_id: bot_id.to_string(),
+            api: api.to_string(),
+            body: normalize_body(body),
+        }
+    }
+
+    fn group_id(&self) -> Option<u64> {
+        let payload: serde_json::Value = serde_json::from_str(&self.body).ok()?;
+        let group_id = payload.get("group_id")?;
+        group_id
+            .as_u64()
+            .or_else(|| group_id.as_str().and_then(|s| s.parse().ok()))
+    }
+}
+
+/// 上游返回的响应
+#[derive(Clone)]
+pub struct CachedResponse {
+    pub status: Status,
+    pub content_type: ContentType,
+    pub body: Vec<u8>,
+}
+
+impl CachedResponse {
+    /// 只缓存 HTTP 成功且 `retcode` 为 0 的响应
+    fn is_cacheable(&self) -> bool {
+        if !self.status.class().is_success() {
+            return false;
+        }
+
+        serde_json::from_slice::<serde_json::Value>(&self.body)
+            .ok()
+            .and_then(|payload| payload.get("retcode").and_then(serde_json::Value::as_i64))
+            == Some(0)
+    }
+}
+
+struct CacheEntry {
+    response: CachedResponse,
+    group_id: Option<u64>,
+    expires_at: Instant,
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiCacheCounter {
+    pub api: String,
+    pub hits: u64,
+    pub misses: u64,
+    /// 与正在进行的相同请求合并、没有单独访问上游的次数
+    pub coalesced: u64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiCacheStats {
+    pub enabled: bool,
+    pub entries: usize,
+    pub hits: u64,
+    pub misses: u64,
+    pub coalesced: u64,
+    pub apis: Vec<ApiCacheCounter>,
+}
+
+#[derive(Default)]
+struct CacheState {
+    entries: HashMap<ApiCallKey, CacheEntry>,
+    counters: HashMap<String, ApiCacheCounter>,
+    /// 每个账号的缓存失效次数，上游调用期间发生失效时不写入调用结果
+    generations: HashMap<String, u64>,
+    /// 清空全部缓存的次数，计入每个账号的失效代数
+    clears: u64,
+}
+
+impl CacheState {
+    fn generation(&self, bot_id: &str) -> u64 {
+        self.clears + self.generations.get(bot_id).copied().unwrap_or(0)
+    }
+
+    fn counter(&mut self, api: &str) -> &mut ApiCacheCounter {
+        self.counters
+            .entry(api.to_string())
+            .or_insert_with(|| ApiCacheCounter {
+                api: api.to_string(),
+                ..ApiCacheCounter::default()
+            })
+    }
+
+    /// 删除匹配的缓存，并让该账号正在进行的上游调用不再写入缓存
+    fn remove_where(&mut self, bot_id: &str, apis: &[&str], group_id: Option<u64>) {
+        *self.generations.entry(bot_id.to_string()).or_default() += 1;
+        self.entries.retain(|key, entry| {
+            key.bot_id != bot_id
+                || !apis.contains(&key.api.as_str())
+                || group_id.is_some_and(|group_id| entry.group_id != Some(group_id))
+        });
+    }
+}
+
+type InFlightCall = Shared<BoxFuture<'static, Result<CachedResponse, Status>>>;
+
+/// 只读 Milky API 的响应缓存，按账号、API 与规范化后的请求体区分
+#[derive(Default)]
+pub struct ApiCache {
+    state: Mutex<CacheState>,
+    /// 正在访问上游的只读调用，相同的并发调用共享同一个结果
+    in_flight: std::sync::Mutex<HashMap<ApiCallKey, InFlightCall>>,
+}
+
+impl ApiCache {
+    /// 执行一次只读调用并写入缓存，已有相同调用正在进行时等待并共享它的结果
+    ///
+    /// 上游请求在独立任务中执行，发起请求的插件断开也不会影响其他等待者。
+    /// 调用期间该账号的缓存被事件清除过时，结果可能已经过期，不写入缓存。
+    pub async fn coalesce<F>(
+        self: &Arc<Self>,
+        config: ApiCacheConfig,
+        key: ApiCallKey,
+        call: F,
+    ) -> Result<CachedResponse, Status>
+    where
+        F: Future<Output = Result<CachedResponse, Status>> + Send + 'static,
+    {
+        let (shared, joined) = {
+            let mut in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+            match in_flight.get(&key) {
+                Some(shared) => (shared.clone(), true),
+                None => {
+                    let cache = self.clone();
+                    let task_key = key.clone();
+                    let handle = tokio::spawn(async move {
+                        let generation = cache.state.lock().await.generation(&task_key.bot_id);
+                        let result = call.await;
+                        if let Ok(response) = &result {
+                            cache
+                                .put(&config, task_key.clone(), response, generation)
+                                .await;
+                        }
+                        cache
+                            .in_flight
+                            .lock()
+                            .unwrap_or_else(|e| e.into_inner())
+                            .remove(&task_key);
+                        result
+                    });
+                    let shared = async move { handle.await.unwrap_or(Err(Status::BadGateway)) }
+                        .boxed()
+                        .shared();
+                    in_flight.insert(key.clone(), shared.clone());
+                    (shared, false)
+                }
+            }
+        };
+
+        if joined {
+            self.state.lock().await.counter(&key.api).coalesced += 1;
+        }
+        shared.await
+    }
+
+    /// 查询缓存并记录命中情况，API 不在缓存列表中时返回 `None` 且不计数
+    pub async fn get(&self, config: &ApiCacheConfig, key: &ApiCallKey) -> Option<CachedResponse> {
+        config.ttl(&key.api)?;
+
+        let now = Instant::now();
+        let mut state = self.state.lock().await;
+        let cached = match state.entries.get(key) {
+            Some(entry) if entry.expires_at > now => Some(entry.response.clone()),
+            Some(_) => {
+                state.entries.remove(key);
+                None
+            }
+            None => None,
+        };
+
+        let counter = state.counter(&key.api);
+        if cached.is_some() {
+            counter.hits += 1;
+        } else {
+            counter.misses += 1;
+        }
+        cached
+    }
+
+    /// 写入缓存，`generation` 为发起上游调用前该账号的失效代数，之后发生过失效时放弃写入
+    async fn put(
+        &self,
+        config: &ApiCacheConfig,
+        key: ApiCallKey,
+        response: &CachedResponse,
+        generation: u64,
+    ) {
+        let Some(ttl) = config.ttl(&key.api) else {
+            return;
+        };
+        if !response.is_cacheable() {
+            return;
+        }
+
+        let now = Instant::now();
+        let mut state = self.state.lock().await;
+        if state.generation(&key.bot_id) != generation {
+            return;
+        }
+        if state.entries.len() >= MAX_CACHE_ENTRIES {
+            state.entries.retain(|_, entry| entry.expires_at > now);
+            if state.entries.len() >= MAX_CACHE_ENTRIES {
+                return;
+            }
+        }
+
+        let group_id = key.group_id();
+        state.entries.insert(
+            key,
+            CacheEntry {
+                response: response.clone(),
+                group_id,
+                expires_at: now + ttl,
+            },
+        );
+    }
+
+    /// 根据上游事件清除可能已经过期的缓存
+    pub async fn invalidate_for_event(
+        &self,
+        bot_id: &str,
+        event_type: Option<&str>,
+        group_id: Option<u64>,
+    ) {
+        let Some(event_type) = event_type else {
+            return;
+        };
+
+        let mut state = self.state.lock().await;
+        match event_type {
+            "group_member_increase" | "group_member_decrease" => {
+                state.remove_where(
+                    bot_id,
+                    &[
+                        "get_group_info",
+                        "get_group_member_list",
+                        "get_group_member_info",
+                    ],
+                    group_id,
+                );
+                // 群列表中包含成员数量，Bot 自己进群/退群时群列表也会变化
+                state.remove_where(bot_id, &["get_group_list"], None);
+            }
+            "group_name_change" => {
+                state.remove_where(bot_id, &["get_group_info"], group_id);
+                state.remove_where(bot_id, &["get_group_list"], None);
+            }
+            "group_admin_change" | "group_member_card_change" => {
+                state.remove_where(
+                    bot_id,
+                    &["get_group_member_list", "get_group_member_info"],
+                    group_id,
+                );
+            }
+            _ => {}
+        }
+    }
+
+    pub async fn clear(&self) {
+        let mut state = self.state.lock().await;
+        state.entries.clear();
+        state.counters.clear();
+        state.clears += 1;
+    }
+
+    pub async fn stats(&self, config: &ApiCacheConfig) -> ApiCacheStats {
+        let now = Instant::now();
+        let mut state = self.state.lock().await;
+        state.entries.retain(|_, entry| entry.expires_at > now);
+
+        let mut apis: Vec<ApiCacheCounter> = state.counters.values().cloned().collect();
+        apis.sort_by(|a, b| a.api.cmp(&b.api));
+
+        ApiCacheStats {
+            enabled: config.enabled,
+            entries: state.entries.len(),
+            hits: apis.iter().map(|c| c.hits).sum(),
+            misses: apis.iter().map(|c| c.misses).sum(),
+            coalesced: apis.iter().map(|c| c.coalesced).sum(),
+            apis,
+        }
+    }
+}
+
+fn api_cache_config_path(exe_dir: &Path) -> std::path::PathBuf {
+    exe_dir.join("config").join("api_cache.json")
+}
+
+pub fn load_api_cache_config_from_disk(exe_dir: &Path) -> ApiCacheConfig {
+    let config_file = api_cache_config_path(exe_dir);
+
+    let Ok(content) = std::fs::read_to_string(&config_file) else {
+        return ApiCacheConfig::default();
+    };
+
+    serde_json::from_str::<ApiCacheConfig>(&content)
+        .unwrap_or_default()
+        .normalized()
+}
+
+#[get("/api_cache/get_config")]
+pub async fn get_api_cache_config(
+    api_cache_config_state: &State<Arc<RwLock<ApiCacheConfig>>>,
+) -> Json<ApiResponse<ApiCacheConfig>> {
+    Json(ApiResponse {
+        retcode: 0,
+        data: api_cache_config_state.read().await.clone(),
+    })
+}
+
+#[post("/api_cache/save_config", format = "json", data = "<config>")]
+pub async fn save_api_cache_config(
+    _webui: WebUiCaller,
+    config: Json<ApiCacheConfig>,
+    api_cache_config_state: &State<Arc<RwLock<ApiCacheConfig>>>,
+    api_cache: &State<Arc<ApiCache>>,
+) -> Json<ApiResponse<String>> {
+    let config_inner = config.into_inner().normalized();
+    let json_str = match serde_json::to_string_pretty(&config_inner) {
+        Ok(value) => value,
+        Err(e) => {
+            log_error!("Failed to serialize API cache config: {}", e);
+            return Json(ApiResponse {
+                retcode: 1,
+                data: format!("Failed to serialize API cache config: {}", e),
+            });
+        }
+    };
+
+    let exe_dir = runtime::get_exe_dir();
+    let config_file = api_cache_config_path(&exe_dir);
+
+    if let Some(config_dir) = config_file.parent() {
+        if let Err(e) = tokio::fs::create_dir_all(config_dir).await {
+            log_error!("Failed to create config directory: {}", e);
+            return Json(ApiResponse {
+                retcode: 1,
+                data: format!("Failed to create config directory: {}", e),
+            });
+        }
+    }
+
+    if let Err(e) = tokio::fs::write(&config_file, json_str).await {
+        log_error!("Failed to write API cache config: {}", e);
+        return Json(ApiResponse {
+            retcode: 1,
+            data: format!("Failed to write API cache config: {}", e),
+        });
+    }
+
+    *api_cache_config_state.write().await = config_inner;
+    api_cache.clear().await;
+
+    Json(ApiResponse {
+        retcode: 0,
+        data: "API cache config saved".to_string(),
+    })
+}
+
+#[get("/api_cache/stats")]
+pub async fn get_api_cache_stats(
+    api_cache_config_state: &State<Arc<RwLock<ApiCacheConfig>>>,
+    api_cache: &State<Arc<ApiCache>>,
+) -> Json<ApiResponse<ApiCacheStats>> {
+    let config = api_cache_config_state.read().await.clone();
+    Json(ApiResponse {
+        retcode: 0,
+        data: api_cache.stats(&config).await,
+    })
+}
+
+#[post("/api_cache/clear")]
+pub async fn clear_api_cache(api_cache: &State<Arc<ApiCache>>) -> Json<ApiResponse<String>> {
+    api_cache.clear().await;
+    Json(ApiResponse {
+        retcode: 0,
+        data: "API cache cleared".to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::oneshot;
+
+    fn ok_response() -> CachedResponse {
+        CachedResponse {
+            status: Status::Ok,
+            content_type: ContentType::JSON,
+            body: br#"{"retcode":0,"data":[]}"#.to_vec(),
+        }
+    }
+
+    fn member_list_key() -> ApiCallKey {
+        ApiCallKey::new("bot", "get_group_member_list", br#"{"group_id":1}"#)
+    }
+
+    #[tokio::test]
+    async fn coalesce_caches_successful_response() {
+        let cache = Arc::new(ApiCache::default());
+        let config = ApiCacheConfig::default();
+
+        let response = cache
+            .coalesce(config.clone(), member_list_key(), async {
+                Ok(ok_response())
+            })
+            .await;
+        assert!(response.is_ok());
+        assert!(cache.get(&config, &member_list_key()).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn coalesce_skips_put_when_invalidated_during_call() {
+        let cache = Arc::new(ApiCache::default());
+        let config = ApiCacheConfig::default();
+        let (started_tx, started_rx) = oneshot::channel();
+        let (finish_tx, finish_rx) = oneshot::channel::<()>();
+
+        let call = tokio::spawn({
+            let cache = cache.clone();
+            let config = config.clone();
+            async move {
+                cache
+                    .coalesce(config, member_list_key(), async move {
+                        let _ = started_tx.send(());
+                        let _ = finish_rx.await;
+                        Ok(ok_response())
+                    })
+                    .await
+            }
+        });
+
+        started_rx.await.unwrap();
+        cache
+            .invalidate_for_event("bot", Some("group_member_increase"), Some(1))
+            .await;
+        finish_tx.send(()).unwrap();
+
+        assert!(call.await.unwrap().is_ok());
+        assert!(cache.get(&config, &member_list_key()).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn invalidation_of_other_bot_does_not_skip_put() {
+        let cache = Arc::new(ApiCache::default());
+        let config = ApiCacheConfig::default();
+        let (finish_tx, finish_rx) = oneshot::channel::<()>();
+
+        let call = tokio::spawn({
+            let cache = cache.clone();
+            let config = config.clone();
+            async move {
+                cache
+                    .coalesce(config, member_list_key(), async move {
+                        let _ = finish_rx.await;
+                        Ok(ok_response())
+                    })
+                    .await
+            }
+        });
+
+        cache
+            .invalidate_for_event("other", Some("group_member_increase"), Some(1))
+            .await;
+        finish_tx.send(()).unwrap();
+
+        assert!(call.await.unwrap().is_ok());
+        assert!(cache.get(&config, &member_list_key()).await.is_some());
+    }
+}
@@ -0,0 +1,200 @@
+use crate::rotating_log::{self, JsonlWriter, RotatingJsonl};
+use crate::server::api::ApiResponse;
+use chrono::Local;
+use rocket::{
+    get,
+    response::stream::{Event, EventStream},
+    serde::json::Json,
+    FromForm, State,
+};
+use serde::{Deserialize, Serialize};
+use std::ops::ControlFlow;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::{broadcast, mpsc};
+
+/// 一次经由 Milky 代理的 API 调用记录
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditRecord {
+    pub time: String,
+    /// 毫秒级 Unix 时间戳，用于按时间范围查询
+    pub timestamp: i64,
+    pub plugin_id: String,
+    pub api: String,
+    /// 实际调用的 Bot 账号 ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bot_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group_id: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<u64>,
+    pub status: u16,
+    pub latency_ms: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retcode: Option<i64>,
+    /// 调用失败或被拦截时的说明
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl AuditRecord {
+    pub fn new(plugin_id: &str, api: &str) -> Self {
+        let now = Local::now();
+        Self {
+            time: now.format("%Y-%m-%d %H:%M:%S%.3f").to_string(),
+            timestamp: now.timestamp_millis(),
+            plugin_id: plugin_id.to_string(),
+            api: api.to_string(),
+            bot_id: None,
+            group_id: None,
+            user_id: None,
+            status: 0,
+            latency_ms: 0,
+            retcode: None,
+            message: None,
+        }
+    }
+}
+
+#[derive(Debug, Default, FromForm)]
+pub struct AuditQuery {
+    pub plugin_id: Option<String>,
+    pub api: Option<String>,
+    pub bot_id: Option<String>,
+    pub group_id: Option<u64>,
+    pub user_id: Option<u64>,
+    /// 毫秒级 Unix 时间戳（含）
+    pub since: Option<i64>,
+    /// 毫秒级 Unix 时间戳（含）
+    pub until: Option<i64>,
+    pub limit: Option<usize>,
+}
+
+impl AuditQuery {
+    fn matches(&self, record: &AuditRecord) -> bool {
+        self.plugin_id
+            .as_deref()
+            .is_none_or(|plugin_id| record.plugin_id == plugin_id)
+            && self.api.as_deref().is_none_or(|api| record.api == api)
+            && self
+                .bot_id
+                .as_deref()
+                .is_none_or(|bot_id| record.bot_id.as_deref() == Some(bot_id))
+            && self
+                .group_id
+                .is_none_or(|group_id| record.group_id == Some(group_id))
+            && self
+                .user_id
+                .is_none_or(|user_id| record.user_id == Some(user_id))
+            && self.since.is_none_or(|since| record.timestamp >= since)
+            && self.until.is_none_or(|until| record.timestamp <= until)
+    }
+}
+
+/// Milky API 调用审计日志，按 JSON Lines 写入 `data/audit/` 并按大小轮转
+pub struct AuditLog {
+    files: RotatingJsonl,
+    writer: mpsc::UnboundedSender<AuditRecord>,
+    tx: broadcast::Sender<AuditRecord>,
+}
+
+impl AuditLog {
+    /// 创建审计日志并启动后台写入任务，需要在 tokio 运行时中调用
+    pub fn new(dir: PathBuf) -> Arc<Self> {
+        let files = RotatingJsonl::new(dir, "audit");
+        let (writer, receiver) = mpsc::unbounded_channel();
+        let (tx, _) = broadcast::channel(256);
+
+        tokio::spawn(run_writer(JsonlWriter::new(files.clone()), receiver));
+
+        Arc::new(Self { files, writer, tx })
+    }
+
+    pub fn record(&self, record: AuditRecord) {
+        let _ = self.tx.send(record.clone());
+        let _ = self.writer.send(record);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<AuditRecord> {
+        self.tx.subscribe()
+    }
+
+    /// 按条件查询审计记录，结果按时间从新到旧排列
+    pub async fn query(&self, query: &AuditQuery) -> Result<Vec<AuditRecord>, String> {
+        let limit = rotating_log::query_limit(query.limit);
+        let mut result = Vec::new();
+
+        self.files
+            .scan_newest_first(|record: AuditRecord| {
+                if query.since.is_some_and(|since| record.timestamp < since) {
+                    return ControlFlow::Break(());
+                }
+                if query.matches(&record) {
+                    result.push(record);
+                    if result.len() >= limit {
+                        return ControlFlow::Break(());
+                    }
+                }
+                ControlFlow::Continue(())
+            })
+            .await
+            .map_err(|e| format!("Failed to read audit log: {}", e))?;
+
+        Ok(result)
+    }
+}
+
+async fn run_writer(mut writer: JsonlWriter, mut receiver: mpsc::UnboundedReceiver<AuditRecord>) {
+    while let Some(record) = receiver.recv().await {
+        if let Err(e) = writer.append(&record).await {
+            log_error!("Failed to write audit log: {}", e);
+        }
+    }
+}
+
+#[get("/audit?<query..>")]
+pub async fn query_audit(
+    query: AuditQuery,
+    audit_log: &State<Arc<AuditLog>>,
+) -> Json<ApiResponse<Vec<AuditRecord>>> {
+    match audit_log.query(&query).await {
+        Ok(records) => Json(ApiResponse {
+            retcode: 0,
+            data: records,
+        }),
+        Err(e) => {
+            log_error!("Failed to query audit log: {}", e);
+            Json(ApiResponse {
+                retcode: 1,
+                data: Vec::new(),
+            })
+        }
+    }
+}
+
+#[get("/audit/stream?<query..>")]
+pub fn audit_stream(
+    query: AuditQuery,
+    audit_log: &State<Arc<AuditLog>>,
+) -> EventStream![Event + 'static] {
+    let mut rx = audit_log.subscribe();
+
+    EventStream! {
+        loop {
+            let record = match rx.recv().await {
+                Ok(record) => record,
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            };
+
+            if !query.matches(&record) {
+                continue;
+            }
+
+            if let Ok(json) = serde_json::to_string(&record) {
+                yield Event::data(json);
+            }
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use crate::server::api::{BotConfig, BotStatusResponse, LoginInfo};
+use crate::server::upstream::{UpstreamClient, UpstreamEvent};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::{broadcast, Mutex, RwLock};
+
+/// 单个 Bot 账号的连接状态
+pub struct BotConnectionState {
+    /// 账号 ID，对应 `BotConfig::id`
+    pub id: String,
+    pub config: RwLock<BotConfig>,
+    pub is_connected: AtomicBool,
+    pub is_connecting: AtomicBool,
+    pub should_connect: AtomicBool,
+    pub connection_task: Mutex<Option<tokio::task::JoinHandle<()>>>,
+    pub cancel_sender: Mutex<Option<tokio::sync::oneshot::Sender<()>>>,
+    /// 最近一次连接成功时获取的登录信息
+    pub login_info: RwLock<Option<LoginInfo>>,
+    /// 访问该账号所连 Milky 服务端的客户端，供 Milky 代理复用
+    pub client: UpstreamClient,
+    status_sender: broadcast::Sender<BotStatusResponse>,
+    event_sender: broadcast::Sender<BotEvent>,
+}
+
+/// 账号连接收到的一条上游事件
+#[derive(Clone)]
+pub struct BotEvent {
+    pub bot_id: String,
+    /// SSE 的 `event:` 字段，WebSocket 下始终为 `None`
+    pub event: Option<String>,
+    pub data: String,
+}
+
+impl BotConnectionState {
+    fn new(
+        config: BotConfig,
+        status_sender: broadcast::Sender<BotStatusResponse>,
+        event_sender: broadcast::Sender<BotEvent>,
+    ) -> Self {
+        Self {
+            id: config.id.clone(),
+            config: RwLock::new(config),
+            is_connected: AtomicBool::new(false),
+            is_connecting: AtomicBool::new(false),
+            should_connect: AtomicBool::new(false),
+            connection_task: Mutex::new(None),
+            cancel_sender: Mutex::new(None),
+            login_info: RwLock::new(None),
+            client: UpstreamClient::default(),
+            status_sender,
+            event_sender,
+        }
+    }
+
+    pub fn status(&self) -> BotStatusResponse {
+        BotStatusResponse {
+            id: self.id.clone(),
+            connected: self.is_connected.load(Ordering::SeqCst),
+            connecting: self.is_connecting.load(Ordering::SeqCst),
+        }
+    }
+
+    /// 向 WebUI 推送该账号的连接状态
+    pub fn notify_status(&self, connected: bool, connecting: bool) {
+        let _ = self.status_sender.send(BotStatusResponse {
+            id: self.id.clone(),
+            connected,
+            connecting,
+        });
+    }
+
+    /// 把上游事件分发给 Milky 事件代理等订阅者
+    pub fn publish_event(&self, event: UpstreamEvent) {
+        let _ = self.event_sender.send(BotEvent {
+            bot_id: self.id.clone(),
+            event: event.event,
+            data: event.data,
+        });
+    }
+}
+
+/// 所有 Bot 账号，按配置顺序排列，第一个账号为默认账号
+pub struct BotRegistry {
+    bots: RwLock<Vec<Arc<BotConnectionState>>>,
+    pub status_sender: broadcast::Sender<BotStatusResponse>,
+    /// 所有账号的上游事件，每个账号只维持一条上游事件流，由框架日志与插件事件代理共用
+    event_sender: broadcast::Sender<BotEvent>,
+    /// 配置文件写入锁，防止并发写入导致配置损坏
+    pub config_write_lock: Mutex<()>,
+}
+
+impl BotRegistry {
+    pub fn new(configs: Vec<BotConfig>) -> Self {
+        let (status_sender, _) = broadcast::channel(100);
+        let (event_sender, _) = broadcast::channel(2048);
+        let bots = configs
+            .into_iter()
+            .map(|config| {
+                Arc::new(BotConnectionState::new(
+                    config,
+                    status_sender.clone(),
+                    event_sender.clone(),
+                ))
+            })
+            .collect();
+
+        Self {
+            bots: RwLock::new(bots),
+            status_sender,
+            event_sender,
+            config_write_lock: Mutex::new(()),
+        }
+    }
+
+    pub fn subscribe_events(&self) -> broadcast::Receiver<BotEvent> {
+        self.event_sender.subscribe()
+    }
+
+    pub async fn list(&self) -> Vec<Arc<BotConnectionState>> {
+        self.bots.read().await.clone()
+    }
+
+    pub async fn get(&self, id: &str) -> Option<Arc<BotConnectionState>> {
+        self.bots
+            .read()
+            .await
+            .iter()
+            .find(|bot| bot.id == id)
+            .cloned()
+    }
+
+    /// 未指定账号时返回默认账号
+    pub async fn get_or_default(&self, id: Option<&str>) -> Option<Arc<BotConnectionState>> {
+        match id.map(str::trim).filter(|id| !id.is_empty()) {
+            Some(id) => self.get(id).await,
+            None => self.bots.read().await.first().cloned(),
+        }
+    }
+
+    /// 按账号 ID 或已登录的 QQ 号查找账号
+    pub async fn resolve(&self, key: &str) -> Option<Arc<BotConnectionState>> {
+        let key = key.trim();
+        if let Some(bot) = self.get(key).await {
+            return Some(bot);
+        }
+
+        let uin = key.parse::<i64>().ok()?;
+        for bot in self.list().await {
+            if bot
+                .login_info
+                .read()
+                .await
+                .as_ref()
+                .is_some_and(|info| info.uin == uin)
+            {
+                return Some(bot);
+            }
+        }
+        None
+    }
+
+    /// 新增账号或更新同 ID 账号的配置，返回该账号的连接状态
+    pub async fn upsert(&self, config: BotConfig) -> Arc<BotConnectionState> {
+        let mut bots = self.bots.write().await;
+        if let Some(bot) = bots.iter().find(|bot| bot.id == config.id) {
+            *bot.config.write().await = config;
+            return bot.clone();
+        }
+
+        let bot = Arc::new(BotConnectionState::new(
+            config,
+            self.status_sender.clone(),
+            self.event_sender.clone(),
+        ));
+        bots.push(bot.clone());
+        bot
+    }
+
+    pub async fn remove(&self, id: &str) -> Option<Arc<BotConnectionState>> {
+        let mut bots = self.bots.write().await;
+        let index = bots.iter().position(|bot| bot.id == id)?;
+        Some(bots.remove(index))
+    }
+
+    pub async fn configs(&self) -> Vec<BotConfig> {
+        let mut configs = Vec::new();
+        for bot in self.list().await {
+            configs.push(bot.config.read().await.clone());
+        }
+        configs
+    }
+}
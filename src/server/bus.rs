@@ -0,0 +1,261 @@
+use crate::server::api::{ApiResponse, PluginCaller};
+use chrono::Local;
+use rocket::{
+    get, post,
+    response::stream::{Event, EventStream},
+    serde::json::Json,
+    State,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use ws as rocket_ws;
+
+const MAX_TOPIC_LEN: usize = 128;
+
+/// 插件之间通过总线传递的一条消息
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BusMessage {
+    pub topic: String,
+    /// 发布该消息的插件 ID
+    pub from: String,
+    pub data: serde_json::Value,
+    /// 毫秒级 Unix 时间戳
+    pub timestamp: i64,
+}
+
+#[derive(Deserialize)]
+pub struct PublishRequest {
+    pub topic: String,
+    #[serde(default)]
+    pub data: serde_json::Value,
+}
+
+/// 插件消息总线，插件通过主程序按主题发布、订阅消息，不需要各自开放端口
+pub struct PluginBus {
+    tx: broadcast::Sender<BusMessage>,
+}
+
+impl Default for PluginBus {
+    fn default() -> Self {
+        let (tx, _) = broadcast::channel(1024);
+        Self { tx }
+    }
+}
+
+impl PluginBus {
+    pub fn publish(&self, from: &str, request: PublishRequest) -> Result<(), String> {
+        validate_topic(&request.topic)?;
+
+        let _ = self.tx.send(BusMessage {
+            topic: request.topic,
+            from: from.to_string(),
+            data: request.data,
+            timestamp: Local::now().timestamp_millis(),
+        });
+        Ok(())
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<BusMessage> {
+        self.tx.subscribe()
+    }
+}
+
+/// 主题只允许字母、数字和 `_ - . : /`，不能包含通配符
+fn validate_topic(topic: &str) -> Result<(), String> {
+    if topic.is_empty() || topic.len() > MAX_TOPIC_LEN {
+        return Err(format!(
+            "Topic must be 1 to {} characters long",
+            MAX_TOPIC_LEN
+        ));
+    }
+    if !topic
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | ':' | '/'))
+    {
+        return Err(format!("Invalid topic '{}'", topic));
+    }
+    Ok(())
+}
+
+/// 订阅的主题列表，逗号分隔；以 `*` 结尾表示前缀匹配，单独的 `*` 表示全部主题
+struct TopicFilter {
+    patterns: Vec<String>,
+}
+
+impl TopicFilter {
+    fn new(topics: Option<&str>) -> Self {
+        let patterns = topics
+            .unwrap_or("*")
+            .split(',')
+            .map(str::trim)
+            .filter(|topic| !topic.is_empty())
+            .map(str::to_string)
+            .collect();
+        Self { patterns }
+    }
+
+    fn matches(&self, topic: &str) -> bool {
+        self.patterns
+            .iter()
+            .any(|pattern| match pattern.strip_suffix('*') {
+                Some(prefix) => topic.starts_with(prefix),
+                None => pattern == topic,
+            })
+    }
+}
+
+async fn next_message(
+    rx: &mut broadcast::Receiver<BusMessage>,
+    plugin_id: &str,
+) -> Option<BusMessage> {
+    loop {
+        match rx.recv().await {
+            Ok(message) => return Some(message),
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                log_warn!(
+                    "插件 {} 接收总线消息过慢，丢弃了 {} 条消息",
+                    plugin_id,
+                    skipped
+                );
+            }
+            Err(broadcast::error::RecvError::Closed) => return None,
+        }
+    }
+}
+
+#[post("/bus/publish", data = "<request>")]
+pub fn bus_publish(
+    caller: PluginCaller,
+    request: Json<PublishRequest>,
+    bus: &State<Arc<PluginBus>>,
+) -> Json<ApiResponse<String>> {
+    match bus.publish(&caller.plugin_id, request.into_inner()) {
+        Ok(()) => Json(ApiResponse {
+            retcode: 0,
+            data: "ok".to_string(),
+        }),
+        Err(e) => Json(ApiResponse {
+            retcode: 1,
+            data: e,
+        }),
+    }
+}
+
+#[get("/bus/subscribe?<topics>", rank = 1, format = "text/event-stream")]
+pub fn bus_subscribe(
+    topics: Option<&str>,
+    caller: PluginCaller,
+    bus: &State<Arc<PluginBus>>,
+) -> EventStream![Event + 'static] {
+    let filter = TopicFilter::new(topics);
+    let mut rx = bus.subscribe();
+    let plugin_id = caller.plugin_id;
+
+    EventStream! {
+        while let Some(message) = next_message(&mut rx, &plugin_id).await {
+            if !filter.matches(&message.topic) {
+                continue;
+            }
+            yield Event::json(&message).event(message.topic.clone());
+        }
+    }
+}
+
+/// WebSocket 订阅，同一连接上也可以发送 `{"topic": ..., "data": ...}` 发布消息
+#[get("/bus/subscribe?<topics>", rank = 2)]
+pub fn bus_subscribe_ws(
+    ws: rocket_ws::WebSocket,
+    topics: Option<&str>,
+    caller: PluginCaller,
+    bus: &State<Arc<PluginBus>>,
+) -> rocket_ws::Channel<'static> {
+    let filter = TopicFilter::new(topics);
+    let bus = bus.inner().clone();
+    let mut rx = bus.subscribe();
+    let plugin_id = caller.plugin_id;
+
+    ws.channel(move |mut stream| {
+        Box::pin(async move {
+            use rocket::futures::{SinkExt, StreamExt};
+
+            loop {
+                tokio::select! {
+                    frame = stream.next() => {
+                        let text = match frame {
+                            Some(Ok(rocket_ws::Message::Text(text))) => text,
+                            Some(Ok(m)) if m.is_close() => break,
+                            Some(Ok(_)) => continue,
+                            Some(Err(_)) | None => break,
+                        };
+
+                        let result = serde_json::from_str::<PublishRequest>(&text)
+                            .map_err(|e| format!("Invalid publish frame: {}", e))
+                            .and_then(|request| bus.publish(&plugin_id, request));
+                        if let Err(e) = result {
+                            let reply = serde_json::json!({ "retcode": 1, "data": e });
+                            if stream.send(rocket_ws::Message::Text(reply.to_string())).await.is_err() {
+                                break;
+                            }
+                        }
+                    }
+                    message = next_message(&mut rx, &plugin_id) => {
+                        let Some(message) = message else {
+                            break;
+                        };
+                        if !filter.matches(&message.topic) {
+                            continue;
+                        }
+                        let Ok(text) = serde_json::to_string(&message) else {
+                            continue;
+                        };
+                        if stream.send(rocket_ws::Message::Text(text)).await.is_err() {
+                            break;
+                        }
+                    }
+                }
+            }
+
+            Ok(())
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn topic_filter_defaults_to_all_topics() {
+        let filter = TopicFilter::new(None);
+        assert!(filter.matches("anything"));
+        assert!(filter.matches("a/b:c"));
+    }
+
+    #[test]
+    fn topic_filter_exact_and_prefix() {
+        let filter = TopicFilter::new(Some(" weather , chat.* ,"));
+        assert!(filter.matches("weather"));
+        assert!(!filter.matches("weather.today"));
+        assert!(filter.matches("chat.group"));
+        assert!(filter.matches("chat."));
+        assert!(!filter.matches("chat"));
+        assert!(!filter.matches("news"));
+    }
+
+    #[test]
+    fn empty_topic_list_matches_nothing() {
+        let filter = TopicFilter::new(Some(" , "));
+        assert!(!filter.matches("weather"));
+    }
+
+    #[test]
+    fn topic_validation() {
+        assert!(validate_topic("plugin.a:event/1_x-y").is_ok());
+        assert!(validate_topic("").is_err());
+        assert!(validate_topic("chat.*").is_err());
+        assert!(validate_topic("has space").is_err());
+        assert!(validate_topic(&"a".repeat(MAX_TOPIC_LEN + 1)).is_err());
+    }
+}
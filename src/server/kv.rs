@@ -0,0 +1,748 @@
+use crate::server::api::{ApiResponse, PluginCaller, WebUiCaller};
+use chrono::Local;
+use rocket::{get, post, serde::json::Json, FromForm, State};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+const MAX_KEY_LEN: usize = 256;
+const DEFAULT_LIST_LIMIT: usize = 100;
+const MAX_LIST_LIMIT: usize = 1000;
+/// 日志中已失效的记录超过该数量，且多于有效记录时压缩日志
+const COMPACT_MIN_STALE_RECORDS: usize = 1000;
+
+/// 每个插件的存储配额，数据全部常驻内存，需要防止单个插件耗尽内存和磁盘
+#[derive(Debug, Clone, Copy)]
+struct KvQuota {
+    max_keys: usize,
+    /// 单个值序列化为 JSON 后的字节数上限
+    max_value_bytes: usize,
+    /// 所有键名与值的字节数之和的上限
+    max_total_bytes: usize,
+}
+
+impl Default for KvQuota {
+    fn default() -> Self {
+        Self {
+            max_keys: 10_000,
+            max_value_bytes: 256 * 1024,
+            max_total_bytes: 16 * 1024 * 1024,
+        }
+    }
+}
+
+/// 插件存储中的一条记录
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct KvEntry {
+    pub key: String,
+    pub value: serde_json::Value,
+    /// 毫秒级 Unix 时间戳，`None` 表示永不过期
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<i64>,
+}
+
+impl KvEntry {
+    fn is_expired(&self, now: i64) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+
+    /// 计入配额的字节数
+    fn size(&self) -> usize {
+        self.key.len() + self.value.to_string().len()
+    }
+}
+
+/// 存储日志中的一行，按顺序重放即可还原数据
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+enum KvLogRecord {
+    Set(KvEntry),
+    Delete { key: String },
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KvNamespaceInfo {
+    pub plugin_id: String,
+    pub keys: usize,
+}
+
+/// 单个插件的数据，内存中保存全部有效记录，修改以追加日志的方式写入磁盘
+struct Namespace {
+    path: PathBuf,
+    entries: BTreeMap<String, KvEntry>,
+    file: Option<tokio::fs::File>,
+    /// 日志文件中的记录行数
+    records: usize,
+    /// 有效记录的字节数之和
+    bytes: usize,
+}
+
+impl Namespace {
+    async fn load(path: PathBuf) -> Result<Self, String> {
+        let content = match tokio::fs::read_to_string(&path).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(format!("Failed to read kv store: {}", e)),
+        };
+
+        let mut entries = BTreeMap::new();
+        let mut records = 0;
+        for line in content.lines() {
+            // 写入过程中崩溃可能留下不完整的最后一行，直接跳过
+            let Ok(record) = serde_json::from_str::<KvLogRecord>(line) else {
+                continue;
+            };
+            records += 1;
+            match record {
+                KvLogRecord::Set(entry) => {
+                    entries.insert(entry.key.clone(), entry);
+                }
+                KvLogRecord::Delete { key } => {
+                    entries.remove(&key);
+                }
+            }
+        }
+
+        let bytes = entries.values().map(KvEntry::size).sum();
+        let mut namespace = Self {
+            path,
+            entries,
+            file: None,
+            records,
+            bytes,
+        };
+        namespace.remove_expired();
+        Ok(namespace)
+    }
+
+    fn insert(&mut self, entry: KvEntry) {
+        self.bytes += entry.size();
+        if let Some(old) = self.entries.insert(entry.key.clone(), entry) {
+            self.bytes -= old.size();
+        }
+    }
+
+    fn remove(&mut self, key: &str) {
+        if let Some(old) = self.entries.remove(key) {
+            self.bytes -= old.size();
+        }
+    }
+
+    fn remove_expired(&mut self) {
+        let now = Local::now().timestamp_millis();
+        let mut removed = 0;
+        self.entries.retain(|_, entry| {
+            let expired = entry.is_expired(now);
+            if expired {
+                removed += entry.size();
+            }
+            !expired
+        });
+        self.bytes -= removed;
+    }
+
+    fn get(&mut self, key: &str) -> Option<KvEntry> {
+        let now = Local::now().timestamp_millis();
+        if self.entries.get(key)?.is_expired(now) {
+            self.remove(key);
+            return None;
+        }
+        self.entries.get(key).cloned()
+    }
+
+    /// 写入前检查配额，覆盖已有的键时按替换后的大小计算
+    fn check_quota(&mut self, entry: &KvEntry, quota: &KvQuota) -> Result<(), String> {
+        let value_bytes = entry.value.to_string().len();
+        if value_bytes > quota.max_value_bytes {
+            return Err(format!(
+                "Value is {} bytes, the limit is {} bytes",
+                value_bytes, quota.max_value_bytes
+            ));
+        }
+
+        let replaced = self.get(&entry.key).map(|old| old.size());
+        if replaced.is_none() && self.entries.len() >= quota.max_keys {
+            self.remove_expired();
+            if self.entries.len() >= quota.max_keys {
+                return Err(format!(
+                    "Key limit of {} per plugin reached",
+                    quota.max_keys
+                ));
+            }
+        }
+
+        let total = self.bytes - replaced.unwrap_or(0) + entry.size();
+        if total > quota.max_total_bytes {
+            return Err(format!(
+                "Storage limit of {} bytes per plugin exceeded",
+                quota.max_total_bytes
+            ));
+        }
+        Ok(())
+    }
+
+    fn list(&mut self, prefix: &str, limit: usize) -> Vec<KvEntry> {
+        self.remove_expired();
+        self.entries
+            .range(prefix.to_string()..)
+            .take_while(|(key, _)| key.starts_with(prefix))
+            .take(limit)
+            .map(|(_, entry)| entry.clone())
+            .collect()
+    }
+
+    async fn append(&mut self, record: &KvLogRecord) -> Result<(), String> {
+        let mut line = serde_json::to_string(record)
+            .map_err(|e| format!("Failed to serialize kv record: {}", e))?;
+        line.push('\n');
+
+        if self.file.is_none() {
+            self.file = Some(open_append(&self.path).await?);
+        }
+        let Some(file) = self.file.as_mut() else {
+            return Err("Failed to open kv store".to_string());
+        };
+
+        let written = match file.write_all(line.as_bytes()).await {
+            Ok(()) => file.flush().await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = written {
+            self.file = None;
+            return Err(format!("Failed to write kv store: {}", e));
+        }
+        self.records += 1;
+        Ok(())
+    }
+
+    async fn compact_if_needed(&mut self) {
+        let stale = self.records.saturating_sub(self.entries.len());
+        if stale < COMPACT_MIN_STALE_RECORDS || stale <= self.entries.len() {
+            return;
+        }
+        if let Err(e) = self.compact().await {
+            log_warn!("压缩插件存储 {} 失败: {}", self.path.display(), e);
+        }
+    }
+
+    /// 只保留有效记录重写日志，先写临时文件再替换，避免中途崩溃丢失数据
+    async fn compact(&mut self) -> Result<(), String> {
+        self.remove_expired();
+
+        let mut content = String::new();
+        for entry in self.entries.values() {
+            let line = serde_json::to_string(&KvLogRecord::Set(entry.clone()))
+                .map_err(|e| format!("Failed to serialize kv record: {}", e))?;
+            content.push_str(&line);
+            content.push('\n');
+        }
+
+        let tmp_path = self.path.with_extension("jsonl.tmp");
+        tokio::fs::write(&tmp_path, content)
+            .await
+            .map_err(|e| format!("Failed to write kv store: {}", e))?;
+
+        self.file = None;
+        tokio::fs::rename(&tmp_path, &self.path)
+            .await
+            .map_err(|e| format!("Failed to replace kv store: {}", e))?;
+        self.records = self.entries.len();
+        Ok(())
+    }
+}
+
+async fn open_append(path: &Path) -> Result<tokio::fs::File, String> {
+    if let Some(dir) = path.parent() {
+        tokio::fs::create_dir_all(dir)
+            .await
+            .map_err(|e| format!("Failed to create kv store directory: {}", e))?;
+    }
+    tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .await
+        .map_err(|e| format!("Failed to open kv store: {}", e))
+}
+
+/// 主程序提供的插件键值存储，每个插件一个命名空间，数据保存在 `data/kv/<插件ID>.jsonl`
+pub struct KvStore {
+    dir: PathBuf,
+    quota: KvQuota,
+    namespaces: Mutex<HashMap<String, Arc<Mutex<Namespace>>>>,
+}
+
+impl KvStore {
+    pub fn new(dir: PathBuf) -> Self {
+        Self::with_quota(dir, KvQuota::default())
+    }
+
+    fn with_quota(dir: PathBuf, quota: KvQuota) -> Self {
+        Self {
+            dir,
+            quota,
+            namespaces: Mutex::new(HashMap::new()),
+        }
+    }
+
+    async fn namespace(&self, plugin_id: &str) -> Result<Arc<Mutex<Namespace>>, String> {
+        if plugin_id.is_empty()
+            || plugin_id.contains(['/', '\\'])
+            || plugin_id == "."
+            || plugin_id == ".."
+        {
+            return Err(format!("Invalid plugin id '{}'", plugin_id));
+        }
+
+        let mut namespaces = self.namespaces.lock().await;
+        if let Some(namespace) = namespaces.get(plugin_id) {
+            return Ok(namespace.clone());
+        }
+
+        let path = self.dir.join(format!("{}.jsonl", plugin_id));
+        let namespace = Arc::new(Mutex::new(Namespace::load(path).await?));
+        namespaces.insert(plugin_id.to_string(), namespace.clone());
+        Ok(namespace)
+    }
+
+    pub async fn get(&self, plugin_id: &str, key: &str) -> Result<Option<KvEntry>, String> {
+        let namespace = self.namespace(plugin_id).await?;
+        let mut namespace = namespace.lock().await;
+        Ok(namespace.get(key))
+    }
+
+    pub async fn set(
+        &self,
+        plugin_id: &str,
+        key: String,
+        value: serde_json::Value,
+        ttl_secs: Option<u64>,
+    ) -> Result<(), String> {
+        validate_key(&key)?;
+
+        let expires_at = ttl_secs.filter(|ttl| *ttl > 0).map(|ttl| {
+            let ttl_ms = i64::try_from(ttl).unwrap_or(i64::MAX).saturating_mul(1000);
+            Local::now().timestamp_millis().saturating_add(ttl_ms)
+        });
+        let entry = KvEntry {
+            key,
+            value,
+            expires_at,
+        };
+
+        let namespace = self.namespace(plugin_id).await?;
+        let mut namespace = namespace.lock().await;
+        namespace.check_quota(&entry, &self.quota)?;
+        namespace.append(&KvLogRecord::Set(entry.clone())).await?;
+        namespace.insert(entry);
+        namespace.compact_if_needed().await;
+        Ok(())
+    }
+
+    /// 删除一个键，返回该键删除前是否存在
+    pub async fn delete(&self, plugin_id: &str, key: &str) -> Result<bool, String> {
+        let namespace = self.namespace(plugin_id).await?;
+        let mut namespace = namespace.lock().await;
+        if namespace.get(key).is_none() {
+            return Ok(false);
+        }
+
+        namespace
+            .append(&KvLogRecord::Delete {
+                key: key.to_string(),
+            })
+            .await?;
+        namespace.remove(key);
+        namespace.compact_if_needed().await;
+        Ok(true)
+    }
+
+    /// 按键名排序列出指定前缀的记录
+    pub async fn list(
+        &self,
+        plugin_id: &str,
+        prefix: &str,
+        limit: usize,
+    ) -> Result<Vec<KvEntry>, String> {
+        let namespace = self.namespace(plugin_id).await?;
+        let mut namespace = namespace.lock().await;
+        Ok(namespace.list(prefix, limit))
+    }
+
+    /// 列出所有有存储数据的插件，供 WebUI 浏览
+    pub async fn namespaces(&self) -> Result<Vec<KvNamespaceInfo>, String> {
+        let mut dir = match tokio::fs::read_dir(&self.dir).await {
+            Ok(dir) => dir,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(format!("Failed to read kv store directory: {}", e)),
+        };
+
+        let mut plugin_ids = Vec::new();
+        while let Ok(Some(entry)) = dir.next_entry().await {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            if let Some(plugin_id) = file_name.strip_suffix(".jsonl") {
+                plugin_ids.push(plugin_id.to_string());
+            }
+        }
+        plugin_ids.sort();
+
+        let mut result = Vec::new();
+        for plugin_id in plugin_ids {
+            let namespace = self.namespace(&plugin_id).await?;
+            let mut namespace = namespace.lock().await;
+            let keys = namespace.list("", usize::MAX).len();
+            result.push(KvNamespaceInfo { plugin_id, keys });
+        }
+        Ok(result)
+    }
+}
+
+fn validate_key(key: &str) -> Result<(), String> {
+    if key.is_empty() || key.len() > MAX_KEY_LEN {
+        return Err(format!("Key must be 1 to {} bytes long", MAX_KEY_LEN));
+    }
+    if key.chars().any(char::is_control) {
+        return Err("Key must not contain control characters".to_string());
+    }
+    Ok(())
+}
+
+#[derive(Deserialize)]
+pub struct KvSetRequest {
+    pub key: String,
+    #[serde(default)]
+    pub value: serde_json::Value,
+    /// 过期时间（秒），省略表示永不过期
+    #[serde(default)]
+    pub ttl_secs: Option<u64>,
+}
+
+#[derive(Deserialize)]
+pub struct KvDeleteRequest {
+    pub key: String,
+}
+
+#[derive(FromForm)]
+pub struct KvListQuery {
+    prefix: Option<String>,
+    limit: Option<usize>,
+}
+
+impl KvListQuery {
+    fn prefix(&self) -> &str {
+        self.prefix.as_deref().unwrap_or("")
+    }
+
+    fn limit(&self) -> usize {
+        self.limit
+            .unwrap_or(DEFAULT_LIST_LIMIT)
+            .clamp(1, MAX_LIST_LIMIT)
+    }
+}
+
+/// 成功时 `data` 为结果，失败时 `retcode` 为 1、`data` 为错误信息
+fn kv_response<T: Serialize>(result: Result<T, String>) -> Json<ApiResponse<serde_json::Value>> {
+    match result.and_then(|data| serde_json::to_value(data).map_err(|e| e.to_string())) {
+        Ok(data) => Json(ApiResponse { retcode: 0, data }),
+        Err(e) => {
+            log_error!("Plugin kv store error: {}", e);
+            Json(ApiResponse {
+                retcode: 1,
+                data: serde_json::Value::String(e),
+            })
+        }
+    }
+}
+
+/// 读取一个键，不存在或已过期时返回 `null`
+#[get("/kv/get?<key>")]
+pub async fn kv_get(
+    key: &str,
+    caller: PluginCaller,
+    store: &State<Arc<KvStore>>,
+) -> Json<ApiResponse<serde_json::Value>> {
+    kv_response(store.get(&caller.plugin_id, key).await)
+}
+
+#[post("/kv/set", data = "<request>")]
+pub async fn kv_set(
+    request: Json<KvSetRequest>,
+    caller: PluginCaller,
+    store: &State<Arc<KvStore>>,
+) -> Json<ApiResponse<serde_json::Value>> {
+    let request = request.into_inner();
+    let result = store
+        .set(
+            &caller.plugin_id,
+            request.key,
+            request.value,
+            request.ttl_secs,
+        )
+        .await;
+    kv_response(result.map(|()| "ok"))
+}
+
+#[post("/kv/delete", data = "<request>")]
+pub async fn kv_delete(
+    request: Json<KvDeleteRequest>,
+    caller: PluginCaller,
+    store: &State<Arc<KvStore>>,
+) -> Json<ApiResponse<serde_json::Value>> {
+    kv_response(store.delete(&caller.plugin_id, &request.key).await)
+}
+
+#[get("/kv/list?<query..>")]
+pub async fn kv_list(
+    query: KvListQuery,
+    caller: PluginCaller,
+    store: &State<Arc<KvStore>>,
+) -> Json<ApiResponse<serde_json::Value>> {
+    kv_response(
+        store
+            .list(&caller.plugin_id, query.prefix(), query.limit())
+            .await,
+    )
+}
+
+/// WebUI 只读浏览：列出有存储数据的插件
+#[get("/kv/namespaces")]
+pub async fn list_kv_namespaces(
+    _webui: WebUiCaller,
+    store: &State<Arc<KvStore>>,
+) -> Json<ApiResponse<serde_json::Value>> {
+    kv_response(store.namespaces().await)
+}
+
+/// WebUI 只读浏览：查看指定插件的存储数据
+#[get("/kv/browse/<plugin_id>?<query..>")]
+pub async fn browse_kv(
+    plugin_id: &str,
+    query: KvListQuery,
+    _webui: WebUiCaller,
+    store: &State<Arc<KvStore>>,
+) -> Json<ApiResponse<serde_json::Value>> {
+    kv_response(store.list(plugin_id, query.prefix(), query.limit()).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+    use serde_json::json;
+
+    /// 每个测试使用独立的临时目录，结束时删除
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new() -> Self {
+            let suffix: u64 = rand::rng().random();
+            Self(std::env::temp_dir().join(format!("yuyubot-kv-test-{:016x}", suffix)))
+        }
+
+        fn log_lines(&self, plugin_id: &str) -> usize {
+            std::fs::read_to_string(self.0.join(format!("{}.jsonl", plugin_id)))
+                .map(|content| content.lines().count())
+                .unwrap_or(0)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn keys(entries: &[KvEntry]) -> Vec<&str> {
+        entries.iter().map(|entry| entry.key.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn reload_replays_sets_and_deletes() {
+        let dir = TempDir::new();
+        let store = KvStore::new(dir.0.clone());
+        store.set("p", "a".into(), json!(1), None).await.unwrap();
+        store
+            .set("p", "b".into(), json!({"x": [1, 2]}), None)
+            .await
+            .unwrap();
+        store.set("p", "a".into(), json!(2), None).await.unwrap();
+        assert!(store.delete("p", "b").await.unwrap());
+        assert!(!store.delete("p", "b").await.unwrap());
+        store
+            .set("other", "a".into(), json!("o"), None)
+            .await
+            .unwrap();
+
+        let reloaded = KvStore::new(dir.0.clone());
+        let a = reloaded.get("p", "a").await.unwrap().unwrap();
+        assert_eq!(a.value, json!(2));
+        assert!(reloaded.get("p", "b").await.unwrap().is_none());
+        let other = reloaded.get("other", "a").await.unwrap().unwrap();
+        assert_eq!(other.value, json!("o"));
+    }
+
+    #[tokio::test]
+    async fn reload_skips_truncated_last_line() {
+        let dir = TempDir::new();
+        std::fs::create_dir_all(&dir.0).unwrap();
+        std::fs::write(
+            dir.0.join("p.jsonl"),
+            "{\"op\":\"set\",\"key\":\"a\",\"value\":1}\n{\"op\":\"set\",\"key\":\"b\",\"val",
+        )
+        .unwrap();
+
+        let store = KvStore::new(dir.0.clone());
+        let entries = store.list("p", "", 10).await.unwrap();
+        assert_eq!(keys(&entries), vec!["a"]);
+    }
+
+    #[tokio::test]
+    async fn expired_entries_are_hidden_and_dropped_on_reload() {
+        let dir = TempDir::new();
+        let past = Local::now().timestamp_millis() - 1;
+        std::fs::create_dir_all(&dir.0).unwrap();
+        std::fs::write(
+            dir.0.join("p.jsonl"),
+            format!(
+                "{{\"op\":\"set\",\"key\":\"old\",\"value\":1,\"expiresAt\":{}}}\n",
+                past
+            ),
+        )
+        .unwrap();
+
+        let store = KvStore::new(dir.0.clone());
+        assert!(store.get("p", "old").await.unwrap().is_none());
+
+        store
+            .set("p", "fresh".into(), json!(1), Some(3600))
+            .await
+            .unwrap();
+        let namespace = store.namespace("p").await.unwrap();
+        namespace.lock().await.insert(KvEntry {
+            key: "stale".into(),
+            value: json!(1),
+            expires_at: Some(past),
+        });
+
+        assert!(store.get("p", "stale").await.unwrap().is_none());
+        let entries = store.list("p", "", 10).await.unwrap();
+        assert_eq!(keys(&entries), vec!["fresh"]);
+        assert!(entries[0].expires_at.is_some());
+        assert!(!store.delete("p", "old").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn list_returns_only_matching_prefix_in_order() {
+        let dir = TempDir::new();
+        let store = KvStore::new(dir.0.clone());
+        for key in ["user:2", "user:10", "group:1", "user", "userx", "user:1"] {
+            store.set("p", key.into(), json!(key), None).await.unwrap();
+        }
+
+        let entries = store.list("p", "user:", 10).await.unwrap();
+        assert_eq!(keys(&entries), vec!["user:1", "user:10", "user:2"]);
+
+        let entries = store.list("p", "user:", 2).await.unwrap();
+        assert_eq!(keys(&entries), vec!["user:1", "user:10"]);
+
+        let entries = store.list("p", "zzz", 10).await.unwrap();
+        assert!(entries.is_empty());
+
+        let entries = store.list("p", "", 100).await.unwrap();
+        assert_eq!(entries.len(), 6);
+    }
+
+    #[tokio::test]
+    async fn compaction_preserves_data() {
+        let dir = TempDir::new();
+        let store = KvStore::new(dir.0.clone());
+        store
+            .set("p", "keep".into(), json!("k"), None)
+            .await
+            .unwrap();
+        store
+            .set("p", "ttl".into(), json!("t"), Some(3600))
+            .await
+            .unwrap();
+        for i in 0..COMPACT_MIN_STALE_RECORDS + 10 {
+            store
+                .set("p", "counter".into(), json!(i), None)
+                .await
+                .unwrap();
+        }
+        store.set("p", "gone".into(), json!(1), None).await.unwrap();
+        store.delete("p", "gone").await.unwrap();
+
+        // 超过阈值后自动压缩，日志只剩少量记录
+        assert!(dir.log_lines("p") < 20);
+
+        let reloaded = KvStore::new(dir.0.clone());
+        let entries = reloaded.list("p", "", 10).await.unwrap();
+        assert_eq!(keys(&entries), vec!["counter", "keep", "ttl"]);
+        assert_eq!(entries[0].value, json!(COMPACT_MIN_STALE_RECORDS + 9));
+        assert!(entries[2].expires_at.is_some());
+
+        // 压缩后继续追加写入
+        reloaded
+            .set("p", "after".into(), json!(1), None)
+            .await
+            .unwrap();
+        let reloaded = KvStore::new(dir.0.clone());
+        assert!(reloaded.get("p", "after").await.unwrap().is_some());
+        assert!(reloaded.get("p", "keep").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn quota_limits_keys_and_bytes() {
+        let dir = TempDir::new();
+        let store = KvStore::with_quota(
+            dir.0.clone(),
+            KvQuota {
+                max_keys: 2,
+                max_value_bytes: 10,
+                max_total_bytes: 16,
+            },
+        );
+
+        let err = store
+            .set("p", "big".into(), json!("0123456789"), None)
+            .await
+            .unwrap_err();
+        assert!(err.contains("limit"));
+
+        store.set("p", "a".into(), json!(1), None).await.unwrap();
+        store.set("p", "b".into(), json!(2), None).await.unwrap();
+        let err = store
+            .set("p", "c".into(), json!(3), None)
+            .await
+            .unwrap_err();
+        assert!(err.contains("Key limit"));
+
+        // 覆盖已有的键不占用新的键数
+        store.set("p", "a".into(), json!(11), None).await.unwrap();
+        // "a" 占 11 字节、"b" 占 2 字节，把 "b" 改为 "xyzw"（7 字节）后合计 18 字节，超出上限
+        store
+            .set("p", "a".into(), json!("abcdefgh"), None)
+            .await
+            .unwrap();
+        let err = store
+            .set("p", "b".into(), json!("xyzw"), None)
+            .await
+            .unwrap_err();
+        assert!(err.contains("Storage limit"));
+
+        // 删除后释放配额，其他插件不受影响
+        store.delete("p", "a").await.unwrap();
+        store.set("p", "c".into(), json!(3), None).await.unwrap();
+        store.set("q", "a".into(), json!(1), None).await.unwrap();
+
+        let reloaded = KvStore::new(dir.0.clone());
+        let entries = reloaded.list("p", "", 10).await.unwrap();
+        assert_eq!(keys(&entries), vec!["b", "c"]);
+    }
+}
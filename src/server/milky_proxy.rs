@@ -1,10 +1,15 @@
+use crate::plus::manager::is_read_only_api;
 use crate::plus::PluginManager;
-use crate::server::api::BotConfig;
+use crate::server::api_cache::{ApiCache, ApiCacheConfig, ApiCallKey, CachedResponse};
+use crate::server::audit::{AuditLog, AuditRecord};
 use crate::server::permissions::{
-    extract_target_group_id_from_api, extract_target_group_id_from_event, is_group_allowed,
-    permission_mode_name, PermissionConfig,
+    extract_target_group_id_from_api, extract_target_group_id_from_event,
+    extract_target_user_from_api, extract_target_user_id_from_event, is_group_allowed,
+    is_user_allowed, permission_mode_name, resolve_friend_request_initiator, PermissionConfig,
+    UserApiTarget,
 };
-use futures_util::StreamExt;
+use crate::server::rate_limit::{RateLimitConfig, RateLimiter};
+use crate::server::{BotConnectionState, BotRegistry};
 use rocket::data::{Data, ToByteUnit};
 use rocket::fairing::AdHoc;
 use rocket::http::{ContentType, Status};
@@ -13,21 +18,95 @@ use rocket::response::stream::{Event, EventStream};
 use rocket::response::{Responder, Response};
 use rocket::Request;
 use rocket::{get, post, routes, Config, State};
+use serde::Deserialize;
+use std::collections::{HashSet, VecDeque};
 use std::io::Cursor;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::{broadcast, RwLock};
 use ws as rocket_ws;
 
 #[derive(Clone)]
 struct SseMessage {
+    /// 事件序号，单调递增，用于断线重连后补发事件
+    id: u64,
+    /// 产生该事件的 Bot 账号 ID
+    bot_id: String,
     event: Option<String>,
     data: String,
+    /// 事件类型（Milky 事件的 `event_type` 字段）
+    event_type: Option<String>,
+    /// 事件关联的群号，由各订阅者按自己插件的群规则过滤
+    group_id: Option<u64>,
+    /// 私聊/好友事件关联的用户 QQ 号，由各订阅者按自己插件的用户规则过滤
+    user_id: Option<u64>,
+}
+
+/// 单个插件的事件订阅，决定哪些事件会被推送给它
+struct EventSubscription {
+    plugin_id: String,
+    /// 允许接收的事件类型，`None` 表示不限制
+    event_types: Option<HashSet<String>>,
+}
+
+impl EventSubscription {
+    /// 订阅的事件类型取 `app.json` 中 `events` 声明与 `/event?events=` 参数的交集，
+    /// 两者都未指定时接收全部事件
+    async fn new(manager: &PluginManager, plugin_id: String, requested: Option<String>) -> Self {
+        let declared: HashSet<String> = manager
+            .get_plugin_event_types(&plugin_id)
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .collect();
+        let requested = requested.map(|raw| parse_event_types(&raw));
+
+        let event_types = match requested {
+            Some(requested) if declared.is_empty() => Some(requested),
+            Some(requested) => Some(requested.intersection(&declared).cloned().collect()),
+            None if declared.is_empty() => None,
+            None => Some(declared),
+        };
+
+        Self {
+            plugin_id,
+            event_types,
+        }
+    }
+
+    fn wants(&self, msg: &SseMessage) -> bool {
+        let Some(event_types) = &self.event_types else {
+            return true;
+        };
+
+        msg.event_type
+            .as_deref()
+            .is_some_and(|event_type| event_types.contains(event_type))
+    }
+}
+
+fn parse_event_types(raw: &str) -> HashSet<String> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn extract_event_type(data: &str) -> Option<String> {
+    let payload: serde_json::Value = serde_json::from_str(data).ok()?;
+    payload
+        .get("event_type")
+        .and_then(serde_json::Value::as_str)
+        .map(str::to_string)
 }
 
 struct ForwardHeaders {
     content_type: Option<String>,
     accept: Option<String>,
+    /// `X-YUYU-BOT-ID`：指定调用哪个 Bot 账号（账号 ID 或 QQ 号）
+    bot_id: Option<String>,
 }
 
 struct PluginAuth {
@@ -42,6 +121,10 @@ impl<'r> FromRequest<'r> for ForwardHeaders {
         Outcome::Success(ForwardHeaders {
             content_type: req.content_type().map(|ct| ct.to_string()),
             accept: req.headers().get_one("Accept").map(|v| v.to_string()),
+            bot_id: req
+                .headers()
+                .get_one("X-YUYU-BOT-ID")
+                .map(|v| v.to_string()),
         })
     }
 }
@@ -68,22 +151,210 @@ impl<'r> FromRequest<'r> for PluginAuth {
     }
 }
 
+/// SSE 重连时浏览器/客户端自动携带的 `Last-Event-ID` 请求头
+struct LastEventId(Option<u64>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for LastEventId {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let id = req
+            .headers()
+            .get_one("Last-Event-ID")
+            .and_then(|v| v.trim().parse::<u64>().ok());
+        Outcome::Success(LastEventId(id))
+    }
+}
+
+/// WebSocket 推送的事件 JSON 中附加的事件序号字段
+const WS_EVENT_ID_FIELD: &str = "yuyu_event_id";
+
+/// 在事件 JSON 中写入事件序号，非 JSON 对象的数据原样返回
+fn with_event_id(data: &str, id: u64) -> String {
+    match serde_json::from_str::<serde_json::Value>(data) {
+        Ok(serde_json::Value::Object(mut payload)) => {
+            payload.insert(WS_EVENT_ID_FIELD.to_string(), id.into());
+            serde_json::Value::Object(payload).to_string()
+        }
+        _ => data.to_string(),
+    }
+}
+
 pub struct MilkyApiProxy {
-    bot_config: Arc<RwLock<BotConfig>>,
+    bots: Arc<BotRegistry>,
     permission_config: Arc<RwLock<PermissionConfig>>,
-    client: reqwest::Client,
-    bot_state: Arc<crate::server::BotConnectionState>,
+    rate_limit_config: Arc<RwLock<RateLimitConfig>>,
+    rate_limiter: Arc<RateLimiter>,
+    cache_config: Arc<RwLock<ApiCacheConfig>>,
+    cache: Arc<ApiCache>,
+    audit_log: Arc<AuditLog>,
+}
+
+/// 补发缓冲区最多保留的事件数
+const EVENT_REPLAY_CAPACITY: usize = 1000;
+
+/// 最后一个订阅者断开后继续缓冲事件的时间，覆盖插件重启的间隙
+const CLIENT_IDLE_GRACE: Duration = Duration::from_secs(30);
+
+/// 最近推送过的事件，插件重连时按 `Last-Event-ID` 补发
+struct EventReplayBuffer {
+    next_id: u64,
+    events: VecDeque<SseMessage>,
+}
+
+impl EventReplayBuffer {
+    fn new() -> Self {
+        Self {
+            next_id: 1,
+            events: VecDeque::with_capacity(EVENT_REPLAY_CAPACITY),
+        }
+    }
+
+    /// 分配序号并写入缓冲区，缓冲区已满时丢弃最早的事件
+    fn push(&mut self, mut msg: SseMessage) -> SseMessage {
+        msg.id = self.next_id;
+        self.next_id += 1;
+
+        if self.events.len() >= EVENT_REPLAY_CAPACITY {
+            self.events.pop_front();
+        }
+        self.events.push_back(msg.clone());
+        msg
+    }
+
+    /// 返回需要补发的事件，以及实时事件的去重基线
+    ///
+    /// 序号不大于基线的实时事件已经补发过，或在订阅之前就已推送。基线取最后一个补发事件的序号；
+    /// 没有补发事件时取客户端的 `Last-Event-ID`，但不超过已分配的最大序号，
+    /// 避免框架重启后旧的序号把新事件全部过滤掉。
+    fn replay(&self, last_event_id: Option<u64>) -> (Vec<SseMessage>, u64) {
+        let latest = self.next_id - 1;
+        let Some(last_event_id) = last_event_id else {
+            return (Vec::new(), latest);
+        };
+
+        let missed = self.since(last_event_id);
+        let baseline = missed
+            .last()
+            .map_or(last_event_id.min(latest), |msg| msg.id);
+        (missed, baseline)
+    }
+
+    /// 返回序号大于 `last_event_id` 的事件
+    ///
+    /// `last_event_id` 比已分配的最大序号还大时说明框架重启过，序号已重新计数，
+    /// 此时补发缓冲区中的全部事件。
+    fn since(&self, last_event_id: u64) -> Vec<SseMessage> {
+        if last_event_id >= self.next_id {
+            return self.events.iter().cloned().collect();
+        }
+
+        if let Some(oldest) = self.events.front() {
+            if last_event_id + 1 < oldest.id {
+                log_warn!(
+                    "事件 {} 之后的部分事件已超出补发缓冲区，只能从 {} 开始补发",
+                    last_event_id,
+                    oldest.id
+                );
+            }
+        }
+
+        self.events
+            .iter()
+            .filter(|msg| msg.id > last_event_id)
+            .cloned()
+            .collect()
+    }
 }
 
 pub struct MilkyEventProxy {
-    bot_config: Arc<RwLock<BotConfig>>,
+    bots: Arc<BotRegistry>,
+    /// 处理插件通过 `/event` WebSocket 发来的 API 调用
+    api: Arc<MilkyApiProxy>,
     permission_config: Arc<RwLock<PermissionConfig>>,
-    client: reqwest::Client,
     tx: broadcast::Sender<SseMessage>,
     clients: AtomicUsize,
-    ws_tx: broadcast::Sender<String>,
+    ws_tx: broadcast::Sender<SseMessage>,
     ws_clients: AtomicUsize,
-    bot_state: Arc<crate::server::BotConnectionState>,
+    replay: std::sync::Mutex<EventReplayBuffer>,
+    /// 最后一个订阅者断开的时间
+    last_client_left: std::sync::Mutex<Option<Instant>>,
+}
+
+impl MilkyEventProxy {
+    fn has_clients(&self) -> bool {
+        self.clients.load(Ordering::SeqCst) > 0 || self.ws_clients.load(Ordering::SeqCst) > 0
+    }
+
+    /// 有订阅者，或最后一个订阅者刚断开不久
+    fn has_recent_clients(&self) -> bool {
+        if self.has_clients() {
+            return true;
+        }
+
+        self.last_client_left
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .is_some_and(|left| left.elapsed() < CLIENT_IDLE_GRACE)
+    }
+
+    /// 账号开启了 `keepEventStream` 时始终缓冲事件，否则只在有订阅者时缓冲
+    async fn wants_event(&self, bot_id: &str) -> bool {
+        if self.has_recent_clients() {
+            return true;
+        }
+
+        match self.bots.get(bot_id).await {
+            Some(bot) => bot.config.read().await.keep_event_stream,
+            None => false,
+        }
+    }
+
+    fn client_left(&self) {
+        *self
+            .last_client_left
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) = Some(Instant::now());
+    }
+
+    /// 为事件分配序号，写入补发缓冲区并推送给所有订阅者
+    fn publish(&self, msg: SseMessage) {
+        let mut replay = self.replay.lock().unwrap_or_else(|e| e.into_inner());
+        let msg = replay.push(msg);
+
+        // 在持有锁时推送，保证订阅时拿到的补发事件与实时事件之间不会遗漏
+        let _ = self.tx.send(msg.clone());
+        let _ = self.ws_tx.send(msg);
+    }
+
+    /// 订阅实时事件，并取出需要补发的事件和实时事件的去重基线
+    fn subscribe(
+        &self,
+        last_event_id: Option<u64>,
+        websocket: bool,
+    ) -> (Vec<SseMessage>, broadcast::Receiver<SseMessage>, u64) {
+        let replay = self.replay.lock().unwrap_or_else(|e| e.into_inner());
+        let rx = if websocket {
+            self.ws_tx.subscribe()
+        } else {
+            self.tx.subscribe()
+        };
+        let (missed, baseline) = replay.replay(last_event_id);
+        (missed, rx, baseline)
+    }
+}
+
+/// 主服务与 Milky 代理共享的状态
+pub struct MilkyProxyContext {
+    pub bots: Arc<BotRegistry>,
+    pub permission_config: Arc<RwLock<PermissionConfig>>,
+    pub rate_limit_config: Arc<RwLock<RateLimitConfig>>,
+    pub rate_limiter: Arc<RateLimiter>,
+    pub api_cache_config: Arc<RwLock<ApiCacheConfig>>,
+    pub api_cache: Arc<ApiCache>,
+    pub audit_log: Arc<AuditLog>,
+    pub plugin_manager: Arc<PluginManager>,
 }
 
 use rocket::{Ignite, Rocket};
@@ -92,10 +363,7 @@ use tokio::task::JoinHandle;
 pub async fn spawn_milky_proxy_servers(
     api_port: u16,
     event_port: u16,
-    bot_config: Arc<RwLock<BotConfig>>,
-    permission_config: Arc<RwLock<PermissionConfig>>,
-    plugin_manager: Arc<PluginManager>,
-    bot_state: Arc<crate::server::BotConnectionState>,
+    context: MilkyProxyContext,
 ) -> Result<
     (
         JoinHandle<Result<Rocket<Ignite>, rocket::Error>>,
@@ -103,27 +371,42 @@ pub async fn spawn_milky_proxy_servers(
     ),
     Box<dyn std::error::Error + Send + Sync>,
 > {
+    let MilkyProxyContext {
+        bots,
+        permission_config,
+        rate_limit_config,
+        rate_limiter,
+        api_cache_config,
+        api_cache,
+        audit_log,
+        plugin_manager,
+    } = context;
+
     let api_proxy = Arc::new(MilkyApiProxy {
-        bot_config: bot_config.clone(),
+        bots: bots.clone(),
         permission_config: permission_config.clone(),
-        client: reqwest::Client::builder().no_proxy().build()?,
-        bot_state: bot_state.clone(),
+        rate_limit_config,
+        rate_limiter,
+        cache_config: api_cache_config,
+        cache: api_cache,
+        audit_log,
     });
 
     let (tx, _) = broadcast::channel::<SseMessage>(2048);
-    let (ws_tx, _) = broadcast::channel::<String>(2048);
+    let (ws_tx, _) = broadcast::channel::<SseMessage>(2048);
     let event_proxy = Arc::new(MilkyEventProxy {
-        bot_config: bot_config.clone(),
+        bots,
+        api: api_proxy.clone(),
         permission_config: permission_config.clone(),
-        client: reqwest::Client::builder().no_proxy().build()?,
         tx,
         clients: AtomicUsize::new(0),
         ws_tx,
         ws_clients: AtomicUsize::new(0),
-        bot_state: bot_state.clone(),
+        replay: std::sync::Mutex::new(EventReplayBuffer::new()),
+        last_client_left: std::sync::Mutex::new(None),
     });
 
-    tokio::spawn(run_upstream_sse(event_proxy.clone()));
+    tokio::spawn(run_event_feed(event_proxy.clone()));
 
     // Prepare API Rocket
     let address: std::net::IpAddr = "127.0.0.1".parse().unwrap();
@@ -179,15 +462,44 @@ pub async fn spawn_milky_proxy_servers(
     Ok((api_handle, event_handle))
 }
 
-async fn should_forward_event(proxy: &MilkyEventProxy, data: &str) -> bool {
-    let Some(group_id) = extract_target_group_id_from_event(data) else {
+async fn should_forward_event(
+    proxy: &MilkyEventProxy,
+    manager: &PluginManager,
+    subscription: &EventSubscription,
+    msg: &SseMessage,
+) -> bool {
+    if !subscription.wants(msg) {
+        return false;
+    }
+
+    if !manager
+        .is_bot_allowed(&subscription.plugin_id, &msg.bot_id)
+        .await
+    {
+        return false;
+    }
+
+    if msg.group_id.is_none() && msg.user_id.is_none() {
         return true;
-    };
+    }
 
-    let permission_config = proxy.permission_config.read().await.clone();
-    is_group_allowed(&permission_config, group_id)
+    let permission_config = proxy.permission_config.read().await;
+    if let Some(group_id) = msg.group_id {
+        if !is_group_allowed(&permission_config, &subscription.plugin_id, group_id) {
+            return false;
+        }
+    }
+    if let Some(user_id) = msg.user_id {
+        if !is_user_allowed(&permission_config, &subscription.plugin_id, user_id) {
+            return false;
+        }
+    }
+    true
 }
 
+/// 触发限流时返回给插件的 `retcode`
+const RATE_LIMITED_RETCODE: i64 = 429;
+
 pub struct ProxyBytesResponse {
     status: Status,
     content_type: ContentType,
@@ -196,8 +508,12 @@ pub struct ProxyBytesResponse {
 
 impl ProxyBytesResponse {
     fn json_error(status: Status, message: &str) -> Self {
+        Self::json_error_with_retcode(status, -1, message)
+    }
+
+    fn json_error_with_retcode(status: Status, retcode: i64, message: &str) -> Self {
         let body = serde_json::to_vec(&serde_json::json!({
-            "retcode": -1,
+            "retcode": retcode,
             "data": message,
         }))
         .unwrap_or_else(|_| b"{\"retcode\":-1,\"data\":\"Internal server error\"}".to_vec());
@@ -210,6 +526,16 @@ impl ProxyBytesResponse {
     }
 }
 
+impl From<CachedResponse> for ProxyBytesResponse {
+    fn from(response: CachedResponse) -> Self {
+        Self {
+            status: response.status,
+            content_type: response.content_type,
+            body: response.body,
+        }
+    }
+}
+
 impl<'r> Responder<'r, 'static> for ProxyBytesResponse {
     fn respond_to(self, _: &'r Request<'_>) -> rocket::response::Result<'static> {
         Response::build()
@@ -227,14 +553,8 @@ async fn proxy_api(
     auth: PluginAuth,
     headers: ForwardHeaders,
     proxy: &State<Arc<MilkyApiProxy>>,
+    manager: &State<Arc<PluginManager>>,
 ) -> Result<ProxyBytesResponse, Status> {
-    if !proxy.bot_state.is_connected.load(Ordering::SeqCst) {
-        return Ok(ProxyBytesResponse::json_error(
-            Status::ServiceUnavailable,
-            "Bot not connected",
-        ));
-    }
-
     let body = data
         .open(4.mebibytes())
         .into_bytes()
@@ -242,22 +562,149 @@ async fn proxy_api(
         .map_err(|_| Status::BadRequest)?
         .value;
 
-    let plugin_id = auth.plugin_id.clone();
+    call_api(api, body, &auth.plugin_id, headers, proxy, manager).await
+}
 
-    if let Some(group_id) = match extract_target_group_id_from_api(api, &body) {
+/// 转发一次 API 调用并写入审计记录，HTTP 与 WebSocket 调用共用
+async fn call_api(
+    api: &str,
+    body: Vec<u8>,
+    plugin_id: &str,
+    headers: ForwardHeaders,
+    proxy: &MilkyApiProxy,
+    manager: &PluginManager,
+) -> Result<ProxyBytesResponse, Status> {
+    let started_at = Instant::now();
+    let mut audit = AuditRecord::new(plugin_id, api);
+
+    let result = forward_api(api, body, plugin_id, headers, proxy, manager, &mut audit).await;
+
+    audit.latency_ms = started_at.elapsed().as_millis() as u64;
+    match &result {
+        Ok(response) => {
+            audit.status = response.status.code;
+            let payload = serde_json::from_slice::<serde_json::Value>(&response.body).ok();
+            audit.retcode = payload
+                .as_ref()
+                .and_then(|p| p.get("retcode"))
+                .and_then(serde_json::Value::as_i64);
+            if audit.retcode != Some(0) || !response.status.class().is_success() {
+                audit.message = payload.as_ref().and_then(|p| {
+                    p.get("message")
+                        .or_else(|| p.get("data"))
+                        .and_then(serde_json::Value::as_str)
+                        .map(str::to_string)
+                });
+            }
+        }
+        Err(status) => {
+            audit.status = status.code;
+            audit.message = Some(status.reason_lossy().to_string());
+        }
+    }
+    proxy.audit_log.record(audit);
+
+    result
+}
+
+/// 按 `X-YUYU-BOT-ID` 请求头选择账号，未指定时优先使用插件可用且已连接的账号
+async fn select_bot(
+    proxy: &MilkyApiProxy,
+    manager: &PluginManager,
+    plugin_id: &str,
+    requested: Option<&str>,
+) -> Result<Arc<BotConnectionState>, (Status, String)> {
+    let bindings = manager
+        .get_plugin_bot_bindings(plugin_id)
+        .await
+        .unwrap_or_default();
+    let is_bound = |bot_id: &str| bindings.is_empty() || bindings.iter().any(|id| id == bot_id);
+
+    if let Some(requested) = requested.map(str::trim).filter(|id| !id.is_empty()) {
+        let bot = proxy
+            .bots
+            .resolve(requested)
+            .await
+            .ok_or_else(|| (Status::NotFound, format!("Bot '{}' not found", requested)))?;
+        if !is_bound(&bot.id) {
+            return Err((
+                Status::Forbidden,
+                format!("Plugin is not bound to bot '{}'", bot.id),
+            ));
+        }
+        return Ok(bot);
+    }
+
+    // 优先使用已连接的账号，都未连接时仍返回第一个，由调用方报告未连接
+    let bound: Vec<_> = proxy
+        .bots
+        .list()
+        .await
+        .into_iter()
+        .filter(|bot| is_bound(&bot.id))
+        .collect();
+    bound
+        .iter()
+        .find(|bot| bot.is_connected.load(Ordering::SeqCst))
+        .or_else(|| bound.first())
+        .cloned()
+        .ok_or_else(|| {
+            (
+                Status::ServiceUnavailable,
+                "No bot account is available to this plugin".to_string(),
+            )
+        })
+}
+
+/// 校验权限与限流后把请求转发给 Bot，同时把调用目标写入审计记录
+async fn forward_api(
+    api: &str,
+    body: Vec<u8>,
+    plugin_id: &str,
+    headers: ForwardHeaders,
+    proxy: &MilkyApiProxy,
+    manager: &PluginManager,
+    audit: &mut AuditRecord,
+) -> Result<ProxyBytesResponse, Status> {
+    let bot = match select_bot(proxy, manager, plugin_id, headers.bot_id.as_deref()).await {
+        Ok(bot) => bot,
+        Err((status, message)) => {
+            log_warn!("Blocked plugin {} API {}: {}", plugin_id, api, message);
+            return Ok(ProxyBytesResponse::json_error(status, &message));
+        }
+    };
+    audit.bot_id = Some(bot.id.clone());
+
+    if !bot.is_connected.load(Ordering::SeqCst) {
+        return Ok(ProxyBytesResponse::json_error(
+            Status::ServiceUnavailable,
+            "Bot not connected",
+        ));
+    }
+
+    if let Err(message) = manager.check_api_permission(plugin_id, api).await {
+        log_warn!("Blocked plugin {} API {}: {}", plugin_id, api, message);
+        return Ok(ProxyBytesResponse::json_error(Status::Forbidden, &message));
+    }
+
+    let target_group_id = match extract_target_group_id_from_api(api, &body) {
         Ok(group_id) => group_id,
         Err(message) => {
             log_warn!("Blocked plugin {} API {}: {}", plugin_id, api, message);
             return Ok(ProxyBytesResponse::json_error(Status::BadRequest, &message));
         }
-    } {
+    };
+
+    audit.group_id = target_group_id;
+
+    if let Some(group_id) = target_group_id {
         let permission_config = proxy.permission_config.read().await.clone();
-        if !is_group_allowed(&permission_config, group_id) {
+        if !is_group_allowed(&permission_config, plugin_id, group_id) {
             let message = format!(
                 "Plugin is not allowed to use side-effect API '{}' for group {} in {} mode",
                 api,
                 group_id,
-                permission_mode_name(permission_config.mode),
+                permission_mode_name(permission_config.effective_mode(plugin_id)),
             );
             log_warn!(
                 "Blocked plugin {} API {} for group {}",
@@ -269,10 +716,108 @@ async fn proxy_api(
         }
     }
 
-    let config = proxy.bot_config.read().await.clone();
-    let url = format!("{}/{}", config.get_api_url(), api);
+    if let Some(target) = match extract_target_user_from_api(api, &body) {
+        Ok(target) => target,
+        Err(message) => {
+            log_warn!("Blocked plugin {} API {}: {}", plugin_id, api, message);
+            return Ok(ProxyBytesResponse::json_error(Status::BadRequest, &message));
+        }
+    } {
+        if let UserApiTarget::UserId(user_id) = &target {
+            audit.user_id = Some(*user_id);
+        }
+
+        let permission_config = proxy.permission_config.read().await.clone();
+        if permission_config.restricts_users(plugin_id) {
+            let user_id = match &target {
+                UserApiTarget::UserId(user_id) => Ok(*user_id),
+                UserApiTarget::FriendRequest {
+                    initiator_uid,
+                    is_filtered,
+                } => {
+                    let config = bot.config.read().await.clone();
+                    match bot.client.get(&config) {
+                        Ok(client) => {
+                            resolve_friend_request_initiator(
+                                &client,
+                                &config,
+                                initiator_uid,
+                                *is_filtered,
+                            )
+                            .await
+                        }
+                        Err(e) => Err(e),
+                    }
+                }
+            };
+
+            audit.user_id = user_id.as_ref().ok().copied();
+            let user_id = match user_id {
+                Ok(user_id) => user_id,
+                Err(e) => {
+                    let message = format!(
+                        "Unable to resolve the target user of side-effect API '{}': {}",
+                        api, e
+                    );
+                    log_warn!("Blocked plugin {} API {}: {}", plugin_id, api, message);
+                    return Ok(ProxyBytesResponse::json_error(Status::Forbidden, &message));
+                }
+            };
+
+            if !is_user_allowed(&permission_config, plugin_id, user_id) {
+                let message = format!(
+                    "Plugin is not allowed to use side-effect API '{}' for user {} in {} mode",
+                    api,
+                    user_id,
+                    permission_mode_name(permission_config.effective_user_mode(plugin_id)),
+                );
+                log_warn!(
+                    "Blocked plugin {} API {} for user {}",
+                    plugin_id,
+                    api,
+                    user_id
+                );
+                return Ok(ProxyBytesResponse::json_error(Status::Forbidden, &message));
+            }
+        }
+    }
+
+    let cache_config = proxy.cache_config.read().await.clone();
+    let cache_key = ApiCallKey::new(&bot.id, api, &body);
+    // 命中缓存不会访问上游，不消耗限流令牌
+    if let Some(cached) = proxy.cache.get(&cache_config, &cache_key).await {
+        return Ok(cached.into());
+    }
 
-    let mut builder = proxy.client.post(url);
+    let rate_limit_config = proxy.rate_limit_config.read().await.clone();
+    if let Err(exceeded) = proxy
+        .rate_limiter
+        .try_acquire(&rate_limit_config, plugin_id, api, target_group_id)
+        .await
+    {
+        log_warn!(
+            "Rate limited plugin {} API {}: {}",
+            plugin_id,
+            api,
+            exceeded
+        );
+        return Ok(ProxyBytesResponse::json_error_with_retcode(
+            Status::TooManyRequests,
+            RATE_LIMITED_RETCODE,
+            &exceeded.to_string(),
+        ));
+    }
+
+    let config = bot.config.read().await.clone();
+    let client = match bot.client.get(&config) {
+        Ok(client) => client,
+        Err(e) => {
+            log_error!("Failed to build upstream client: {}", e);
+            return Ok(ProxyBytesResponse::json_error(Status::BadGateway, &e));
+        }
+    };
+
+    let mut builder = client.post(format!("{}/{}", config.get_api_url(), api));
 
     if let Some(ct) = headers.content_type {
         builder = builder.header("Content-Type", ct);
@@ -288,13 +833,23 @@ async fn proxy_api(
         builder = builder.header("Authorization", format!("Bearer {}", token));
     }
 
-    builder = builder.header("X-YUYU-PLUGIN-ID", plugin_id);
+    builder = builder.header("X-YUYU-PLUGIN-ID", plugin_id).body(body);
 
-    let response = builder
-        .body(body)
-        .send()
-        .await
-        .map_err(|_| Status::BadGateway)?;
+    // 相同的只读调用同时到达时只访问一次上游，结果由 coalesce 写入缓存
+    let response = if is_read_only_api(api) {
+        proxy
+            .cache
+            .coalesce(cache_config, cache_key, send_upstream(builder))
+            .await?
+    } else {
+        send_upstream(builder).await?
+    };
+
+    Ok(response.into())
+}
+
+async fn send_upstream(builder: reqwest::RequestBuilder) -> Result<CachedResponse, Status> {
+    let response = builder.send().await.map_err(|_| Status::BadGateway)?;
 
     let status = Status::new(response.status().as_u16());
     let content_type = response
@@ -310,19 +865,38 @@ async fn proxy_api(
         .map_err(|_| Status::BadGateway)?
         .to_vec();
 
-    Ok(ProxyBytesResponse {
+    Ok(CachedResponse {
         status,
         content_type,
         body: bytes,
     })
 }
 
-#[get("/event", rank = 1, format = "text/event-stream")]
+fn sse_event(msg: SseMessage) -> Event {
+    let mut ev = Event::data(format!(" {}", msg.data)).id(msg.id.to_string());
+    if let Some(name) = msg.event {
+        ev = ev.event(name);
+    }
+    ev
+}
+
+#[get(
+    "/event?<events>&<last_event_id>",
+    rank = 1,
+    format = "text/event-stream"
+)]
 fn event_stream(
-    _auth: PluginAuth,
+    events: Option<&str>,
+    last_event_id: Option<u64>,
+    header_last_event_id: LastEventId,
+    auth: PluginAuth,
     proxy: &State<Arc<MilkyEventProxy>>,
+    manager: &State<Arc<PluginManager>>,
 ) -> EventStream![Event + 'static] {
     let proxy = proxy.inner().clone();
+    let manager = manager.inner().clone();
+    let requested = events.map(str::to_string);
+    let last_event_id = header_last_event_id.0.or(last_event_id);
 
     EventStream! {
         proxy.clients.fetch_add(1, Ordering::SeqCst);
@@ -330,49 +904,197 @@ fn event_stream(
         impl Drop for Guard {
             fn drop(&mut self) {
                 self.0.clients.fetch_sub(1, Ordering::SeqCst);
+                self.0.client_left();
             }
         }
         let _guard = Guard(proxy.clone());
 
-        let mut rx = proxy.tx.subscribe();
+        let subscription = EventSubscription::new(&manager, auth.plugin_id, requested).await;
+        let (missed, mut rx, mut last_sent) = proxy.subscribe(last_event_id, false);
+
+        for msg in missed {
+            if should_forward_event(&proxy, &manager, &subscription, &msg).await {
+                yield sse_event(msg);
+            }
+        }
+
         while let Ok(msg) = rx.recv().await {
-            let mut ev = Event::data(format!(" {}", msg.data));
-            if let Some(name) = msg.event {
-                ev = ev.event(name);
+            // 补发过的事件可能同时出现在实时队列中
+            if msg.id <= last_sent {
+                continue;
+            }
+            last_sent = msg.id;
+            if !should_forward_event(&proxy, &manager, &subscription, &msg).await {
+                continue;
             }
-            yield ev;
+
+            yield sse_event(msg);
         }
     }
 }
 
-#[get("/event", rank = 2)]
+/// 插件通过 `/event` WebSocket 发送的 API 调用
+#[derive(Deserialize)]
+struct WsActionFrame {
+    action: String,
+    #[serde(default)]
+    params: serde_json::Value,
+    /// 原样写回响应，供插件匹配请求与响应
+    #[serde(default)]
+    echo: serde_json::Value,
+    /// 指定调用的 Bot 账号，未指定时使用建立连接时的 `X-YUYU-BOT-ID`
+    #[serde(default)]
+    bot_id: Option<String>,
+}
+
+fn is_valid_action(action: &str) -> bool {
+    !action.is_empty()
+        && action
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn ws_action_reply(mut payload: serde_json::Value, echo: serde_json::Value) -> String {
+    if let serde_json::Value::Object(map) = &mut payload {
+        map.insert("echo".to_string(), echo);
+    }
+    payload.to_string()
+}
+
+/// 执行一条 WebSocket API 调用，返回带有相同 `echo` 的响应帧
+///
+/// 与 `/api/<api>` 走同一套权限、限流与审计逻辑。
+async fn handle_ws_action(
+    text: &str,
+    plugin_id: &str,
+    default_bot_id: Option<String>,
+    proxy: &MilkyApiProxy,
+    manager: &PluginManager,
+) -> String {
+    let frame = match serde_json::from_str::<WsActionFrame>(text) {
+        Ok(frame) => frame,
+        Err(e) => {
+            return ws_action_reply(
+                serde_json::json!({
+                    "retcode": -1,
+                    "data": format!("Invalid action frame: {}", e),
+                }),
+                serde_json::Value::Null,
+            );
+        }
+    };
+
+    if !is_valid_action(&frame.action) {
+        return ws_action_reply(
+            serde_json::json!({
+                "retcode": -1,
+                "data": format!("Invalid action '{}'", frame.action),
+            }),
+            frame.echo,
+        );
+    }
+
+    let params = if frame.params.is_null() {
+        serde_json::json!({})
+    } else {
+        frame.params
+    };
+    let body = serde_json::to_vec(&params).unwrap_or_default();
+    let headers = ForwardHeaders {
+        content_type: None,
+        accept: None,
+        bot_id: frame.bot_id.or(default_bot_id),
+    };
+
+    let payload = match call_api(&frame.action, body, plugin_id, headers, proxy, manager).await {
+        Ok(response) => serde_json::from_slice::<serde_json::Value>(&response.body)
+            .ok()
+            .filter(serde_json::Value::is_object)
+            .unwrap_or_else(|| {
+                serde_json::json!({
+                    "retcode": -1,
+                    "data": String::from_utf8_lossy(&response.body),
+                })
+            }),
+        Err(status) => serde_json::json!({
+            "retcode": -1,
+            "data": status.reason_lossy(),
+        }),
+    };
+
+    ws_action_reply(payload, frame.echo)
+}
+
+#[get("/event?<events>&<last_event_id>", rank = 2)]
 fn event_ws(
     ws: rocket_ws::WebSocket,
-    _auth: PluginAuth,
+    events: Option<&str>,
+    last_event_id: Option<u64>,
+    auth: PluginAuth,
+    headers: ForwardHeaders,
     proxy: &State<Arc<MilkyEventProxy>>,
+    manager: &State<Arc<PluginManager>>,
 ) -> rocket_ws::Channel<'static> {
     let proxy = proxy.inner().clone();
+    let api_proxy = proxy.api.clone();
+    let manager = manager.inner().clone();
+    let default_bot_id = headers.bot_id;
+    let requested = events.map(str::to_string);
     proxy.ws_clients.fetch_add(1, Ordering::SeqCst);
 
     struct Guard(Arc<MilkyEventProxy>);
     impl Drop for Guard {
         fn drop(&mut self) {
             self.0.ws_clients.fetch_sub(1, Ordering::SeqCst);
+            self.0.client_left();
         }
     }
     let guard = Guard(proxy.clone());
+    let plugin_id = auth.plugin_id;
 
     ws.channel(move |mut stream| {
         Box::pin(async move {
             use rocket::futures::{SinkExt, StreamExt};
 
-            let mut rx = proxy.ws_tx.subscribe();
+            let subscription = EventSubscription::new(&manager, plugin_id, requested).await;
+            let (missed, mut rx, mut last_sent) = proxy.subscribe(last_event_id, true);
             let mut inbound_closed = false;
+            let (reply_tx, mut reply_rx) = tokio::sync::mpsc::channel::<String>(64);
 
-            loop {
+            for msg in missed {
+                if !should_forward_event(&proxy, &manager, &subscription, &msg).await {
+                    continue;
+                }
+                let text = with_event_id(&msg.data, msg.id);
+                if stream.send(rocket_ws::Message::Text(text)).await.is_err() {
+                    inbound_closed = true;
+                    break;
+                }
+            }
+
+            while !inbound_closed {
                 tokio::select! {
                     msg = stream.next(), if !inbound_closed => {
                         match msg {
+                            Some(Ok(rocket_ws::Message::Text(text))) => {
+                                // API 调用可能较慢，放到单独的任务中执行，避免阻塞事件推送
+                                let reply_tx = reply_tx.clone();
+                                let api_proxy = api_proxy.clone();
+                                let manager = manager.clone();
+                                let plugin_id = subscription.plugin_id.clone();
+                                let default_bot_id = default_bot_id.clone();
+                                tokio::spawn(async move {
+                                    let reply = handle_ws_action(
+                                        &text,
+                                        &plugin_id,
+                                        default_bot_id,
+                                        &api_proxy,
+                                        &manager,
+                                    )
+                                    .await;
+                                    let _ = reply_tx.send(reply).await;
+                                });
+                            }
                             Some(Ok(m)) => {
                                 if m.is_close() {
                                     inbound_closed = true;
@@ -383,9 +1105,22 @@ fn event_ws(
                             }
                         }
                     }
+                    Some(reply) = reply_rx.recv() => {
+                        if stream.send(rocket_ws::Message::Text(reply)).await.is_err() {
+                            break;
+                        }
+                    }
                     msg = rx.recv() => {
                         match msg {
-                            Ok(text) => {
+                            Ok(msg) => {
+                                if msg.id <= last_sent {
+                                    continue;
+                                }
+                                last_sent = msg.id;
+                                if !should_forward_event(&proxy, &manager, &subscription, &msg).await {
+                                    continue;
+                                }
+                                let text = with_event_id(&msg.data, msg.id);
                                 if stream.send(rocket_ws::Message::Text(text)).await.is_err() {
                                     break;
                                 }
@@ -394,10 +1129,6 @@ fn event_ws(
                         }
                     }
                 }
-
-                if inbound_closed {
-                    break;
-                }
             }
 
             drop(guard);
@@ -406,122 +1137,44 @@ fn event_ws(
     })
 }
 
-async fn run_upstream_sse(proxy: Arc<MilkyEventProxy>) {
-    loop {
-        // 检查是否应该连接
-        if !proxy.bot_state.should_connect.load(Ordering::SeqCst)
-            || !proxy.bot_state.is_connected.load(Ordering::SeqCst)
-        {
-            tokio::time::sleep(std::time::Duration::from_millis(200)).await;
-            continue;
-        }
-
-        if proxy.clients.load(Ordering::SeqCst) == 0 && proxy.ws_clients.load(Ordering::SeqCst) == 0
-        {
-            tokio::time::sleep(std::time::Duration::from_millis(200)).await;
-            continue;
-        }
-
-        let config = proxy.bot_config.read().await.clone();
-        let url = config.get_event_url();
-
-        let mut request_builder = proxy
-            .client
-            .get(&url)
-            .header("Accept", "text/event-stream")
-            .header("Cache-Control", "no-cache");
-
-        if let Some(token) = config.token {
-            request_builder = request_builder.header("Authorization", format!("Bearer {}", token));
-        }
+/// 把各账号连接收到的事件写入补发缓冲区并推送给插件
+///
+/// 事件来自账号自身的上游连接，只要账号保持连接就不会因为插件重启而中断。
+async fn run_event_feed(proxy: Arc<MilkyEventProxy>) {
+    let mut rx = proxy.bots.subscribe_events();
 
-        let response = match request_builder.send().await {
-            Ok(r) => r,
-            Err(_) => {
-                tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+    loop {
+        let event = match rx.recv().await {
+            Ok(event) => event,
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                log_warn!("Milky 事件代理处理过慢，丢弃了 {} 条事件", skipped);
                 continue;
             }
+            Err(broadcast::error::RecvError::Closed) => break,
         };
 
-        if !response.status().is_success() {
-            tokio::time::sleep(std::time::Duration::from_secs(2)).await;
-            continue;
-        }
-
-        let mut current_event: Option<String> = None;
-        let mut data_lines: Vec<String> = Vec::new();
-        let mut buffer = String::new();
-        let mut stream = response.bytes_stream();
-
-        while let Some(chunk) = stream.next().await {
-            // 检查是否应该断开连接
-            if !proxy.bot_state.should_connect.load(Ordering::SeqCst)
-                || !proxy.bot_state.is_connected.load(Ordering::SeqCst)
-            {
-                break;
-            }
-
-            if proxy.clients.load(Ordering::SeqCst) == 0
-                && proxy.ws_clients.load(Ordering::SeqCst) == 0
-            {
-                break;
-            }
-
-            let bytes = match chunk {
-                Ok(b) => b,
-                Err(_) => break,
-            };
-
-            let text = match std::str::from_utf8(&bytes) {
-                Ok(t) => t,
-                Err(_) => continue,
-            };
-
-            buffer.push_str(text);
-
-            while let Some(pos) = buffer.find('\n') {
-                let mut line = buffer[..pos].to_string();
-                buffer.drain(..=pos);
-
-                if line.ends_with('\r') {
-                    line.pop();
-                }
-
-                if line.is_empty() {
-                    if data_lines.is_empty() && current_event.is_none() {
-                        continue;
-                    }
+        let event_type = extract_event_type(&event.data);
+        let group_id = extract_target_group_id_from_event(&event.data);
+        proxy
+            .api
+            .cache
+            .invalidate_for_event(&event.bot_id, event_type.as_deref(), group_id)
+            .await;
 
-                    let data = data_lines.join("\n");
-                    if !should_forward_event(&proxy, &data).await {
-                        current_event = None;
-                        data_lines.clear();
-                        continue;
-                    }
-
-                    let _ = proxy.tx.send(SseMessage {
-                        event: current_event.clone(),
-                        data: data.clone(),
-                    });
-                    let _ = proxy.ws_tx.send(data);
-                    current_event = None;
-                    data_lines.clear();
-                    continue;
-                }
-
-                if let Some(v) = line.strip_prefix("event:") {
-                    current_event = Some(v.trim().to_string());
-                    continue;
-                }
-
-                if let Some(v) = line.strip_prefix("data:") {
-                    data_lines.push(v.trim_start().to_string());
-                    continue;
-                }
-            }
+        if !proxy.wants_event(&event.bot_id).await {
+            continue;
         }
 
-        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+        let data = event.data;
+        proxy.publish(SseMessage {
+            id: 0,
+            bot_id: event.bot_id,
+            event: event.event,
+            event_type,
+            group_id,
+            user_id: extract_target_user_id_from_event(&data),
+            data,
+        });
     }
 }
 
@@ -552,3 +1205,83 @@ fn extract_access_token(req: &Request<'_>) -> String {
 
     String::new()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(data: &str) -> SseMessage {
+        SseMessage {
+            id: 0,
+            bot_id: "bot".to_string(),
+            event: None,
+            data: data.to_string(),
+            event_type: None,
+            group_id: None,
+            user_id: None,
+        }
+    }
+
+    fn ids(events: &[SseMessage]) -> Vec<u64> {
+        events.iter().map(|msg| msg.id).collect()
+    }
+
+    #[test]
+    fn replay_returns_events_after_last_event_id() {
+        let mut buffer = EventReplayBuffer::new();
+        for i in 0..5 {
+            buffer.push(event(&i.to_string()));
+        }
+
+        let (missed, baseline) = buffer.replay(Some(3));
+        assert_eq!(ids(&missed), vec![4, 5]);
+        assert_eq!(baseline, 5);
+
+        let (missed, baseline) = buffer.replay(Some(5));
+        assert!(missed.is_empty());
+        assert_eq!(baseline, 5);
+    }
+
+    #[test]
+    fn replay_without_last_event_id_skips_history() {
+        let mut buffer = EventReplayBuffer::new();
+        buffer.push(event("a"));
+        buffer.push(event("b"));
+
+        let (missed, baseline) = buffer.replay(None);
+        assert!(missed.is_empty());
+        assert_eq!(baseline, 2);
+    }
+
+    #[test]
+    fn replay_after_restart_with_empty_buffer_does_not_filter_new_events() {
+        let buffer = EventReplayBuffer::new();
+
+        let (missed, baseline) = buffer.replay(Some(500));
+        assert!(missed.is_empty());
+        assert_eq!(baseline, 0);
+    }
+
+    #[test]
+    fn replay_after_restart_returns_whole_buffer() {
+        let mut buffer = EventReplayBuffer::new();
+        buffer.push(event("a"));
+        buffer.push(event("b"));
+
+        let (missed, baseline) = buffer.replay(Some(500));
+        assert_eq!(ids(&missed), vec![1, 2]);
+        assert_eq!(baseline, 2);
+    }
+
+    #[test]
+    fn push_drops_oldest_event_when_full() {
+        let mut buffer = EventReplayBuffer::new();
+        for i in 0..EVENT_REPLAY_CAPACITY + 2 {
+            buffer.push(event(&i.to_string()));
+        }
+
+        assert_eq!(buffer.events.len(), EVENT_REPLAY_CAPACITY);
+        let (missed, _) = buffer.replay(Some(0));
+        assert_eq!(missed.first().map(|msg| msg.id), Some(3));
+    }
+}
@@ -1,40 +1,75 @@
 pub mod api;
+pub mod api_cache;
+pub mod audit;
+pub mod bots;
+pub mod bus;
+pub mod kv;
 pub mod milky_proxy;
 pub mod permissions;
+pub mod rate_limit;
+pub mod rpc;
+pub mod upstream;
+
+pub use bots::{BotConnectionState, BotRegistry};
 
 use crate::plus::PluginManager;
 use crate::runtime;
+#[cfg(windows)]
 use crate::window::UserEvent;
 use rocket::fairing::AdHoc;
 #[cfg(debug_assertions)]
 use rocket::fs::NamedFile;
 #[cfg(not(debug_assertions))]
 use rocket::http::ContentType;
-use rocket::{get, routes, Config};
+use rocket::http::{Cookie, CookieJar, SameSite};
+use rocket::response::Redirect;
+use rocket::{get, routes, Config, State};
 use std::net::TcpListener;
 use std::path::Path;
-use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
+#[cfg(windows)]
 use tao::event_loop::EventLoopProxy;
-use tokio::sync::broadcast;
 use tokio::sync::RwLock;
 
 #[cfg(not(debug_assertions))]
 use crate::Assets;
 
+#[derive(Default)]
 pub struct MainProxy {
+    #[cfg(windows)]
     pub proxy: RwLock<Option<EventLoopProxy<UserEvent>>>,
 }
 
-pub struct BotConnectionState {
-    pub is_connected: AtomicBool,
-    pub is_connecting: AtomicBool,
-    pub should_connect: AtomicBool,
-    pub status_sender: broadcast::Sender<api::BotStatusResponse>,
-    pub connection_task: tokio::sync::Mutex<Option<tokio::task::JoinHandle<()>>>,
-    pub cancel_sender: tokio::sync::Mutex<Option<tokio::sync::oneshot::Sender<()>>>,
-    /// 配置文件写入锁，防止并发写入导致配置损坏
-    pub config_write_lock: tokio::sync::Mutex<()>,
+impl MainProxy {
+    /// 请求主窗口事件循环重启程序，无界面模式下没有事件循环，返回 `false`
+    pub async fn request_restart(&self) -> bool {
+        #[cfg(windows)]
+        if let Some(proxy) = &*self.proxy.read().await {
+            return proxy.send_event(UserEvent::RestartRequested).is_ok();
+        }
+
+        false
+    }
+}
+
+/// 带着会话令牌打开首页时写入 Cookie，再跳转到不含令牌的地址，避免令牌留在地址栏中
+#[get("/?<session>")]
+fn login(
+    session: &str,
+    webui_session: &State<Arc<api::WebUiSession>>,
+    cookies: &CookieJar<'_>,
+) -> Redirect {
+    if webui_session.verify(session) {
+        cookies.add(
+            Cookie::build((api::WebUiSession::COOKIE, webui_session.token().to_string()))
+                .http_only(true)
+                .same_site(SameSite::Strict)
+                .path("/"),
+        );
+    } else {
+        log_warn!("WebUI 会话令牌无效，请使用本次启动时给出的地址打开");
+    }
+    Redirect::to("/")
 }
 
 #[cfg(debug_assertions)]
@@ -76,7 +111,11 @@ fn serve_embedded_asset(path: &str) -> Option<(ContentType, Vec<u8>)> {
 
 pub struct ServerState {
     pub plugin_manager: Arc<PluginManager>,
+    /// 只有 Windows 的窗口和托盘会读取
+    #[cfg_attr(not(windows), allow(dead_code))]
     pub main_proxy: Arc<MainProxy>,
+    /// 主窗口和无界面模式用它生成带会话令牌的 WebUI 地址
+    pub webui_session: Arc<api::WebUiSession>,
 }
 
 pub fn start_server_safe() -> Result<(u16, Arc<ServerState>), String> {
@@ -84,6 +123,14 @@ pub fn start_server_safe() -> Result<(u16, Arc<ServerState>), String> {
 
     // 预先创建插件管理器，以便返回给 main 函数使用
     let exe_dir = runtime::get_exe_dir();
+    crate::logger::set_log_levels(
+        api::load_system_config_from_disk(&exe_dir)
+            .log_levels
+            .unwrap_or_default(),
+    );
+
+    // 整个进程共用一个会话令牌，服务器重试启动时不会让已打开的 WebUI 失效
+    let webui_session = Arc::new(api::WebUiSession::generate());
 
     // 使用全局 Runtime spawn 服务器任务，而不是创建新的 Runtime
     runtime::spawn(async move {
@@ -108,14 +155,18 @@ pub fn start_server_safe() -> Result<(u16, Arc<ServerState>), String> {
                 milky_api_port,
                 milky_event_port,
             ));
+            plugin_manager.set_output_retention(
+                api::load_system_config_from_disk(&exe_dir)
+                    .plugin_output_retention
+                    .unwrap_or_default(),
+            );
 
-            let main_proxy = Arc::new(MainProxy {
-                proxy: RwLock::new(None),
-            });
+            let main_proxy = Arc::new(MainProxy::default());
 
             let server_state = Arc::new(ServerState {
                 plugin_manager: plugin_manager.clone(),
                 main_proxy: main_proxy.clone(),
+                webui_session: webui_session.clone(),
             });
 
             let address = match "127.0.0.1".parse() {
@@ -132,21 +183,23 @@ pub fn start_server_safe() -> Result<(u16, Arc<ServerState>), String> {
                 plugins_root,
             });
 
-            let (status_sender, _) = broadcast::channel(100);
-            let bot_state = Arc::new(BotConnectionState {
-                is_connected: AtomicBool::new(false),
-                is_connecting: AtomicBool::new(false),
-                should_connect: AtomicBool::new(false),
-                status_sender,
-                connection_task: tokio::sync::Mutex::new(None),
-                cancel_sender: tokio::sync::Mutex::new(None),
-                config_write_lock: tokio::sync::Mutex::new(()),
-            });
-
-            let bot_config_state = Arc::new(RwLock::new(api::load_bot_config_from_disk(&exe_dir)));
+            let bot_registry =
+                Arc::new(BotRegistry::new(api::load_bot_configs_from_disk(&exe_dir)));
             let permission_config_state = Arc::new(RwLock::new(
                 permissions::load_permission_config_from_disk(&exe_dir),
             ));
+            let rate_limit_config_state = Arc::new(RwLock::new(
+                rate_limit::load_rate_limit_config_from_disk(&exe_dir),
+            ));
+            let rate_limiter = Arc::new(rate_limit::RateLimiter::default());
+            let api_cache_config_state = Arc::new(RwLock::new(
+                api_cache::load_api_cache_config_from_disk(&exe_dir),
+            ));
+            let api_cache = Arc::new(api_cache::ApiCache::default());
+            let audit_log = audit::AuditLog::new(exe_dir.join("data").join("audit"));
+            let plugin_bus = Arc::new(bus::PluginBus::default());
+            let plugin_rpc = Arc::new(rpc::PluginRpc::default());
+            let kv_store = Arc::new(kv::KvStore::new(exe_dir.join("data").join("kv")));
 
             let config = Config {
                 address,
@@ -162,12 +215,20 @@ pub fn start_server_safe() -> Result<(u16, Arc<ServerState>), String> {
 
             let main_rocket = rocket::custom(config)
                 .manage(system_info.clone())
-                .manage(bot_state.clone())
-                .manage(bot_config_state.clone())
+                .manage(bot_registry.clone())
                 .manage(permission_config_state.clone())
+                .manage(rate_limit_config_state.clone())
+                .manage(rate_limiter.clone())
+                .manage(api_cache_config_state.clone())
+                .manage(api_cache.clone())
+                .manage(audit_log.clone())
+                .manage(plugin_bus)
+                .manage(plugin_rpc)
+                .manage(kv_store)
                 .manage(plugin_manager.clone())
                 .manage(main_proxy.clone())
-                .mount("/", routes![index, assets, api::set_webui])
+                .manage(webui_session.clone())
+                .mount("/", routes![login, index, assets, api::set_webui])
                 .mount(
                     "/api",
                     routes![
@@ -181,8 +242,10 @@ pub fn start_server_safe() -> Result<(u16, Arc<ServerState>), String> {
                         api::open_plugins_dir,
                         api::restart_program,
                         api::get_app_info,
+                        api::list_bots,
                         api::get_bot_config,
                         api::save_bot_config,
+                        api::delete_bot,
                         api::disconnect_bot,
                         api::get_bot_status,
                         api::bot_status_stream,
@@ -194,17 +257,41 @@ pub fn start_server_safe() -> Result<(u16, Arc<ServerState>), String> {
                         api::export_plugin,
                         api::import_plugin,
                         api::get_plugin_output,
+                        api::search_plugin_output,
                         api::clear_plugin_output,
                         api::open_plugin_dir,
                         api::open_plugin_data_dir,
                         api::plugin_output_stream,
                         api::plugins_status_stream,
                         api::plugins_events_stream,
+                        api::approve_plugin_permissions,
+                        api::revoke_plugin_permissions,
+                        api::set_plugin_bots,
+                        api::plugin_ready,
                         api::get_ui_state,
                         api::save_ui_state,
                         permissions::get_permission_config,
                         permissions::get_permission_group_options,
-                        permissions::save_permission_config
+                        permissions::save_permission_config,
+                        rate_limit::get_rate_limit_config,
+                        rate_limit::save_rate_limit_config,
+                        api_cache::get_api_cache_config,
+                        api_cache::save_api_cache_config,
+                        api_cache::get_api_cache_stats,
+                        api_cache::clear_api_cache,
+                        audit::query_audit,
+                        audit::audit_stream,
+                        bus::bus_publish,
+                        bus::bus_subscribe,
+                        bus::bus_subscribe_ws,
+                        rpc::rpc_call,
+                        rpc::rpc_serve,
+                        kv::kv_get,
+                        kv::kv_set,
+                        kv::kv_delete,
+                        kv::kv_list,
+                        kv::list_kv_namespaces,
+                        kv::browse_kv
                     ],
                 )
                 .attach(AdHoc::on_liftoff("Get Port", move |rocket| {
@@ -237,10 +324,16 @@ pub fn start_server_safe() -> Result<(u16, Arc<ServerState>), String> {
             let _handles = match milky_proxy::spawn_milky_proxy_servers(
                 milky_api_port,
                 milky_event_port,
-                bot_config_state.clone(),
-                permission_config_state.clone(),
-                plugin_manager.clone(),
-                bot_state.clone(),
+                milky_proxy::MilkyProxyContext {
+                    bots: bot_registry.clone(),
+                    permission_config: permission_config_state.clone(),
+                    rate_limit_config: rate_limit_config_state.clone(),
+                    rate_limiter: rate_limiter.clone(),
+                    api_cache_config: api_cache_config_state.clone(),
+                    api_cache: api_cache.clone(),
+                    audit_log: audit_log.clone(),
+                    plugin_manager: plugin_manager.clone(),
+                },
             )
             .await
             {
@@ -258,6 +351,8 @@ pub fn start_server_safe() -> Result<(u16, Arc<ServerState>), String> {
                 log_error!("Failed to load plugins: {}", e);
             }
 
+            tokio::spawn(plugin_manager.clone().run_restart_supervisor());
+
             // 自动启动之前启用的插件
             let plugin_manager_for_auto_start = plugin_manager.clone();
             tokio::spawn(async move {
@@ -265,32 +360,18 @@ pub fn start_server_safe() -> Result<(u16, Arc<ServerState>), String> {
                 plugin_manager_for_auto_start.wait_for_port().await;
 
                 let enabled_plugins = plugin_manager_for_auto_start.get_enabled_plugins().await;
-                for plugin_id in enabled_plugins {
-                    let name = plugin_manager_for_auto_start
-                        .get_plugin_name(&plugin_id)
-                        .await
-                        .unwrap_or_else(|| plugin_id.clone());
-                    log_info!("Auto-starting plugin: {}({})", name, plugin_id);
-                    if let Err(e) = plugin_manager_for_auto_start.start_plugin(&plugin_id).await {
-                        if e == "Plugin not found"
-                            && plugin_manager_for_auto_start
-                                .purge_enabled_plugin_if_absent(&plugin_id)
-                                .await
-                        {
-                            continue;
-                        }
-                        log_error!("Failed to auto-start plugin {}({}): {}", name, plugin_id, e);
-                    }
-                }
+                plugin_manager_for_auto_start
+                    .start_plugins_in_order(enabled_plugins)
+                    .await;
             });
 
             // 检查并执行自动连接
-            let bot_state_for_auto_connect = bot_state.clone();
+            let bot_registry_for_auto_connect = bot_registry.clone();
             let plugin_manager_for_auto_connect = plugin_manager.clone();
             tokio::spawn(async move {
                 // 等待服务器端口就绪
                 plugin_manager_for_auto_connect.wait_for_port().await;
-                api::check_and_auto_connect(bot_state_for_auto_connect).await;
+                api::check_and_auto_connect(bot_registry_for_auto_connect).await;
             });
 
             // Launch Main Server
@@ -304,3 +385,70 @@ pub fn start_server_safe() -> Result<(u16, Arc<ServerState>), String> {
 
     rx.recv().map_err(|e| format!("接收服务器端口失败: {}", e))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rocket::http::Status;
+    use rocket::local::asynchronous::Client;
+    use rocket::post;
+
+    #[post("/guarded")]
+    fn guarded(_webui: api::WebUiCaller) -> &'static str {
+        "ok"
+    }
+
+    async fn client(session: &Arc<api::WebUiSession>) -> Client {
+        let rocket = rocket::build()
+            .manage(session.clone())
+            .mount("/", routes![login, guarded]);
+        Client::tracked(rocket).await.expect("valid rocket")
+    }
+
+    #[rocket::async_test]
+    async fn webui_routes_require_session_cookie() {
+        let session = Arc::new(api::WebUiSession::generate());
+        let client = client(&session).await;
+
+        let response = client.post("/guarded").dispatch().await;
+        assert_eq!(response.status(), Status::Unauthorized);
+
+        let response = client
+            .post("/guarded")
+            .cookie(Cookie::new(api::WebUiSession::COOKIE, "forged"))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Unauthorized);
+
+        let response = client
+            .post("/guarded")
+            .header(rocket::http::Header::new("Authorization", "Bearer guess"))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Unauthorized);
+    }
+
+    #[rocket::async_test]
+    async fn login_url_sets_session_cookie() {
+        let session = Arc::new(api::WebUiSession::generate());
+        let client = client(&session).await;
+
+        let response = client
+            .get(format!("/?session={}", "0".repeat(64)))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::SeeOther);
+        assert!(response.cookies().get(api::WebUiSession::COOKIE).is_none());
+        let response = client.post("/guarded").dispatch().await;
+        assert_eq!(response.status(), Status::Unauthorized);
+
+        let login_url = session.login_url(0);
+        let query = login_url.trim_start_matches("http://127.0.0.1:0");
+        let response = client.get(query).dispatch().await;
+        assert_eq!(response.status(), Status::SeeOther);
+        assert_eq!(response.headers().get_one("Location"), Some("/"));
+
+        let response = client.post("/guarded").dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+    }
+}
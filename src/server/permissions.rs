@@ -1,8 +1,10 @@
 use crate::runtime;
-use crate::server::api::{ApiResponse, BotConfig};
+use crate::server::api::{ApiResponse, BotConfig, WebUiCaller};
+use crate::server::BotRegistry;
 use rocket::{get, post, serde::json::Json, State};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::HashMap;
 use std::path::Path;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
@@ -26,6 +28,33 @@ pub struct PermissionConfig {
     pub blacklist_groups: Vec<u64>,
     #[serde(default)]
     pub whitelist_groups: Vec<u64>,
+    /// 私聊（好友/临时会话）对象的名单模式
+    #[serde(default)]
+    pub user_mode: PermissionMode,
+    #[serde(default)]
+    pub blacklist_users: Vec<u64>,
+    #[serde(default)]
+    pub whitelist_users: Vec<u64>,
+    /// 按插件 ID 单独设置的群/用户规则，存在时完全替代全局规则
+    #[serde(default)]
+    pub plugin_overrides: HashMap<String, PluginPermissionOverride>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginPermissionOverride {
+    #[serde(default)]
+    pub mode: PermissionMode,
+    #[serde(default)]
+    pub blacklist_groups: Vec<u64>,
+    #[serde(default)]
+    pub whitelist_groups: Vec<u64>,
+    #[serde(default)]
+    pub user_mode: PermissionMode,
+    #[serde(default)]
+    pub blacklist_users: Vec<u64>,
+    #[serde(default)]
+    pub whitelist_users: Vec<u64>,
 }
 
 #[derive(Debug, Serialize, Clone)]
@@ -47,10 +76,46 @@ pub struct PermissionGroupOptionsData {
 
 impl PermissionConfig {
     pub fn normalized(mut self) -> Self {
-        normalize_group_ids(&mut self.blacklist_groups);
-        normalize_group_ids(&mut self.whitelist_groups);
+        normalize_ids(&mut self.blacklist_groups);
+        normalize_ids(&mut self.whitelist_groups);
+        normalize_ids(&mut self.blacklist_users);
+        normalize_ids(&mut self.whitelist_users);
+        self.plugin_overrides
+            .retain(|plugin_id, _| !plugin_id.trim().is_empty());
+        for rule in self.plugin_overrides.values_mut() {
+            normalize_ids(&mut rule.blacklist_groups);
+            normalize_ids(&mut rule.whitelist_groups);
+            normalize_ids(&mut rule.blacklist_users);
+            normalize_ids(&mut rule.whitelist_users);
+        }
         self
     }
+
+    /// 插件实际生效的权限模式（有单独规则时使用单独规则）
+    pub fn effective_mode(&self, plugin_id: &str) -> PermissionMode {
+        self.plugin_overrides
+            .get(plugin_id)
+            .map(|rule| rule.mode)
+            .unwrap_or(self.mode)
+    }
+
+    /// 插件实际生效的私聊对象名单模式
+    pub fn effective_user_mode(&self, plugin_id: &str) -> PermissionMode {
+        self.plugin_overrides
+            .get(plugin_id)
+            .map(|rule| rule.user_mode)
+            .unwrap_or(self.user_mode)
+    }
+
+    /// 插件的私聊规则是否会拒绝某些用户（黑名单为空时放行所有用户）
+    pub fn restricts_users(&self, plugin_id: &str) -> bool {
+        match self.plugin_overrides.get(plugin_id) {
+            Some(rule) => {
+                rule.user_mode == PermissionMode::Whitelist || !rule.blacklist_users.is_empty()
+            }
+            None => self.user_mode == PermissionMode::Whitelist || !self.blacklist_users.is_empty(),
+        }
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -59,7 +124,26 @@ enum ProtectedGroupApiKind {
     GroupScenePeerId,
 }
 
-fn normalize_group_ids(groups: &mut Vec<u64>) {
+#[derive(Clone, Copy)]
+enum ProtectedUserApiKind {
+    UserId,
+    PrivateScenePeerId,
+    FriendRequestUid,
+}
+
+/// 私聊类副作用 API 的目标用户
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UserApiTarget {
+    /// 已知 QQ 号
+    UserId(u64),
+    /// 好友请求只携带发起者的 uid，需要向 Bot 查询对应的 QQ 号
+    FriendRequest {
+        initiator_uid: String,
+        is_filtered: bool,
+    },
+}
+
+fn normalize_ids(groups: &mut Vec<u64>) {
     groups.sort_unstable();
     groups.dedup();
 }
@@ -102,6 +186,26 @@ fn protected_group_api_kind(api: &str) -> Option<ProtectedGroupApiKind> {
     }
 }
 
+fn protected_user_api_kind(api: &str) -> Option<ProtectedUserApiKind> {
+    match api {
+        "delete_friend"
+        | "recall_private_message"
+        | "send_friend_nudge"
+        | "send_private_message"
+        | "send_profile_like"
+        | "upload_private_file" => Some(ProtectedUserApiKind::UserId),
+        "mark_message_as_read" | "set_peer_pin" => Some(ProtectedUserApiKind::PrivateScenePeerId),
+        "accept_friend_request" | "reject_friend_request" => {
+            Some(ProtectedUserApiKind::FriendRequestUid)
+        }
+        _ => None,
+    }
+}
+
+fn is_private_scene(scene: &str) -> bool {
+    scene == "friend" || scene == "temp"
+}
+
 pub fn load_permission_config_from_disk(exe_dir: &Path) -> PermissionConfig {
     let config_file = permission_config_path(exe_dir);
 
@@ -153,6 +257,57 @@ pub fn extract_target_group_id_from_api(api: &str, body: &[u8]) -> Result<Option
     }
 }
 
+pub fn extract_target_user_from_api(
+    api: &str,
+    body: &[u8],
+) -> Result<Option<UserApiTarget>, String> {
+    let Some(kind) = protected_user_api_kind(api) else {
+        return Ok(None);
+    };
+
+    let payload: Value = serde_json::from_slice(body)
+        .map_err(|_| format!("Protected API '{}' requires a valid JSON body", api))?;
+
+    match kind {
+        ProtectedUserApiKind::UserId => extract_u64_field(&payload, "user_id")
+            .map(|user_id| Some(UserApiTarget::UserId(user_id)))
+            .ok_or_else(|| format!("Protected API '{}' is missing a valid user_id", api)),
+        ProtectedUserApiKind::PrivateScenePeerId => {
+            let scene = payload
+                .get("message_scene")
+                .and_then(Value::as_str)
+                .ok_or_else(|| {
+                    format!("Protected API '{}' is missing a valid message_scene", api)
+                })?;
+
+            if !is_private_scene(scene) {
+                return Ok(None);
+            }
+
+            extract_u64_field(&payload, "peer_id")
+                .map(|user_id| Some(UserApiTarget::UserId(user_id)))
+                .ok_or_else(|| format!("Protected API '{}' is missing a valid peer_id", api))
+        }
+        ProtectedUserApiKind::FriendRequestUid => {
+            let initiator_uid = payload
+                .get("initiator_uid")
+                .and_then(Value::as_str)
+                .filter(|uid| !uid.trim().is_empty())
+                .ok_or_else(|| {
+                    format!("Protected API '{}' is missing a valid initiator_uid", api)
+                })?;
+
+            Ok(Some(UserApiTarget::FriendRequest {
+                initiator_uid: initiator_uid.to_string(),
+                is_filtered: payload
+                    .get("is_filtered")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(false),
+            }))
+        }
+    }
+}
+
 pub fn extract_target_group_id_from_event(data: &str) -> Option<u64> {
     let payload: Value = serde_json::from_str(data).ok()?;
     let event_data = payload.get("data")?;
@@ -169,10 +324,62 @@ pub fn extract_target_group_id_from_event(data: &str) -> Option<u64> {
     extract_u64_field(event_data, "peer_id")
 }
 
-pub fn is_group_allowed(config: &PermissionConfig, group_id: u64) -> bool {
-    match config.mode {
-        PermissionMode::Blacklist => !config.blacklist_groups.contains(&group_id),
-        PermissionMode::Whitelist => config.whitelist_groups.contains(&group_id),
+pub fn extract_target_user_id_from_event(data: &str) -> Option<u64> {
+    let payload: Value = serde_json::from_str(data).ok()?;
+    let event_data = payload.get("data")?;
+
+    if let Some(scene) = event_data.get("message_scene").and_then(Value::as_str) {
+        if !is_private_scene(scene) {
+            return None;
+        }
+        return extract_u64_field(event_data, "peer_id");
+    }
+
+    match payload.get("event_type").and_then(Value::as_str)? {
+        "friend_request" => extract_u64_field(event_data, "initiator_id"),
+        "friend_nudge" | "friend_file_upload" => extract_u64_field(event_data, "user_id"),
+        _ => None,
+    }
+}
+
+fn is_allowed_by(mode: PermissionMode, blacklist: &[u64], whitelist: &[u64], id: u64) -> bool {
+    match mode {
+        PermissionMode::Blacklist => !blacklist.contains(&id),
+        PermissionMode::Whitelist => whitelist.contains(&id),
+    }
+}
+
+pub fn is_group_allowed(config: &PermissionConfig, plugin_id: &str, group_id: u64) -> bool {
+    match config.plugin_overrides.get(plugin_id) {
+        Some(rule) => is_allowed_by(
+            rule.mode,
+            &rule.blacklist_groups,
+            &rule.whitelist_groups,
+            group_id,
+        ),
+        None => is_allowed_by(
+            config.mode,
+            &config.blacklist_groups,
+            &config.whitelist_groups,
+            group_id,
+        ),
+    }
+}
+
+pub fn is_user_allowed(config: &PermissionConfig, plugin_id: &str, user_id: u64) -> bool {
+    match config.plugin_overrides.get(plugin_id) {
+        Some(rule) => is_allowed_by(
+            rule.user_mode,
+            &rule.blacklist_users,
+            &rule.whitelist_users,
+            user_id,
+        ),
+        None => is_allowed_by(
+            config.user_mode,
+            &config.blacklist_users,
+            &config.whitelist_users,
+            user_id,
+        ),
     }
 }
 
@@ -183,16 +390,79 @@ pub fn permission_mode_name(mode: PermissionMode) -> &'static str {
     }
 }
 
+/// 查找好友请求发起者时获取的请求条数
+const FRIEND_REQUEST_LOOKUP_LIMIT: usize = 100;
+
+/// 通过 `get_friend_requests` 查询好友请求发起者的 QQ 号。
+/// 该接口只能按 `limit` 获取最新的请求、不能翻页，因此只在最新的 `FRIEND_REQUEST_LOOKUP_LIMIT` 条请求中查找，
+/// 更早的请求会返回说明这一限制的错误
+pub async fn resolve_friend_request_initiator(
+    client: &reqwest::Client,
+    config: &BotConfig,
+    initiator_uid: &str,
+    is_filtered: bool,
+) -> Result<u64, String> {
+    let api_url = format!("{}/get_friend_requests", config.get_api_url());
+
+    let mut request_builder = client
+        .post(&api_url)
+        .header("Content-Type", "application/json")
+        .timeout(Duration::from_secs(5));
+
+    if let Some(token_str) = config.token.as_deref() {
+        request_builder = request_builder.header("Authorization", format!("Bearer {}", token_str));
+    }
+
+    let response = request_builder
+        .body(
+            serde_json::json!({
+                "limit": FRIEND_REQUEST_LOOKUP_LIMIT,
+                "is_filtered": is_filtered,
+            })
+            .to_string(),
+        )
+        .send()
+        .await
+        .map_err(|e| format!("API request failed: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(format!("API returned HTTP {}", response.status()));
+    }
+
+    let body = response
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read API response: {}", e))?;
+    let payload: Value =
+        serde_json::from_str(&body).map_err(|e| format!("Failed to parse API response: {}", e))?;
+
+    let requests = payload
+        .get("data")
+        .and_then(|data| data.get("requests"))
+        .and_then(Value::as_array)
+        .ok_or_else(|| "API response missing data.requests".to_string())?;
+
+    let request = requests
+        .iter()
+        .find(|request| request.get("initiator_uid").and_then(Value::as_str) == Some(initiator_uid))
+        .ok_or_else(|| {
+            format!(
+                "Friend request from {} not found among the latest {} {}requests",
+                initiator_uid,
+                FRIEND_REQUEST_LOOKUP_LIMIT,
+                if is_filtered { "filtered " } else { "" },
+            )
+        })?;
+    extract_u64_field(request, "initiator_id")
+        .ok_or_else(|| "Friend request is missing initiator_id".to_string())
+}
+
 async fn fetch_group_options_from_config(
     config: &BotConfig,
 ) -> Result<Vec<PermissionGroupOption>, String> {
     let api_url = format!("{}/get_group_list", config.get_api_url());
 
-    let client = reqwest::Client::builder()
-        .no_proxy()
-        .timeout(Duration::from_secs(5))
-        .build()
-        .map_err(|e| format!("Failed to build reqwest client: {}", e))?;
+    let client = config.build_http_client(Some(Duration::from_secs(5)))?;
 
     let mut request_builder = client
         .post(&api_url)
@@ -281,12 +551,35 @@ pub async fn get_permission_config(
     })
 }
 
+/// 汇总所有已连接账号所在的群，同一个群只保留一次
 #[get("/permissions/group_options")]
 pub async fn get_permission_group_options(
-    bot_state: &State<Arc<crate::server::BotConnectionState>>,
-    bot_config_state: &State<Arc<RwLock<BotConfig>>>,
+    registry: &State<Arc<BotRegistry>>,
 ) -> Json<ApiResponse<PermissionGroupOptionsData>> {
-    if !bot_state.is_connected.load(Ordering::SeqCst) {
+    let mut connected = false;
+    let mut groups: Vec<PermissionGroupOption> = Vec::new();
+    let mut errors = Vec::new();
+
+    for bot in registry.list().await {
+        if !bot.is_connected.load(Ordering::SeqCst) {
+            continue;
+        }
+        connected = true;
+
+        let config = bot.config.read().await.clone();
+        match fetch_group_options_from_config(&config).await {
+            Ok(options) => {
+                for option in options {
+                    if !groups.iter().any(|group| group.group_id == option.group_id) {
+                        groups.push(option);
+                    }
+                }
+            }
+            Err(message) => errors.push(format!("{}: {}", bot.id, message)),
+        }
+    }
+
+    if !connected {
         return Json(ApiResponse {
             retcode: 0,
             data: PermissionGroupOptionsData {
@@ -297,29 +590,24 @@ pub async fn get_permission_group_options(
         });
     }
 
-    let config = bot_config_state.read().await.clone();
-    match fetch_group_options_from_config(&config).await {
-        Ok(groups) => Json(ApiResponse {
-            retcode: 0,
-            data: PermissionGroupOptionsData {
-                groups,
-                connected: true,
-                message: String::new(),
-            },
-        }),
-        Err(message) => Json(ApiResponse {
-            retcode: 1,
-            data: PermissionGroupOptionsData {
-                groups: Vec::new(),
-                connected: true,
-                message,
-            },
-        }),
-    }
+    groups.sort_by(|left, right| left.group_name.cmp(&right.group_name));
+    Json(ApiResponse {
+        retcode: if groups.is_empty() && !errors.is_empty() {
+            1
+        } else {
+            0
+        },
+        data: PermissionGroupOptionsData {
+            groups,
+            connected: true,
+            message: errors.join("; "),
+        },
+    })
 }
 
 #[post("/permissions/save_config", format = "json", data = "<config>")]
 pub async fn save_permission_config(
+    _webui: WebUiCaller,
     config: Json<PermissionConfig>,
     permission_config_state: &State<Arc<RwLock<PermissionConfig>>>,
 ) -> Json<ApiResponse<String>> {
@@ -0,0 +1,472 @@
+use crate::runtime;
+use crate::server::api::{ApiResponse, WebUiCaller};
+use rocket::{get, post, serde::json::Json, State};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, RwLock};
+
+/// 令牌桶数量超过该值时清理长时间未使用的桶，避免按群计数的桶无限增长
+const MAX_IDLE_BUCKETS: usize = 4096;
+/// 每秒恢复次数的下限，约 17 分钟恢复一次，更低的速率视为配置错误
+const MIN_PER_SECOND: f64 = 0.001;
+
+/// 令牌桶规则：最多积攒 `burst` 个令牌，每秒补充 `per_second` 个
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RateLimitRule {
+    pub burst: u32,
+    pub per_second: f64,
+}
+
+impl RateLimitRule {
+    fn is_valid(&self) -> bool {
+        self.burst > 0 && self.per_second.is_finite() && self.per_second >= MIN_PER_SECOND
+    }
+}
+
+/// 未配置时不限流，由运维人员在 WebUI 中开启并设置规则
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RateLimitConfig {
+    /// 配置文件中省略该字段时同样视为关闭
+    #[serde(default)]
+    pub enabled: bool,
+    /// 每个插件所有 API 调用共享的限制
+    #[serde(default)]
+    pub plugin: Option<RateLimitRule>,
+    /// 每个插件对指定 API 的限制
+    #[serde(default)]
+    pub apis: HashMap<String, RateLimitRule>,
+    /// 每个插件对同一个目标群、同一个 API 的限制
+    #[serde(default)]
+    pub group: Option<RateLimitRule>,
+}
+
+impl RateLimitConfig {
+    /// 丢弃无效规则（`burst` 为 0 或每秒恢复次数低于 0.001）
+    pub fn normalized(mut self) -> Self {
+        self.plugin = self.plugin.filter(RateLimitRule::is_valid);
+        self.group = self.group.filter(RateLimitRule::is_valid);
+        self.apis
+            .retain(|api, rule| !api.trim().is_empty() && rule.is_valid());
+        self
+    }
+}
+
+/// 触发限流时的说明
+#[derive(Debug, Clone)]
+pub struct RateLimitExceeded {
+    pub scope: String,
+    pub retry_after: Duration,
+}
+
+impl std::fmt::Display for RateLimitExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Rate limit exceeded for {}, retry after {} ms",
+            self.scope,
+            self.retry_after.as_millis()
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum BucketKey {
+    Plugin(String),
+    Api(String, String),
+    Group(String, String, u64),
+}
+
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl Bucket {
+    fn refill(&mut self, rule: &RateLimitRule, now: Instant) {
+        let elapsed = now.duration_since(self.updated_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rule.per_second).min(rule.burst as f64);
+        self.updated_at = now;
+    }
+}
+
+/// 按插件 / API / 目标群维护的令牌桶
+#[derive(Default)]
+pub struct RateLimiter {
+    buckets: Mutex<HashMap<BucketKey, Bucket>>,
+}
+
+impl RateLimiter {
+    /// 检查并消耗一次调用所需的令牌
+    ///
+    /// 只有所有适用的桶都有剩余令牌时才会扣减，被拒绝的调用不会消耗任何桶。
+    pub async fn try_acquire(
+        &self,
+        config: &RateLimitConfig,
+        plugin_id: &str,
+        api: &str,
+        group_id: Option<u64>,
+    ) -> Result<(), RateLimitExceeded> {
+        if !config.enabled {
+            return Ok(());
+        }
+
+        let mut checks: Vec<(BucketKey, RateLimitRule, String)> = Vec::with_capacity(3);
+        if let Some(rule) = config.plugin {
+            checks.push((
+                BucketKey::Plugin(plugin_id.to_string()),
+                rule,
+                format!("plugin '{}'", plugin_id),
+            ));
+        }
+        if let Some(rule) = config.apis.get(api) {
+            checks.push((
+                BucketKey::Api(plugin_id.to_string(), api.to_string()),
+                *rule,
+                format!("API '{}'", api),
+            ));
+        }
+        if let (Some(rule), Some(group_id)) = (config.group, group_id) {
+            checks.push((
+                BucketKey::Group(plugin_id.to_string(), api.to_string(), group_id),
+                rule,
+                format!("API '{}' in group {}", api, group_id),
+            ));
+        }
+        if checks.is_empty() {
+            return Ok(());
+        }
+
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().await;
+
+        if buckets.len() > MAX_IDLE_BUCKETS {
+            buckets.retain(|_, bucket| {
+                now.duration_since(bucket.updated_at) < Duration::from_secs(600)
+            });
+        }
+
+        for (key, rule, scope) in &checks {
+            let bucket = buckets.entry(key.clone()).or_insert(Bucket {
+                tokens: rule.burst as f64,
+                updated_at: now,
+            });
+            bucket.refill(rule, now);
+
+            if bucket.tokens < 1.0 {
+                let missing = 1.0 - bucket.tokens;
+                return Err(RateLimitExceeded {
+                    scope: scope.clone(),
+                    retry_after: Duration::try_from_secs_f64(missing / rule.per_second)
+                        .unwrap_or(Duration::MAX),
+                });
+            }
+        }
+
+        for (key, _, _) in &checks {
+            if let Some(bucket) = buckets.get_mut(key) {
+                bucket.tokens -= 1.0;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 配置变更后清空所有桶
+    pub async fn reset(&self) {
+        self.buckets.lock().await.clear();
+    }
+}
+
+fn rate_limit_config_path(exe_dir: &Path) -> std::path::PathBuf {
+    exe_dir.join("config").join("rate_limits.json")
+}
+
+pub fn load_rate_limit_config_from_disk(exe_dir: &Path) -> RateLimitConfig {
+    let config_file = rate_limit_config_path(exe_dir);
+
+    let Ok(content) = std::fs::read_to_string(&config_file) else {
+        return RateLimitConfig::default();
+    };
+
+    serde_json::from_str::<RateLimitConfig>(&content)
+        .unwrap_or_default()
+        .normalized()
+}
+
+#[get("/rate_limits/get_config")]
+pub async fn get_rate_limit_config(
+    rate_limit_config_state: &State<Arc<RwLock<RateLimitConfig>>>,
+) -> Json<ApiResponse<RateLimitConfig>> {
+    Json(ApiResponse {
+        retcode: 0,
+        data: rate_limit_config_state.read().await.clone(),
+    })
+}
+
+#[post("/rate_limits/save_config", format = "json", data = "<config>")]
+pub async fn save_rate_limit_config(
+    _webui: WebUiCaller,
+    config: Json<RateLimitConfig>,
+    rate_limit_config_state: &State<Arc<RwLock<RateLimitConfig>>>,
+    rate_limiter: &State<Arc<RateLimiter>>,
+) -> Json<ApiResponse<String>> {
+    let config_inner = config.into_inner().normalized();
+    let json_str = match serde_json::to_string_pretty(&config_inner) {
+        Ok(value) => value,
+        Err(e) => {
+            log_error!("Failed to serialize rate limit config: {}", e);
+            return Json(ApiResponse {
+                retcode: 1,
+                data: format!("Failed to serialize rate limit config: {}", e),
+            });
+        }
+    };
+
+    let exe_dir = runtime::get_exe_dir();
+    let config_file = rate_limit_config_path(&exe_dir);
+
+    if let Some(config_dir) = config_file.parent() {
+        if let Err(e) = tokio::fs::create_dir_all(config_dir).await {
+            log_error!("Failed to create config directory: {}", e);
+            return Json(ApiResponse {
+                retcode: 1,
+                data: format!("Failed to create config directory: {}", e),
+            });
+        }
+    }
+
+    if let Err(e) = tokio::fs::write(&config_file, json_str).await {
+        log_error!("Failed to write rate limit config: {}", e);
+        return Json(ApiResponse {
+            retcode: 1,
+            data: format!("Failed to write rate limit config: {}", e),
+        });
+    }
+
+    *rate_limit_config_state.write().await = config_inner;
+    rate_limiter.reset().await;
+
+    Json(ApiResponse {
+        retcode: 0,
+        data: "Rate limit config saved".to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 补充速度足够慢，测试期间可以忽略补充的令牌
+    fn rule(burst: u32) -> RateLimitRule {
+        RateLimitRule {
+            burst,
+            per_second: 0.001,
+        }
+    }
+
+    fn enabled(config: RateLimitConfig) -> RateLimitConfig {
+        RateLimitConfig {
+            enabled: true,
+            ..config
+        }
+    }
+
+    #[test]
+    fn bucket_refills_up_to_burst() {
+        let rule = RateLimitRule {
+            burst: 5,
+            per_second: 2.0,
+        };
+        let start = Instant::now();
+        let mut bucket = Bucket {
+            tokens: 0.0,
+            updated_at: start,
+        };
+
+        bucket.refill(&rule, start + Duration::from_millis(1500));
+        assert!((bucket.tokens - 3.0).abs() < 1e-9);
+
+        bucket.refill(&rule, start + Duration::from_secs(60));
+        assert_eq!(bucket.tokens, 5.0);
+    }
+
+    #[tokio::test]
+    async fn default_config_does_not_limit() {
+        let limiter = RateLimiter::default();
+        let config = RateLimitConfig::default();
+        for _ in 0..100 {
+            assert!(limiter
+                .try_acquire(&config, "p", "send_group_message", Some(1))
+                .await
+                .is_ok());
+        }
+    }
+
+    #[tokio::test]
+    async fn disabled_config_ignores_rules() {
+        let limiter = RateLimiter::default();
+        let config = RateLimitConfig {
+            enabled: false,
+            plugin: Some(rule(1)),
+            ..RateLimitConfig::default()
+        };
+        for _ in 0..3 {
+            assert!(limiter.try_acquire(&config, "p", "api", None).await.is_ok());
+        }
+    }
+
+    #[tokio::test]
+    async fn plugin_bucket_allows_burst_then_rejects() {
+        let limiter = RateLimiter::default();
+        let config = enabled(RateLimitConfig {
+            plugin: Some(rule(2)),
+            ..RateLimitConfig::default()
+        });
+
+        assert!(limiter.try_acquire(&config, "a", "x", None).await.is_ok());
+        assert!(limiter.try_acquire(&config, "a", "y", None).await.is_ok());
+        let err = limiter
+            .try_acquire(&config, "a", "x", None)
+            .await
+            .unwrap_err();
+        assert_eq!(err.scope, "plugin 'a'");
+        assert!(err.retry_after > Duration::ZERO);
+
+        // 每个插件有独立的桶
+        assert!(limiter.try_acquire(&config, "b", "x", None).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn rejected_call_consumes_no_tokens() {
+        let limiter = RateLimiter::default();
+        let config = enabled(RateLimitConfig {
+            plugin: Some(rule(2)),
+            apis: HashMap::from([("send".to_string(), rule(1))]),
+            ..RateLimitConfig::default()
+        });
+
+        assert!(limiter
+            .try_acquire(&config, "p", "send", None)
+            .await
+            .is_ok());
+        let err = limiter
+            .try_acquire(&config, "p", "send", None)
+            .await
+            .unwrap_err();
+        assert_eq!(err.scope, "API 'send'");
+
+        // 被 API 规则拒绝的调用没有扣减插件桶，其他 API 仍可调用一次
+        assert!(limiter
+            .try_acquire(&config, "p", "other", None)
+            .await
+            .is_ok());
+        assert!(limiter
+            .try_acquire(&config, "p", "other", None)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn group_buckets_are_per_group() {
+        let limiter = RateLimiter::default();
+        let config = enabled(RateLimitConfig {
+            group: Some(rule(1)),
+            ..RateLimitConfig::default()
+        });
+
+        assert!(limiter
+            .try_acquire(&config, "p", "send", Some(1))
+            .await
+            .is_ok());
+        assert!(limiter
+            .try_acquire(&config, "p", "send", Some(1))
+            .await
+            .is_err());
+        assert!(limiter
+            .try_acquire(&config, "p", "send", Some(2))
+            .await
+            .is_ok());
+        // 没有目标群的调用不受群规则限制
+        assert!(limiter
+            .try_acquire(&config, "p", "send", None)
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn reset_refills_all_buckets() {
+        let limiter = RateLimiter::default();
+        let config = enabled(RateLimitConfig {
+            plugin: Some(rule(1)),
+            ..RateLimitConfig::default()
+        });
+
+        assert!(limiter.try_acquire(&config, "p", "x", None).await.is_ok());
+        assert!(limiter.try_acquire(&config, "p", "x", None).await.is_err());
+        limiter.reset().await;
+        assert!(limiter.try_acquire(&config, "p", "x", None).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn retry_after_does_not_overflow_for_tiny_rates() {
+        let limiter = RateLimiter::default();
+        let config = enabled(RateLimitConfig {
+            plugin: Some(RateLimitRule {
+                burst: 1,
+                per_second: 1e-300,
+            }),
+            ..RateLimitConfig::default()
+        });
+
+        assert!(limiter.try_acquire(&config, "p", "x", None).await.is_ok());
+        let err = limiter
+            .try_acquire(&config, "p", "x", None)
+            .await
+            .unwrap_err();
+        assert_eq!(err.retry_after, Duration::MAX);
+    }
+
+    #[test]
+    fn omitted_enabled_field_means_disabled() {
+        let config: RateLimitConfig =
+            serde_json::from_str(r#"{"plugin": {"burst": 1, "perSecond": 1}}"#).unwrap();
+        assert!(!config.enabled);
+        assert!(!RateLimitConfig::default().enabled);
+    }
+
+    #[test]
+    fn normalized_drops_invalid_rules() {
+        let config = RateLimitConfig {
+            enabled: true,
+            plugin: Some(RateLimitRule {
+                burst: 0,
+                per_second: 1.0,
+            }),
+            group: Some(RateLimitRule {
+                burst: 1,
+                per_second: f64::NAN,
+            }),
+            apis: HashMap::from([
+                (" ".to_string(), rule(1)),
+                ("ok".to_string(), rule(1)),
+                (
+                    "slow".to_string(),
+                    RateLimitRule {
+                        burst: 1,
+                        per_second: 1e-300,
+                    },
+                ),
+            ]),
+        }
+        .normalized();
+        assert!(config.plugin.is_none());
+        assert!(config.group.is_none());
+        assert_eq!(config.apis.len(), 1);
+        assert!(config.apis.contains_key("ok"));
+    }
+}
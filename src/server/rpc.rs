@@ -0,0 +1,288 @@
+use crate::plus::PluginManager;
+use crate::server::api::{ApiResponse, PluginCaller};
+use rocket::{get, post, serde::json::Json, State};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use ws as rocket_ws;
+
+const DEFAULT_TIMEOUT_MS: u64 = 10_000;
+const MAX_TIMEOUT_MS: u64 = 120_000;
+/// 每个提供方连接上排队等待发送的调用数量上限
+const MAX_QUEUED_CALLS: usize = 64;
+
+type RpcResult = Result<serde_json::Value, String>;
+
+/// 推送给提供方插件的一次调用
+#[derive(Serialize)]
+struct RpcRequest {
+    id: u64,
+    /// 发起调用的插件 ID
+    from: String,
+    method: String,
+    params: serde_json::Value,
+}
+
+/// 提供方插件对一次调用的回复，`error` 不为空时表示调用失败
+#[derive(Deserialize)]
+struct RpcReply {
+    id: u64,
+    #[serde(default)]
+    result: serde_json::Value,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct RpcCallRequest {
+    /// `插件ID.方法名`
+    pub method: String,
+    #[serde(default)]
+    pub params: serde_json::Value,
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+/// 一个插件通过 `/api/rpc/serve` 连接注册的方法
+struct RpcService {
+    connection_id: u64,
+    methods: HashSet<String>,
+    requests: mpsc::Sender<RpcRequest>,
+    pending: HashMap<u64, oneshot::Sender<RpcResult>>,
+}
+
+/// 插件间 RPC 注册表，由主程序在插件之间转发调用和回复
+#[derive(Default)]
+pub struct PluginRpc {
+    services: Mutex<HashMap<String, RpcService>>,
+    next_id: AtomicU64,
+}
+
+impl PluginRpc {
+    /// 注册插件提供的方法，同一插件的旧连接会被替换，其未完成的调用直接失败
+    fn serve(
+        &self,
+        plugin_id: &str,
+        methods: HashSet<String>,
+    ) -> (u64, mpsc::Receiver<RpcRequest>) {
+        let connection_id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (requests, rx) = mpsc::channel(MAX_QUEUED_CALLS);
+
+        let mut services = self.services.lock().unwrap_or_else(|e| e.into_inner());
+        services.insert(
+            plugin_id.to_string(),
+            RpcService {
+                connection_id,
+                methods,
+                requests,
+                pending: HashMap::new(),
+            },
+        );
+        (connection_id, rx)
+    }
+
+    fn unregister(&self, plugin_id: &str, connection_id: u64) {
+        let mut services = self.services.lock().unwrap_or_else(|e| e.into_inner());
+        if services
+            .get(plugin_id)
+            .is_some_and(|service| service.connection_id == connection_id)
+        {
+            services.remove(plugin_id);
+        }
+    }
+
+    fn reply(&self, plugin_id: &str, connection_id: u64, reply: RpcReply) {
+        let mut services = self.services.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(service) = services.get_mut(plugin_id) else {
+            return;
+        };
+        if service.connection_id != connection_id {
+            return;
+        }
+        if let Some(tx) = service.pending.remove(&reply.id) {
+            let _ = tx.send(match reply.error {
+                Some(error) => Err(error),
+                None => Ok(reply.result),
+            });
+        }
+    }
+
+    fn forget(&self, plugin_id: &str, id: u64) {
+        let mut services = self.services.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(service) = services.get_mut(plugin_id) {
+            service.pending.remove(&id);
+        }
+    }
+
+    /// 调用 `插件ID.方法名`，提供方插件必须在运行且已注册该方法
+    pub async fn call(
+        &self,
+        manager: &PluginManager,
+        from: &str,
+        request: RpcCallRequest,
+    ) -> RpcResult {
+        let (plugin_id, method) = request
+            .method
+            .rsplit_once('.')
+            .filter(|(plugin_id, method)| !plugin_id.is_empty() && is_valid_method(method))
+            .ok_or_else(|| format!("Invalid method '{}'", request.method))?;
+
+        if !manager.is_plugin_running(plugin_id).await {
+            return Err(format!("Plugin '{}' is not running", plugin_id));
+        }
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = oneshot::channel();
+        let requests = {
+            let mut services = self.services.lock().unwrap_or_else(|e| e.into_inner());
+            let service = services
+                .get_mut(plugin_id)
+                .ok_or_else(|| format!("Plugin '{}' does not serve RPC", plugin_id))?;
+            if !service.methods.contains(method) {
+                return Err(format!(
+                    "Method '{}' is not registered by plugin '{}'",
+                    method, plugin_id
+                ));
+            }
+            service.pending.insert(id, tx);
+            service.requests.clone()
+        };
+
+        let params = if request.params.is_null() {
+            serde_json::json!({})
+        } else {
+            request.params
+        };
+        let sent = requests.try_send(RpcRequest {
+            id,
+            from: from.to_string(),
+            method: method.to_string(),
+            params,
+        });
+        if sent.is_err() {
+            self.forget(plugin_id, id);
+            return Err(format!("Plugin '{}' is busy", plugin_id));
+        }
+
+        let timeout = Duration::from_millis(
+            request
+                .timeout_ms
+                .unwrap_or(DEFAULT_TIMEOUT_MS)
+                .clamp(1, MAX_TIMEOUT_MS),
+        );
+        match tokio::time::timeout(timeout, rx).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(_)) => Err(format!(
+                "Plugin '{}' disconnected before replying",
+                plugin_id
+            )),
+            Err(_) => {
+                self.forget(plugin_id, id);
+                Err(format!(
+                    "Call to '{}' timed out after {} ms",
+                    request.method,
+                    timeout.as_millis()
+                ))
+            }
+        }
+    }
+}
+
+fn is_valid_method(method: &str) -> bool {
+    !method.is_empty()
+        && method
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[post("/rpc/call", data = "<request>")]
+pub async fn rpc_call(
+    caller: PluginCaller,
+    request: Json<RpcCallRequest>,
+    rpc: &State<Arc<PluginRpc>>,
+    manager: &State<Arc<PluginManager>>,
+) -> Json<ApiResponse<serde_json::Value>> {
+    match rpc
+        .call(manager, &caller.plugin_id, request.into_inner())
+        .await
+    {
+        Ok(result) => Json(ApiResponse {
+            retcode: 0,
+            data: result,
+        }),
+        Err(e) => Json(ApiResponse {
+            retcode: 1,
+            data: serde_json::Value::String(e),
+        }),
+    }
+}
+
+/// 提供方插件通过该 WebSocket 注册方法并接收调用，连接断开后注册自动失效
+#[get("/rpc/serve?<methods>")]
+pub fn rpc_serve(
+    ws: rocket_ws::WebSocket,
+    methods: &str,
+    caller: PluginCaller,
+    rpc: &State<Arc<PluginRpc>>,
+) -> Result<rocket_ws::Channel<'static>, rocket::http::Status> {
+    let methods: HashSet<String> = methods
+        .split(',')
+        .map(str::trim)
+        .filter(|method| !method.is_empty())
+        .map(str::to_string)
+        .collect();
+    if methods.is_empty() || !methods.iter().all(|method| is_valid_method(method)) {
+        return Err(rocket::http::Status::BadRequest);
+    }
+
+    let rpc = rpc.inner().clone();
+    let plugin_id = caller.plugin_id;
+
+    Ok(ws.channel(move |mut stream| {
+        Box::pin(async move {
+            use rocket::futures::{SinkExt, StreamExt};
+
+            let mut names: Vec<&str> = methods.iter().map(String::as_str).collect();
+            names.sort_unstable();
+            log_info!("插件 {} 注册了 RPC 方法: {}", plugin_id, names.join(", "));
+            let (connection_id, mut rx) = rpc.serve(&plugin_id, methods);
+
+            loop {
+                tokio::select! {
+                    frame = stream.next() => {
+                        let text = match frame {
+                            Some(Ok(rocket_ws::Message::Text(text))) => text,
+                            Some(Ok(m)) if m.is_close() => break,
+                            Some(Ok(_)) => continue,
+                            Some(Err(_)) | None => break,
+                        };
+                        match serde_json::from_str::<RpcReply>(&text) {
+                            Ok(reply) => rpc.reply(&plugin_id, connection_id, reply),
+                            Err(e) => {
+                                log_warn!("插件 {} 的 RPC 回复格式错误: {}", plugin_id, e);
+                            }
+                        }
+                    }
+                    request = rx.recv() => {
+                        // 同一插件建立了新的连接，旧连接不再接收调用
+                        let Some(request) = request else {
+                            break;
+                        };
+                        let Ok(text) = serde_json::to_string(&request) else {
+                            continue;
+                        };
+                        if stream.send(rocket_ws::Message::Text(text)).await.is_err() {
+                            break;
+                        }
+                    }
+                }
+            }
+
+            rpc.unregister(&plugin_id, connection_id);
+            Ok(())
+        })
+    }))
+}
@@ -0,0 +1,182 @@
+use crate::server::api::{BotConfig, BotScheme, BotTlsConfig, EventTransport};
+use futures_util::stream::BoxStream;
+use futures_util::StreamExt;
+use std::sync::Mutex;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::HeaderValue;
+use tokio_tungstenite::tungstenite::Message;
+
+type WsStream =
+    tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
+
+/// 访问上游 Milky 服务的 `reqwest` 客户端，TLS 设置变化时自动重建
+#[derive(Default)]
+pub struct UpstreamClient {
+    cached: Mutex<Option<(BotTlsConfig, reqwest::Client)>>,
+}
+
+impl UpstreamClient {
+    pub fn get(&self, config: &BotConfig) -> Result<reqwest::Client, String> {
+        let mut cached = self.cached.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((tls, client)) = cached.as_ref() {
+            if *tls == config.tls {
+                return Ok(client.clone());
+            }
+        }
+
+        let client = config.build_http_client(None)?;
+        *cached = Some((config.tls.clone(), client.clone()));
+        Ok(client)
+    }
+}
+
+/// 上游 Milky 服务推送的一条事件
+pub struct UpstreamEvent {
+    /// SSE 的 `event:` 字段，WebSocket 下始终为 `None`
+    pub event: Option<String>,
+    pub data: String,
+}
+
+/// 上游 Milky 事件流，屏蔽 SSE 与 WebSocket 两种传输方式的差异
+pub enum UpstreamEventStream {
+    Sse(SseReader),
+    WebSocket(Box<WsStream>),
+}
+
+impl UpstreamEventStream {
+    /// 按 `BotConfig` 中配置的传输方式连接上游事件流
+    pub async fn connect(client: &reqwest::Client, config: &BotConfig) -> Result<Self, String> {
+        match config.event_transport {
+            EventTransport::Sse => connect_sse(client, config).await,
+            EventTransport::WebSocket => connect_websocket(config).await,
+        }
+    }
+
+    /// 读取下一条事件，连接断开或出错时返回 `None`
+    ///
+    /// 可以安全地在 `tokio::select!` 中被取消，不会丢失已读取的数据。
+    pub async fn next_event(&mut self) -> Option<UpstreamEvent> {
+        match self {
+            Self::Sse(reader) => reader.next_event().await,
+            Self::WebSocket(ws) => loop {
+                match ws.next().await? {
+                    Ok(Message::Text(data)) => {
+                        return Some(UpstreamEvent { event: None, data });
+                    }
+                    Ok(Message::Binary(bytes)) => {
+                        if let Ok(data) = String::from_utf8(bytes) {
+                            return Some(UpstreamEvent { event: None, data });
+                        }
+                    }
+                    Ok(Message::Close(_)) | Err(_) => return None,
+                    Ok(_) => {}
+                }
+            },
+        }
+    }
+}
+
+async fn connect_sse(
+    client: &reqwest::Client,
+    config: &BotConfig,
+) -> Result<UpstreamEventStream, String> {
+    let mut request_builder = client
+        .get(config.get_event_url())
+        .header("Accept", "text/event-stream")
+        .header("Cache-Control", "no-cache");
+
+    if let Some(token) = config.token.as_deref() {
+        request_builder = request_builder.header("Authorization", format!("Bearer {}", token));
+    }
+
+    let response = request_builder
+        .send()
+        .await
+        .map_err(|e| format!("错误: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(format!("HTTP {}", response.status()));
+    }
+
+    Ok(UpstreamEventStream::Sse(SseReader {
+        stream: response
+            .bytes_stream()
+            .map(|chunk| chunk.map(|bytes| bytes.to_vec()))
+            .boxed(),
+        buffer: Vec::new(),
+        current_event: None,
+        data_lines: Vec::new(),
+    }))
+}
+
+async fn connect_websocket(config: &BotConfig) -> Result<UpstreamEventStream, String> {
+    let mut request = config
+        .get_event_ws_url()
+        .into_client_request()
+        .map_err(|e| format!("错误: {}", e))?;
+
+    if let Some(token) = config.token.as_deref() {
+        let value = HeaderValue::from_str(&format!("Bearer {}", token))
+            .map_err(|e| format!("错误: {}", e))?;
+        request.headers_mut().insert("Authorization", value);
+    }
+
+    let connector = match config.scheme {
+        BotScheme::Https => Some(tokio_tungstenite::Connector::NativeTls(
+            config.tls.build_tls_connector()?,
+        )),
+        BotScheme::Http => None,
+    };
+
+    let (ws, _) = tokio_tungstenite::connect_async_tls_with_config(request, None, false, connector)
+        .await
+        .map_err(|e| format!("错误: {}", e))?;
+
+    Ok(UpstreamEventStream::WebSocket(Box::new(ws)))
+}
+
+/// 增量解析 SSE 字节流
+pub struct SseReader {
+    stream: BoxStream<'static, reqwest::Result<Vec<u8>>>,
+    buffer: Vec<u8>,
+    current_event: Option<String>,
+    data_lines: Vec<String>,
+}
+
+impl SseReader {
+    async fn next_event(&mut self) -> Option<UpstreamEvent> {
+        loop {
+            while let Some(pos) = self.buffer.iter().position(|b| *b == b'\n') {
+                let raw: Vec<u8> = self.buffer.drain(..=pos).collect();
+                let mut line = String::from_utf8_lossy(&raw[..pos]).into_owned();
+                if line.ends_with('\r') {
+                    line.pop();
+                }
+
+                if line.is_empty() {
+                    if self.data_lines.is_empty() && self.current_event.is_none() {
+                        continue;
+                    }
+
+                    let data = self.data_lines.join("\n");
+                    self.data_lines.clear();
+                    return Some(UpstreamEvent {
+                        event: self.current_event.take(),
+                        data,
+                    });
+                }
+
+                if let Some(v) = line.strip_prefix("event:") {
+                    self.current_event = Some(v.trim().to_string());
+                } else if let Some(v) = line.strip_prefix("data:") {
+                    self.data_lines.push(v.trim_start().to_string());
+                }
+            }
+
+            match self.stream.next().await? {
+                Ok(bytes) => self.buffer.extend_from_slice(&bytes),
+                Err(_) => return None,
+            }
+        }
+    }
+}
@@ -278,7 +278,7 @@ pub fn run_app(
 ) {
     use tao::event_loop::{ControlFlow, EventLoopBuilder};
 
-    let base_url = format!("http://127.0.0.1:{}", port);
+    let base_url = server_state.webui_session.login_url(port);
     let event_loop = EventLoopBuilder::<UserEvent>::with_user_event().build();
     let proxy = event_loop.create_proxy();
 